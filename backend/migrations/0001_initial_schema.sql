@@ -1,12 +1,10 @@
--- db/init.sql
-
 -- ===================================================================
--- 🗄️ Midnight Scavenger - Database initialization script
+-- Migration 0001 : schéma initial du backend de statistiques
 -- ===================================================================
--- Ce script est exécuté automatiquement lors du premier démarrage du
--- conteneur Postgres. Il prépare les tables nécessaires au backend
--- de collecte de statistiques et de journalisation des interactions
--- avec les API Midnight.
+-- Appliquée au démarrage du backend par `sqlx::migrate!`, qui gère seul le
+-- schéma (plus aucun script d'initialisation Postgres). Idempotente : sur une
+-- base déjà pourvue de ces tables, elle ne fait qu'enregistrer son passage
+-- dans `_sqlx_migrations`.
 -- ===================================================================
 
 -- ========================
@@ -19,7 +17,8 @@ CREATE TABLE IF NOT EXISTS stats (
 --    wallet_addr TEXT NOT NULL,
     hash_rate DOUBLE PRECISION NOT NULL,
     timestamp TIMESTAMP NOT NULL DEFAULT NOW(),
-    description TEXT
+    description TEXT,
+    wallet_rates JSONB                 -- hash rate par wallet : { "<préfixe adresse>": H/s }
 );
 
 -- Index utiles
@@ -75,7 +74,3 @@ SELECT DISTINCT ON (wallet_addr, endpoint)
     api_response
 FROM api_return
 ORDER BY wallet_addr, endpoint, timestamp DESC;
-
--- ===================================================================
--- ✅ Fin du script d'initialisation
--- ===================================================================
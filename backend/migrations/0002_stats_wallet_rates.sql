@@ -0,0 +1,8 @@
+-- ===================================================================
+-- Migration 0002 : hash rate par wallet dans `stats`
+-- ===================================================================
+-- Ajoute la colonne sur les bases créées avant son introduction
+-- (no-op si 0001 l'a déjà créée).
+-- ===================================================================
+
+ALTER TABLE stats ADD COLUMN IF NOT EXISTS wallet_rates JSONB;
@@ -0,0 +1,10 @@
+-- ===================================================================
+-- Migration 0003 : compteurs d'appels API dans `stats`
+-- ===================================================================
+-- Appels réussis / échoués depuis la mesure précédente et taux d'erreur,
+-- pour rapprocher les baisses de hash rate des problèmes d'API.
+-- ===================================================================
+
+ALTER TABLE stats ADD COLUMN IF NOT EXISTS api_success BIGINT;
+ALTER TABLE stats ADD COLUMN IF NOT EXISTS api_failure BIGINT;
+ALTER TABLE stats ADD COLUMN IF NOT EXISTS api_error_rate DOUBLE PRECISION;
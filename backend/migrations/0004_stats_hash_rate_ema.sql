@@ -0,0 +1,8 @@
+-- ===================================================================
+-- Migration 0004 : hash rate lissé dans `stats`
+-- ===================================================================
+-- Moyenne mobile exponentielle du hash rate calculée par le mineur,
+-- stockée à côté de la mesure instantanée (`hash_rate`).
+-- ===================================================================
+
+ALTER TABLE stats ADD COLUMN IF NOT EXISTS hash_rate_ema DOUBLE PRECISION;
@@ -0,0 +1,24 @@
+-- ===================================================================
+-- Migration 0005 : une ligne de stats par wallet
+-- ===================================================================
+-- Les mineurs envoient, en plus de la mesure agrégée de l'instance
+-- (`wallet_addr` NULL), une mesure par wallet miné.
+-- ===================================================================
+
+ALTER TABLE stats ADD COLUMN IF NOT EXISTS wallet_addr TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_stats_wallet_addr_timestamp
+    ON stats (wallet_addr, timestamp DESC)
+    WHERE wallet_addr IS NOT NULL;
+
+-- Les lignes par wallet partagent `container_id` et `timestamp` avec la ligne
+-- agrégée : la vue ne garde que cette dernière pour le hashrate de l'instance.
+CREATE OR REPLACE VIEW latest_hashrate AS
+SELECT DISTINCT ON (container_id)
+    container_id,
+    miner_id,
+    hash_rate,
+    timestamp
+FROM stats
+WHERE wallet_addr IS NULL
+ORDER BY container_id, timestamp DESC;
@@ -1,31 +1,115 @@
 use axum::{
-    extract::{Json, State},
-    http::HeaderMap,
-    routing::post,
+    extract::{rejection::QueryRejection, FromRef, Json, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
     Router,
 };
+use dashmap::DashMap;
 use chrono::{DateTime, Utc, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use sqlx::{Pool, Postgres, postgres::PgPoolOptions};
+use sqlx::{Pool, Postgres, QueryBuilder, postgres::PgPoolOptions};
+use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
 use tracing::{info, warn, error};
 use tracing_subscriber;
 use sqlx::types::Json as sqlxJson;
+use tower::limit::ConcurrencyLimitLayer;
 
 // -------------------- STRUCTURES --------------------
 
+/// État partagé des handlers ; ceux qui n'ont besoin que du pool l'extraient via `FromRef`
+#[derive(Clone, FromRef)]
+struct AppState {
+    pool: Pool<Postgres>,
+    start_time: Instant,
+    rate_limiter: RateLimiter,
+}
+
+/// Seau de jetons d'un mineur : `tokens` disponibles au moment `last_refill`
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Limite les insertions à `per_minute` par `miner_id` (seau de `per_minute` jetons, rechargé en continu)
+#[derive(Clone)]
+struct RateLimiter {
+    buckets: Arc<DashMap<String, TokenBucket>>,
+    per_minute: f64,
+}
+
+impl RateLimiter {
+    fn new(per_minute: u32) -> Self {
+        Self { buckets: Arc::new(DashMap::new()), per_minute: per_minute.max(1) as f64 }
+    }
+
+    /// Consomme un jeton pour `miner_id` ; sinon, délai avant le prochain jeton disponible
+    fn check(&self, miner_id: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let refill_per_sec = self.per_minute / 60.0;
+        let mut bucket = self
+            .buckets
+            .entry(miner_id.to_string())
+            .or_insert_with(|| TokenBucket { tokens: self.per_minute, last_refill: now });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(self.per_minute);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / refill_per_sec))
+        }
+    }
+
+    /// Oublie les mineurs inactifs depuis `idle` (leur seau serait de toute façon plein)
+    fn prune(&self, idle: Duration) {
+        self.buckets.retain(|_, bucket| bucket.last_refill.elapsed() < idle);
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct Stat {
     container_id: String,
     miner_id: String,
+    /// Wallet de la mesure ; absent (NULL) pour la mesure agrégée de l'instance
+    #[serde(default)]
+    wallet_addr: Option<String>,
     hash_rate: f64,
+    /// Hash rate lissé (moyenne mobile exponentielle calculée par le mineur)
+    #[serde(default)]
+    hash_rate_ema: Option<f64>,
     timestamp: DateTime<Utc>,
-    description: Option<String>
+    description: Option<String>,
+    #[serde(default)]
+    wallet_rates: Option<HashMap<String, f64>>,
+    #[serde(default)]
+    api_success: Option<i64>,
+    #[serde(default)]
+    api_failure: Option<i64>,
+    #[serde(default)]
+    api_error_rate: Option<f64>,
+}
+
+/// Corps de `POST /stats/batch`
+#[derive(Debug, Deserialize)]
+struct StatBatch {
+    records: Vec<Stat>,
 }
 
+/// Nombre maximal de stats acceptées par `POST /stats/batch`
+const MAX_BATCH_RECORDS: usize = 1000;
+
+/// Âge au-delà duquel une stat reçue en lot est ignorée
+const MAX_BATCH_RECORD_AGE_HOURS: i64 = 24;
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ApiReturn {
     container_id: String,
@@ -38,6 +122,67 @@ struct ApiReturn {
     api_response: Option<Value>,
 }
 
+#[derive(Debug, Deserialize)]
+struct StatsQuery {
+    miner_id: Option<String>,
+    /// Mesures de ce wallet ; sans ce filtre, seules les mesures agrégées des instances
+    wallet_addr: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    page: Option<i64>,
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MinerSeriesQuery {
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SummaryQuery {
+    minutes: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LeaderboardQuery {
+    /// Fenêtre glissante : `30m`, `1h`, `7d`... (1h par défaut)
+    window: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct LeaderboardRow {
+    id: String,
+    avg_hash_rate: f64,
+    last_seen: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct HashRatePoint {
+    container_id: Option<String>,
+    hash_rate: f64,
+    timestamp: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+struct StatRecord {
+    id: i32,
+    container_id: Option<String>,
+    miner_id: Option<String>,
+    wallet_addr: Option<String>,
+    hash_rate: f64,
+    hash_rate_ema: Option<f64>,
+    timestamp: NaiveDateTime,
+    description: Option<String>,
+    wallet_rates: Option<Value>,
+    api_success: Option<i64>,
+    api_failure: Option<i64>,
+    api_error_rate: Option<f64>,
+}
+
 // -------------------- HELPERS --------------------
 
 fn get_bearer_token() -> String {
@@ -56,16 +201,79 @@ fn check_bearer(headers: &HeaderMap) -> bool {
     false
 }
 
+// Ajoute les clauses WHERE correspondant aux filtres présents
+fn push_stats_filters(qb: &mut QueryBuilder<'_, Postgres>, params: &StatsQuery) {
+    if let Some(miner_id) = &params.miner_id {
+        qb.push(" AND miner_id = ").push_bind(miner_id.clone());
+    }
+    match &params.wallet_addr {
+        Some(wallet_addr) => qb.push(" AND wallet_addr = ").push_bind(wallet_addr.clone()),
+        None => qb.push(" AND wallet_addr IS NULL"),
+    };
+    if let Some(from) = params.from {
+        qb.push(" AND timestamp >= ").push_bind(from.naive_utc());
+    }
+    if let Some(to) = params.to {
+        qb.push(" AND timestamp < ").push_bind(to.naive_utc());
+    }
+}
+
+type HandlerError = (StatusCode, Json<serde_json::Value>);
+
+fn unauthorized() -> HandlerError {
+    (StatusCode::UNAUTHORIZED, Json(serde_json::json!({"status": "error", "message": "Unauthorized"})))
+}
+
+fn bad_request(rejection: QueryRejection) -> HandlerError {
+    (StatusCode::BAD_REQUEST, Json(serde_json::json!({"status": "error", "message": rejection.body_text()})))
+}
+
+/// 429 avec `Retry-After` (en secondes, arrondi au supérieur)
+fn too_many_requests(miner_id: &str, retry_after: Duration) -> Response {
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    warn!("🚦 Rate limit exceeded for miner_id={} (retry in {}s)", miner_id, secs);
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, secs.to_string())],
+        Json(serde_json::json!({"status": "error", "message": "Rate limit exceeded"})),
+    )
+        .into_response()
+}
+
+/// Durée de la forme `<n>s`, `<n>m`, `<n>h` ou `<n>d` (au plus 30 jours)
+fn parse_window(window: &str) -> Option<chrono::Duration> {
+    let window = window.trim();
+    let unit = window.chars().last()?;
+    let n: i64 = window[..window.len() - unit.len_utf8()].parse().ok().filter(|n| *n > 0)?;
+    let duration = match unit {
+        's' => chrono::Duration::seconds(n),
+        'm' => chrono::Duration::minutes(n),
+        'h' => chrono::Duration::hours(n),
+        'd' => chrono::Duration::days(n),
+        _ => return None,
+    };
+    (duration <= chrono::Duration::days(30)).then_some(duration)
+}
+
+fn db_error(e: sqlx::Error) -> HandlerError {
+    error!("❌ DB select error: {:?}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({"status": "error", "message": e.to_string()})))
+}
+
 // -------------------- HANDLERS --------------------
 
 #[axum::debug_handler]
 async fn insert_stat(
     State(pool): State<Pool<Postgres>>,
+    State(rate_limiter): State<RateLimiter>,
     headers: HeaderMap,
     Json(payload): Json<Stat>,
-) -> Result<Json<serde_json::Value>, Json<serde_json::Value>> {
+) -> Result<Json<serde_json::Value>, Response> {
     if !check_bearer(&headers) {
-        return Err(Json(serde_json::json!({"status": "error", "message": "Unauthorized"})));
+        return Err(Json(serde_json::json!({"status": "error", "message": "Unauthorized"})).into_response());
+    }
+    if let Err(retry_after) = rate_limiter.check(&payload.miner_id) {
+        return Err(too_many_requests(&payload.miner_id, retry_after));
     }
 
     info!("📥 Received stat: miner_id={} hash_rate={} timestamp={}", 
@@ -74,19 +282,122 @@ async fn insert_stat(
     let ts_naive: NaiveDateTime = payload.timestamp.naive_utc();
 
     match sqlx::query(
-        "INSERT INTO stats (container_id, miner_id, hash_rate, timestamp) VALUES ($1, $2, $3, $4)"
+        "INSERT INTO stats (container_id, miner_id, hash_rate, timestamp, wallet_rates, api_success, api_failure, api_error_rate, hash_rate_ema, wallet_addr)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
     ) 
     .bind(&payload.container_id)
     .bind(&payload.miner_id)
     .bind(payload.hash_rate)
     .bind(ts_naive)
+    .bind(payload.wallet_rates.map(sqlxJson))
+    .bind(payload.api_success)
+    .bind(payload.api_failure)
+    .bind(payload.api_error_rate)
+    .bind(payload.hash_rate_ema)
+    .bind(&payload.wallet_addr)
     .execute(&pool)
     .await
     {
         Ok(_) => Ok(Json(serde_json::json!({"status": "ok"}))),
         Err(e) => {
             error!("❌ DB insert error: {:?}", e);
-            Err(Json(serde_json::json!({"status": "error", "message": e.to_string()})))
+            Err(Json(serde_json::json!({"status": "error", "message": e.to_string()})).into_response())
+        }
+    }
+}
+
+/// Insertion en masse des stats bufferisées par un mineur (après une coupure réseau) :
+/// un seul `INSERT ... SELECT unnest(...)` pour tout le lot
+#[axum::debug_handler]
+async fn insert_stats_batch(
+    State(pool): State<Pool<Postgres>>,
+    State(rate_limiter): State<RateLimiter>,
+    headers: HeaderMap,
+    Json(payload): Json<StatBatch>,
+) -> Result<Json<serde_json::Value>, Response> {
+    if !check_bearer(&headers) {
+        return Err(Json(serde_json::json!({"status": "error", "message": "Unauthorized"})).into_response());
+    }
+    if payload.records.len() > MAX_BATCH_RECORDS {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!("Too many records ({} > {})", payload.records.len(), MAX_BATCH_RECORDS),
+            })),
+        )
+            .into_response());
+    }
+
+    // Un jeton par mineur présent dans le lot, pas par stat
+    let mut miner_ids: Vec<&str> = payload.records.iter().map(|r| r.miner_id.as_str()).collect();
+    miner_ids.sort_unstable();
+    miner_ids.dedup();
+    for miner_id in miner_ids {
+        if let Err(retry_after) = rate_limiter.check(miner_id) {
+            return Err(too_many_requests(miner_id, retry_after));
+        }
+    }
+
+    let cutoff = Utc::now() - chrono::Duration::hours(MAX_BATCH_RECORD_AGE_HOURS);
+    let total = payload.records.len();
+    let (records, stale): (Vec<Stat>, Vec<Stat>) =
+        payload.records.into_iter().partition(|r| r.timestamp >= cutoff);
+    for record in &stale {
+        warn!("⏳ Skipping stale stat: miner_id={} timestamp={}", record.miner_id, record.timestamp);
+    }
+    let skipped = stale.len();
+
+    info!("📥 Received stat batch: {} record(s), {} skipped", total, skipped);
+
+    if records.is_empty() {
+        return Ok(Json(serde_json::json!({"inserted": 0, "skipped": skipped})));
+    }
+
+    let mut container_ids = Vec::with_capacity(records.len());
+    let mut miner_ids = Vec::with_capacity(records.len());
+    let mut hash_rates = Vec::with_capacity(records.len());
+    let mut timestamps = Vec::with_capacity(records.len());
+    let mut wallet_rates = Vec::with_capacity(records.len());
+    let mut api_successes = Vec::with_capacity(records.len());
+    let mut api_failures = Vec::with_capacity(records.len());
+    let mut api_error_rates = Vec::with_capacity(records.len());
+    let mut hash_rate_emas = Vec::with_capacity(records.len());
+    let mut wallet_addrs = Vec::with_capacity(records.len());
+    for record in records {
+        container_ids.push(record.container_id);
+        miner_ids.push(record.miner_id);
+        hash_rates.push(record.hash_rate);
+        timestamps.push(record.timestamp.naive_utc());
+        wallet_rates.push(record.wallet_rates.map(sqlxJson));
+        api_successes.push(record.api_success);
+        api_failures.push(record.api_failure);
+        api_error_rates.push(record.api_error_rate);
+        hash_rate_emas.push(record.hash_rate_ema);
+        wallet_addrs.push(record.wallet_addr);
+    }
+
+    match sqlx::query(
+        "INSERT INTO stats (container_id, miner_id, hash_rate, timestamp, wallet_rates, api_success, api_failure, api_error_rate, hash_rate_ema, wallet_addr)
+        SELECT * FROM unnest($1::text[], $2::text[], $3::float8[], $4::timestamp[], $5::jsonb[], $6::int8[], $7::int8[], $8::float8[], $9::float8[], $10::text[])"
+    )
+    .bind(&container_ids)
+    .bind(&miner_ids)
+    .bind(&hash_rates)
+    .bind(&timestamps)
+    .bind(&wallet_rates)
+    .bind(&api_successes)
+    .bind(&api_failures)
+    .bind(&api_error_rates)
+    .bind(&hash_rate_emas)
+    .bind(&wallet_addrs)
+    .execute(&pool)
+    .await
+    {
+        Ok(res) => Ok(Json(serde_json::json!({"inserted": res.rows_affected(), "skipped": skipped}))),
+        Err(e) => {
+            error!("❌ DB batch insert error: {:?}", e);
+            Err(Json(serde_json::json!({"status": "error", "message": e.to_string()})).into_response())
         }
     }
 }
@@ -94,11 +405,15 @@ async fn insert_stat(
 #[axum::debug_handler]
 async fn insert_api_return(
     State(pool): State<Pool<Postgres>>,
+    State(rate_limiter): State<RateLimiter>,
     headers: HeaderMap,
     Json(payload): Json<ApiReturn>,
-) -> Result<Json<serde_json::Value>, Json<serde_json::Value>> {
+) -> Result<Json<serde_json::Value>, Response> {
     if !check_bearer(&headers) {
-        return Err(Json(serde_json::json!({"status": "error", "message": "Unauthorized"})));
+        return Err(Json(serde_json::json!({"status": "error", "message": "Unauthorized"})).into_response());
+    }
+    if let Err(retry_after) = rate_limiter.check(&payload.miner_id) {
+        return Err(too_many_requests(&payload.miner_id, retry_after));
     }
 
     info!("📥 Received API return: miner_id={} endpoint={}", payload.miner_id, payload.endpoint);
@@ -122,13 +437,221 @@ async fn insert_api_return(
         Ok(_) => Ok(Json(serde_json::json!({"status": "ok"}))),
         Err(e) => {
             error!("❌ Failed to log API return: {:?}", e);
-            Err(Json(serde_json::json!({"status": "error", "message": e.to_string()})))
+            Err(Json(serde_json::json!({"status": "error", "message": e.to_string()})).into_response())
         }
     }
 }
 
+#[axum::debug_handler]
+async fn get_stats(
+    State(pool): State<Pool<Postgres>>,
+    headers: HeaderMap,
+    Query(params): Query<StatsQuery>,
+) -> Result<Json<serde_json::Value>, HandlerError> {
+    if !check_bearer(&headers) {
+        return Err(unauthorized());
+    }
+
+    let page = params.page.unwrap_or(1).max(1);
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+    let offset = (page - 1) * limit;
+
+    let mut count_qb = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM stats WHERE 1=1");
+    push_stats_filters(&mut count_qb, &params);
+    let total_count: i64 = count_qb.build_query_scalar().fetch_one(&pool).await.map_err(db_error)?;
+
+    let mut qb = QueryBuilder::<Postgres>::new(
+        "SELECT id, container_id, miner_id, wallet_addr, hash_rate, hash_rate_ema, timestamp, description, wallet_rates, api_success, api_failure, api_error_rate FROM stats WHERE 1=1"
+    );
+    push_stats_filters(&mut qb, &params);
+    qb.push(" ORDER BY timestamp DESC LIMIT ").push_bind(limit);
+    qb.push(" OFFSET ").push_bind(offset);
+
+    let records = qb.build_query_as::<StatRecord>().fetch_all(&pool).await.map_err(db_error)?;
+    Ok(Json(serde_json::json!({
+        "total_count": total_count,
+        "page": page,
+        "limit": limit,
+        "records": records,
+    })))
+}
+
 // -------------------- MAIN --------------------
 
+/// Série temporelle du hash rate d'un mineur (`from` / `to` en RFC 3339, 400 si illisibles)
+async fn get_miner_stats(
+    State(pool): State<Pool<Postgres>>,
+    headers: HeaderMap,
+    Path(miner_id): Path<String>,
+    query: Result<Query<MinerSeriesQuery>, QueryRejection>,
+) -> Result<Json<serde_json::Value>, HandlerError> {
+    if !check_bearer(&headers) {
+        return Err(unauthorized());
+    }
+    let Query(params) = query.map_err(bad_request)?;
+    let limit = params.limit.unwrap_or(100).clamp(1, 1000);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let mut qb = QueryBuilder::<Postgres>::new("SELECT container_id, hash_rate, timestamp FROM stats WHERE wallet_addr IS NULL AND miner_id = ");
+    qb.push_bind(&miner_id);
+    if let Some(from) = params.from {
+        qb.push(" AND timestamp >= ").push_bind(from.naive_utc());
+    }
+    if let Some(to) = params.to {
+        qb.push(" AND timestamp < ").push_bind(to.naive_utc());
+    }
+    qb.push(" ORDER BY timestamp DESC LIMIT ").push_bind(limit);
+    qb.push(" OFFSET ").push_bind(offset);
+
+    let points = qb.build_query_as::<HashRatePoint>().fetch_all(&pool).await.map_err(db_error)?;
+    Ok(Json(serde_json::json!({
+        "miner_id": miner_id,
+        "limit": limit,
+        "offset": offset,
+        "points": points,
+    })))
+}
+
+/// H/s agrégé sur les `minutes` dernières minutes (5 par défaut) : dernière mesure de chaque conteneur
+async fn get_stats_summary(
+    State(pool): State<Pool<Postgres>>,
+    headers: HeaderMap,
+    query: Result<Query<SummaryQuery>, QueryRejection>,
+) -> Result<Json<serde_json::Value>, HandlerError> {
+    if !check_bearer(&headers) {
+        return Err(unauthorized());
+    }
+    let Query(params) = query.map_err(bad_request)?;
+    let minutes = params.minutes.unwrap_or(5).clamp(1, 24 * 60);
+    let since = (Utc::now() - chrono::Duration::minutes(minutes)).naive_utc();
+
+    let (containers, miners, total_hash_rate): (i64, i64, f64) = sqlx::query_as(
+        "SELECT COUNT(*), COUNT(DISTINCT miner_id), COALESCE(SUM(hash_rate), 0) FROM ( \
+            SELECT DISTINCT ON (container_id) container_id, miner_id, hash_rate \
+            FROM stats WHERE timestamp >= $1 AND wallet_addr IS NULL \
+            ORDER BY container_id, timestamp DESC \
+         ) latest"
+    )
+    .bind(since)
+    .fetch_one(&pool)
+    .await
+    .map_err(db_error)?;
+
+    Ok(Json(serde_json::json!({
+        "minutes": minutes,
+        "since": since,
+        "containers": containers,
+        "miners": miners,
+        "total_hash_rate": total_hash_rate,
+    })))
+}
+
+/// Classement par hash rate moyen sur `window` (1h par défaut), regroupé par `miner_id`
+async fn get_leaderboard(
+    State(pool): State<Pool<Postgres>>,
+    headers: HeaderMap,
+    query: Result<Query<LeaderboardQuery>, QueryRejection>,
+) -> Result<Json<serde_json::Value>, HandlerError> {
+    leaderboard(&pool, &headers, query, "miner_id").await
+}
+
+/// Même classement que `/leaderboard`, regroupé par `container_id`
+async fn get_container_leaderboard(
+    State(pool): State<Pool<Postgres>>,
+    headers: HeaderMap,
+    query: Result<Query<LeaderboardQuery>, QueryRejection>,
+) -> Result<Json<serde_json::Value>, HandlerError> {
+    leaderboard(&pool, &headers, query, "container_id").await
+}
+
+/// `group_by` est une colonne de `stats` choisie par le handler, jamais par le client
+async fn leaderboard(
+    pool: &Pool<Postgres>,
+    headers: &HeaderMap,
+    query: Result<Query<LeaderboardQuery>, QueryRejection>,
+    group_by: &'static str,
+) -> Result<Json<serde_json::Value>, HandlerError> {
+    if !check_bearer(headers) {
+        return Err(unauthorized());
+    }
+    let Query(params) = query.map_err(bad_request)?;
+    let window = params.window.as_deref().unwrap_or("1h");
+    let Some(duration) = parse_window(window) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "status": "error",
+                "message": format!("invalid window '{}' (expected e.g. 30m, 1h, 7d; max 30d)", window),
+            })),
+        ));
+    };
+    let limit = params.limit.unwrap_or(10).clamp(1, 1000);
+    let since = (Utc::now() - duration).naive_utc();
+
+    let sql = format!(
+        "WITH recent AS ( \
+            SELECT {col} AS id, hash_rate, timestamp FROM stats \
+            WHERE timestamp >= $1 AND {col} IS NOT NULL AND wallet_addr IS NULL \
+         ) \
+         SELECT id, AVG(hash_rate) AS avg_hash_rate, MAX(timestamp) AS last_seen \
+         FROM recent GROUP BY id ORDER BY avg_hash_rate DESC LIMIT $2",
+        col = group_by
+    );
+    let rows: Vec<LeaderboardRow> = sqlx::query_as(&sql)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(serde_json::Value::Array(
+        rows.into_iter()
+            .map(|row| {
+                serde_json::json!({
+                    group_by: row.id,
+                    "avg_hash_rate": row.avg_hash_rate,
+                    "last_seen": row.last_seen,
+                })
+            })
+            .collect(),
+    )))
+}
+
+/// Sonde de vie (sans authentification) : `SELECT 1` doit répondre en moins de 500 ms, sinon 503
+async fn health(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let uptime_secs = state.start_time.elapsed().as_secs();
+    let start = Instant::now();
+
+    match tokio::time::timeout(Duration::from_millis(500), sqlx::query("SELECT 1").execute(&state.pool)).await {
+        Ok(Ok(_)) => (
+            StatusCode::OK,
+            Json(serde_json::json!({
+                "status": "ok",
+                "db_latency_ms": start.elapsed().as_millis() as u64,
+                "uptime_secs": uptime_secs,
+            })),
+        ),
+        Ok(Err(e)) => {
+            warn!("🩺 Health check: DB error: {:?}", e);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({"status": "degraded", "error": e.to_string(), "uptime_secs": uptime_secs})),
+            )
+        }
+        Err(_) => {
+            warn!("🩺 Health check: DB timeout");
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({
+                    "status": "degraded",
+                    "error": "database did not answer within 500 ms",
+                    "uptime_secs": uptime_secs,
+                })),
+            )
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt()
@@ -156,10 +679,59 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    let app = Router::new()
+    // Schéma géré par backend/migrations (embarqué à la compilation)
+    if let Err(e) = sqlx::migrate!("./migrations").run(&pool).await {
+        error!("❌ Database migration failed: {}", e);
+        std::process::exit(1);
+    }
+    info!("🗄️ Database migrations applied");
+
+    // Limites des insertions : par mineur (seau de jetons) et en concurrence globale
+    let per_miner_per_min: u32 = std::env::var("RATE_LIMIT_PER_MINER_PER_MIN")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(120);
+    let max_concurrent_inserts: usize = std::env::var("MAX_CONCURRENT_INSERTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(32);
+    info!(
+        "🚦 Insert limits: {} per miner per minute, {} concurrent",
+        per_miner_per_min, max_concurrent_inserts
+    );
+    let rate_limiter = RateLimiter::new(per_miner_per_min);
+    {
+        let rate_limiter = rate_limiter.clone();
+        tokio::spawn(async move {
+            loop {
+                sleep(Duration::from_secs(300)).await;
+                rate_limiter.prune(Duration::from_secs(300));
+            }
+        });
+    }
+
+    let state = AppState {
+        pool: pool.clone(),
+        start_time: Instant::now(),
+        rate_limiter,
+    };
+
+    let inserts = Router::new()
         .route("/insert_stat", post(insert_stat))
+        .route("/stats/batch", post(insert_stats_batch))
         .route("/insert_api_return", post(insert_api_return))
-        .with_state(pool.clone());
+        .layer(ConcurrencyLimitLayer::new(max_concurrent_inserts));
+
+    let app = Router::new()
+        .route("/health", get(health))
+        .merge(inserts)
+        .route("/stats", get(get_stats))
+        .route("/stats/summary", get(get_stats_summary))
+        .route("/stats/:miner_id", get(get_miner_stats))
+        .route("/leaderboard", get(get_leaderboard))
+        .route("/leaderboard/container", get(get_container_leaderboard))
+        .with_state(state);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], 8080)); 
     info!("🌍 Listening on http://{}", addr); 
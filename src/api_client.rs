@@ -1,12 +1,87 @@
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response};
 use std::error::Error;
 use log::{info, error, warn, debug};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::spawn;
+use chrono::{DateTime, Utc};
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use eventsource_stream::Eventsource;
+use futures::{future, stream::{self, BoxStream}, StreamExt};
+use crate::retry::{self, RetryConfig};
+use async_trait::async_trait;
+
+/// Durée maximale d'une connexion au flux SSE avant reconnexion
+const CHALLENGE_STREAM_TIMEOUT: Duration = Duration::from_secs(3600);
+
+/// User-Agent par défaut des appels à l'API Scavenger
+const DEFAULT_USER_AGENT: &str = "scavenger_miner/1.0 - github.com/whosbax/midnight-scavenger";
+
+/// ------------------ Errors ------------------
+#[derive(Debug, Clone)]
+pub enum ApiError {
+    /// Circuit ouvert : trop d'échecs consécutifs sur l'endpoint, appel non effectué
+    CircuitOpen,
+    /// Erreur réseau / transport
+    Http(String),
+    /// Réponse HTTP non-2xx
+    Status { status: u16, body: String },
+    /// Réponse illisible
+    Decode(String),
+    /// Configuration du client invalide (URL, proxy...)
+    Config(String),
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::CircuitOpen => write!(f, "Circuit breaker open"),
+            ApiError::Http(e) => write!(f, "HTTP error: {}", e),
+            ApiError::Status { status, body } => write!(f, "HTTP status {}: {}", status, body),
+            ApiError::Decode(e) => write!(f, "Decode error: {}", e),
+            ApiError::Config(e) => write!(f, "Invalid client config: {}", e),
+        }
+    }
+}
+
+impl Error for ApiError {}
+
+impl ApiError {
+    /// Erreur susceptible de disparaître au prochain essai : réseau, 429 ou 5xx.
+    /// Un refus 4xx (signature, adresse inconnue...) ou une réponse illisible est définitif.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ApiError::Http(_) => true,
+            ApiError::Status { status, .. } => *status == 429 || *status >= 500,
+            ApiError::CircuitOpen | ApiError::Decode(_) | ApiError::Config(_) => false,
+        }
+    }
+}
+
+/// État du circuit breaker d'un endpoint
+#[derive(Debug, Default)]
+pub struct CircuitBreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    /// Début de la requête de test half-open ; expire après le cooldown si son résultat
+    /// n'est jamais enregistré (future abandonnée par un `select!`, un timeout, une tâche annulée)
+    probe_started_at: Option<Instant>,
+}
+
+/// Compteurs d'appels d'un endpoint (succès / échecs au sens du circuit breaker)
+#[derive(Debug, Default)]
+pub struct ApiCallCounters {
+    pub successes: AtomicU64,
+    pub failures: AtomicU64,
+}
 
 /// ------------------ Donate ------------------
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DonateResponse {
     pub status: Option<String>,
     pub message: Option<String>,
@@ -20,7 +95,7 @@ pub struct DonateResponse {
 }
 
 /// ------------------ Terms & Conditions ------------------
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TermsResponse {
     pub version: String,
     pub content: String,
@@ -28,39 +103,108 @@ pub struct TermsResponse {
 }
 
 /// ------------------ Register ------------------
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RegistrationReceipt {
     pub preimage: String,
     pub signature: String,
     pub timestamp: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RegisterResponse {
     #[serde(rename = "registrationReceipt")]
     pub registration_receipt: RegistrationReceipt,
 }
 
 /// ------------------ Challenge ------------------
-#[derive(Debug, Deserialize, Clone, Serialize)]
-pub struct ChallengeParams {
-    #[serde(rename = "challenge_id")]
-    pub challenge_id: String,
-    pub day: Option<u32>,
-    #[serde(rename = "challenge_number")]
-    pub challenge_number: Option<u32>,
-    #[serde(rename = "issued_at")]
-    pub issued_at: Option<String>,
-    #[serde(rename = "latest_submission")]
-    pub latest_submission: Option<String>,
-    pub difficulty: Option<String>,
-    #[serde(rename = "no_pre_mine")]
-    pub no_pre_mine: Option<String>,
-    #[serde(rename = "no_pre_mine_hour")]
-    pub no_pre_mine_hour: Option<String>,
+pub use crate::challenge::ChallengeParams;
+
+impl ChallengeResponse {
+    /// Tous les challenges ouverts : `challenge` puis `challenges`, sans doublon d'identifiant.
+    pub fn open_challenges(self) -> Vec<ChallengeParams> {
+        let mut open: Vec<ChallengeParams> = Vec::with_capacity(1 + self.challenges.len());
+        for challenge in self.challenge.into_iter().chain(self.challenges) {
+            if !open.iter().any(|c| c.challenge_id == challenge.challenge_id) {
+                open.push(challenge);
+            }
+        }
+        open
+    }
+
+    /// Fin de la période de minage annoncée par le serveur (`mining_period_ends`, RFC 3339).
+    pub fn mining_period_end(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp("mining_period_ends", self.mining_period_ends.as_deref()?)
+    }
+
+    /// Prochain instant où un nouveau challenge sera disponible :
+    /// `next_challenge_starts_at`, ou `starts_at` si la période de minage n'a pas commencé.
+    pub fn next_challenge_start(&self) -> Option<DateTime<Utc>> {
+        let next = self
+            .next_challenge_starts_at
+            .as_deref()
+            .and_then(|v| parse_timestamp("next_challenge_starts_at", v));
+        let starts = self
+            .starts_at
+            .as_deref()
+            .and_then(|v| parse_timestamp("starts_at", v))
+            .filter(|s| *s > Utc::now());
+        next.into_iter().chain(starts).max()
+    }
+
+    /// Attente avant le prochain polling de `/challenge` : jusqu'au prochain challenge
+    /// (`next_challenge_start`) plus `margin`, bornée à [1 s, 600 s].
+    /// Sans date annoncée (ou illisible), intervalle par défaut de 10 s.
+    pub fn next_poll_delay(&self, now: DateTime<Utc>, margin: Duration) -> Duration {
+        const DEFAULT_POLL_DELAY: Duration = Duration::from_secs(10);
+        const MIN_POLL_DELAY: Duration = Duration::from_secs(1);
+        const MAX_POLL_DELAY: Duration = Duration::from_secs(600);
+
+        match self.next_challenge_start() {
+            // Date déjà passée : le challenge est ouvert, on repasse vite
+            Some(at) => ((at - now).to_std().unwrap_or_default() + margin).clamp(MIN_POLL_DELAY, MAX_POLL_DELAY),
+            None => DEFAULT_POLL_DELAY,
+        }
+    }
+}
+
+/// Parse un horodatage RFC 3339 renvoyé par l'API (espaces ignorés) ; un champ illisible est journalisé.
+fn parse_timestamp(field: &str, value: &str) -> Option<DateTime<Utc>> {
+    match DateTime::parse_from_rfc3339(value.trim()) {
+        Ok(ts) => Some(ts.with_timezone(&Utc)),
+        Err(e) => {
+            warn!("{} illisible: {} ({})", field, value, e);
+            None
+        }
+    }
+}
+
+impl ChallengeParams {
+    /// Date limite de soumission (`latest_submission`, RFC 3339)
+    pub fn latest_submission_at(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp("latest_submission", self.latest_submission.as_deref()?)
+    }
+
+    /// Indique si le challenge a été émis il y a plus de `max_age` (`issued_at` au format RFC 3339).
+    /// Un `issued_at` absent ou illisible n'est pas considéré comme expiré.
+    pub fn is_expired(&self, max_age: Duration) -> bool {
+        let issued_at = match self.issued_at.as_deref() {
+            Some(v) => v,
+            None => return false,
+        };
+        match DateTime::parse_from_rfc3339(issued_at) {
+            Ok(issued) => (Utc::now() - issued.with_timezone(&Utc))
+                .to_std()
+                .map(|age| age > max_age)
+                .unwrap_or(false),
+            Err(e) => {
+                warn!("issued_at illisible pour challenge {}: {} ({})", self.challenge_id, issued_at, e);
+                false
+            }
+        }
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ChallengeResponse {
     pub code: String,
     pub challenge: Option<ChallengeParams>,
@@ -76,17 +220,20 @@ pub struct ChallengeResponse {
     pub next_challenge_starts_at: Option<String>,
     #[serde(rename = "starts_at")]
     pub starts_at: Option<String>,
+    /// Challenges ouverts simultanément, si le serveur les expose
+    #[serde(default)]
+    pub challenges: Vec<ChallengeParams>,
 }
 
 /// ------------------ Solution ------------------
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CryptoReceipt {
     pub preimage: String,
     pub timestamp: String,
     pub signature: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct SubmitResponse {
     #[serde(rename = "crypto_receipt")]
     pub crypto_receipt: Option<CryptoReceipt>,
@@ -95,33 +242,421 @@ pub struct SubmitResponse {
     pub message: Option<String>,
 }
 
+/// ------------------ Balance ------------------
+/// Récompenses d'une adresse (`/balance/{address}`, endpoint pas encore exposé par le serveur)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BalanceResponse {
+    pub address: String,
+    pub pending_rewards: u64,
+    pub confirmed_rewards: u64,
+    pub last_updated: DateTime<Utc>,
+}
+
+impl BalanceResponse {
+    /// Solde nul, renvoyé tant que le serveur n'expose pas `/balance`
+    pub fn zero(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            pending_rewards: 0,
+            confirmed_rewards: 0,
+            last_updated: Utc::now(),
+        }
+    }
+}
+
+/// Timeouts par opération, appliqués requête par requête (`RequestBuilder::timeout`) :
+/// la soumission échoue vite pour être rejouée, les conditions d'utilisation peuvent attendre.
+#[derive(Clone, Debug)]
+pub struct ApiTimeouts {
+    pub terms: Duration,
+    pub register: Duration,
+    pub challenge: Duration,
+    pub submit: Duration,
+    pub verify: Duration,
+    pub donate: Duration,
+    pub balance: Duration,
+}
+
+impl Default for ApiTimeouts {
+    fn default() -> Self {
+        Self {
+            terms: Duration::from_secs(30),
+            register: Duration::from_secs(20),
+            challenge: Duration::from_secs(10),
+            submit: Duration::from_secs(10),
+            verify: Duration::from_secs(10),
+            donate: Duration::from_secs(20),
+            balance: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ApiTimeouts {
+    /// Valeurs par défaut remplacées par `API_TIMEOUT_<OPÉRATION>_SECS`
+    /// (`TERMS`, `REGISTER`, `CHALLENGE`, `SUBMIT`, `VERIFY`, `DONATE`, `BALANCE`)
+    pub fn from_env() -> Self {
+        let var = |op: &str, default: Duration| {
+            std::env::var(format!("API_TIMEOUT_{}_SECS", op))
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|s| *s > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(default)
+        };
+        let defaults = Self::default();
+        Self {
+            terms: var("TERMS", defaults.terms),
+            register: var("REGISTER", defaults.register),
+            challenge: var("CHALLENGE", defaults.challenge),
+            submit: var("SUBMIT", defaults.submit),
+            verify: var("VERIFY", defaults.verify),
+            donate: var("DONATE", defaults.donate),
+            balance: var("BALANCE", defaults.balance),
+        }
+    }
+}
+
 /// ------------------ ApiClient ------------------
 pub struct ApiClient {
     base_url: String,
     http_client: Client,
     backend_url: String,
-    backend_token: String
+    backend_token: String,
+    user_agent: String,
+    retry: RetryConfig,
+    timeouts: ApiTimeouts,
+    /// Envois concurrents max vers le backend de journalisation ; au-delà, le log est abandonné
+    log_permits: Arc<tokio::sync::Semaphore>,
+    circuit_breakers: Arc<DashMap<String, CircuitBreakerState>>,
+    breaker_threshold: u32,
+    breaker_cooldown: Duration,
+    api_counters: Arc<DashMap<String, ApiCallCounters>>,
+    /// Totaux tous endpoints confondus, partagés avec le reporter de stats
+    api_totals: Arc<ApiCallCounters>,
+    /// Dernière réponse de `/challenge` et son ETag, renvoyée telle quelle sur un 304
+    challenge_cache: Mutex<Option<(String, ChallengeResponse)>>,
+    /// Conditions d'utilisation courantes et date de leur récupération (`get_terms_cached`)
+    terms_cache: Mutex<Option<(TermsResponse, Instant)>>,
+    terms_ttl: Duration,
+}
+
+/// Construction d'un `ApiClient` sans dépendre des variables d'environnement.
+///
+/// Les valeurs par défaut sont fixes (timeout 20s, aucun retry, pas de proxy) ;
+/// `ApiClient::new` les remplace par celles lues dans l'environnement.
+pub struct ApiClientBuilder {
+    base_url: Option<String>,
+    timeout: Duration,
+    timeouts: ApiTimeouts,
+    retry: RetryConfig,
+    log_concurrency: usize,
+    terms_ttl: Duration,
+    proxy: Option<String>,
+    extra_ca_pem: Option<Vec<u8>>,
+    bearer_token: String,
+    backend_url: String,
+    user_agent: String,
+    breaker_threshold: u32,
+    breaker_cooldown: Duration,
+}
+
+impl Default for ApiClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: None,
+            timeout: Duration::from_secs(20),
+            timeouts: ApiTimeouts::default(),
+            retry: RetryConfig::default(),
+            log_concurrency: 8,
+            terms_ttl: Duration::from_secs(3600),
+            proxy: None,
+            extra_ca_pem: None,
+            bearer_token: String::new(),
+            backend_url: "http://stats-backend:8080/insert_api_return".to_string(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            breaker_threshold: 5,
+            breaker_cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ApiClientBuilder {
+    /// URL de l'API Scavenger (obligatoire)
+    pub fn base_url(mut self, url: &str) -> Self {
+        self.base_url = Some(url.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Timeout global des requêtes sans timeout propre (journalisation des appels)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Timeouts des appels à l'API Scavenger, opération par opération
+    pub fn operation_timeouts(mut self, timeouts: ApiTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Nombre de nouvelles tentatives sur erreur réseau (les réponses HTTP ne sont pas rejouées)
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.retry.max_attempts = max_retries;
+        self
+    }
+
+    /// Backoff des nouvelles tentatives sur erreur réseau
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Nombre maximal d'envois simultanés vers le backend de journalisation (au moins 1)
+    pub fn log_concurrency(mut self, permits: usize) -> Self {
+        self.log_concurrency = permits.max(1);
+        self
+    }
+
+    /// Durée de validité des conditions d'utilisation mises en cache par `get_terms_cached`
+    pub fn terms_ttl(mut self, ttl: Duration) -> Self {
+        self.terms_ttl = ttl;
+        self
+    }
+
+    /// Proxy HTTP(S) pour toutes les requêtes, sauf les hôtes de `NO_PROXY` / `no_proxy`
+    pub fn proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_string());
+        self
+    }
+
+    /// Certificat racine supplémentaire (PEM), par exemple l'autorité d'un proxy d'entreprise
+    pub fn extra_ca_cert(mut self, pem: Vec<u8>) -> Self {
+        self.extra_ca_pem = Some(pem);
+        self
+    }
+
+    /// Token Bearer du backend de journalisation
+    pub fn bearer_token(mut self, token: &str) -> Self {
+        self.bearer_token = token.to_string();
+        self
+    }
+
+    /// URL du backend de journalisation des appels API
+    pub fn backend_url(mut self, url: &str) -> Self {
+        self.backend_url = url.to_string();
+        self
+    }
+
+    /// User-Agent envoyé à l'API Scavenger
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Seuil d'échecs consécutifs et durée d'ouverture du circuit breaker
+    pub fn circuit_breaker(mut self, threshold: u32, cooldown: Duration) -> Self {
+        self.breaker_threshold = threshold;
+        self.breaker_cooldown = cooldown;
+        self
+    }
+
+    pub fn build(self) -> Result<ApiClient, ApiError> {
+        let base_url = self
+            .base_url
+            .ok_or_else(|| ApiError::Config("base_url manquante".to_string()))?;
+
+        let mut http = Client::builder().timeout(self.timeout);
+        if let Some(proxy) = &self.proxy {
+            let proxy = reqwest::Proxy::all(proxy)
+                .map_err(|e| ApiError::Config(format!("proxy {}: {}", proxy, e)))?
+                .no_proxy(reqwest::NoProxy::from_env());
+            http = http.proxy(proxy);
+        }
+        if let Some(pem) = &self.extra_ca_pem {
+            let cert = reqwest::Certificate::from_pem(pem)
+                .map_err(|e| ApiError::Config(format!("certificat CA invalide: {}", e)))?;
+            http = http.add_root_certificate(cert);
+        }
+        let http_client = http.build().map_err(|e| ApiError::Config(e.to_string()))?;
+
+        Ok(ApiClient {
+            base_url,
+            http_client,
+            backend_url: self.backend_url,
+            backend_token: self.bearer_token,
+            user_agent: self.user_agent,
+            retry: self.retry,
+            timeouts: self.timeouts,
+            log_permits: Arc::new(tokio::sync::Semaphore::new(self.log_concurrency)),
+            circuit_breakers: Arc::new(DashMap::new()),
+            api_counters: Arc::new(DashMap::new()),
+            api_totals: Arc::default(),
+            challenge_cache: Mutex::new(None),
+            terms_cache: Mutex::new(None),
+            terms_ttl: self.terms_ttl,
+            breaker_threshold: self.breaker_threshold,
+            breaker_cooldown: self.breaker_cooldown,
+        })
+    }
 }
 
 impl ApiClient {
-    /// Crée un nouveau client API avec timeout raisonnable
+    /// Builder sans valeurs issues de l'environnement
+    pub fn builder() -> ApiClientBuilder {
+        ApiClientBuilder::default()
+    }
+
+    /// Crée un nouveau client API avec timeout raisonnable, configuré par les variables d'environnement
     pub fn new(base_url: &str) -> Result<Self, Box<dyn Error>> {
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(20))
-            .build()?;
+        let mut builder = Self::builder().base_url(base_url);
 
-        let backend_url = std::env::var("API_BACKEND_URL")
-            .unwrap_or_else(|_| "http://stats-backend:8080/insert_api_return".to_string());
+        if let Ok(url) = std::env::var("API_BACKEND_URL") {
+            builder = builder.backend_url(&url);
+        }
         let backend_token = std::env::var("STATS_BEARER_TOKEN")
             .unwrap_or_else(|_| "secret_token".to_string());
+        builder = builder.bearer_token(&backend_token);
 
+        let breaker_threshold = std::env::var("CIRCUIT_BREAKER_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(5);
+        let breaker_cooldown = Duration::from_secs(
+            std::env::var("CIRCUIT_BREAKER_COOLDOWN_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60),
+        );
+        builder = builder.circuit_breaker(breaker_threshold, breaker_cooldown);
+        builder = builder.retry(RetryConfig::from_env("API", 2));
+        builder = builder.operation_timeouts(ApiTimeouts::from_env());
+        if let Some(ttl) = std::env::var("TERMS_CACHE_TTL_SECS").ok().and_then(|v| v.parse::<u64>().ok()) {
+            builder = builder.terms_ttl(Duration::from_secs(ttl));
+        }
 
-        Ok(Self {
-            base_url: base_url.to_string(),
-            http_client: client,
-            backend_url,
-            backend_token
-        })
+        // Proxy : HTTPS_PROXY prime sur HTTP_PROXY (majuscules puis minuscules), pour tout le trafic
+        let proxy = ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy"]
+            .iter()
+            .find_map(|name| std::env::var(name).ok().filter(|v| !v.trim().is_empty()));
+        if let Some(proxy) = proxy {
+            // L'URL peut contenir des identifiants : seul l'hôte est journalisé
+            let host = reqwest::Url::parse(proxy.trim())
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string))
+                .unwrap_or_default();
+            info!("🌐 Proxy API : {}", host);
+            builder = builder.proxy(proxy.trim());
+        }
+        if let Some(path) = std::env::var("API_EXTRA_CA_CERT").ok().filter(|p| !p.is_empty()) {
+            let pem = std::fs::read(&path)
+                .map_err(|e| ApiError::Config(format!("API_EXTRA_CA_CERT {} illisible: {}", path, e)))?;
+            info!("🔐 Certificat CA supplémentaire chargé : {}", path);
+            builder = builder.extra_ca_cert(pem);
+        }
+        if let Some(permits) = std::env::var("STATS_LOG_CONCURRENCY").ok().and_then(|v| v.parse::<usize>().ok()) {
+            builder = builder.log_concurrency(permits);
+        }
+
+        Ok(builder.build()?)
+    }
+
+    /// Envoie la requête en la rejouant sur erreur réseau selon `retry` (backoff exponentiel).
+    /// Les méthodes non idempotentes (`POST /solution`, `/register`, `/donate_to`) ne sont rejouées
+    /// que sur échec de connexion : une requête expirée a pu être traitée par le serveur.
+    async fn send(&self, request: RequestBuilder) -> Result<Response, reqwest::Error> {
+        // Corps non clonable : un seul essai
+        let Some(probe) = request.try_clone() else {
+            return request.send().await;
+        };
+        let idempotent = probe.build().map(|r| r.method().is_idempotent()).unwrap_or(false);
+        let max_attempts = self.retry.max_attempts;
+        let mut attempt = 0;
+        retry::retry_if(
+            self.retry.backoff(),
+            || {
+                attempt += 1;
+                if attempt > 1 {
+                    warn!("🔁 Nouvelle tentative {}/{} après erreur réseau", attempt - 1, max_attempts);
+                }
+                request.try_clone().expect("requête clonable").send()
+            },
+            |e: &reqwest::Error| e.is_connect() || (idempotent && (e.is_timeout() || e.is_request())),
+        )
+        .await
+    }
+
+    /// Refuse l'appel si le circuit de l'endpoint est ouvert.
+    /// Après le cooldown, une seule requête de test (half-open) est autorisée par cooldown.
+    fn circuit_check(&self, endpoint: &str) -> Result<(), ApiError> {
+        let mut state = self.circuit_breakers.entry(endpoint.to_string()).or_default();
+        let opened_at = state.opened_at;
+        let probe_pending = state.probe_started_at.is_some_and(|t| t.elapsed() < self.breaker_cooldown);
+        match opened_at {
+            None => Ok(()),
+            Some(opened_at) if opened_at.elapsed() < self.breaker_cooldown => Err(ApiError::CircuitOpen),
+            Some(_) if probe_pending => Err(ApiError::CircuitOpen),
+            Some(_) => {
+                info!("🔌 Circuit half-open sur {}, requête de test", endpoint);
+                state.probe_started_at = Some(Instant::now());
+                Ok(())
+            }
+        }
+    }
+
+    /// Met à jour le circuit breaker selon le résultat HTTP (erreur réseau, 5xx ou 429 = échec)
+    fn circuit_record(
+        &self,
+        endpoint: &str,
+        result: Result<reqwest::Response, reqwest::Error>,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let failed = match &result {
+            Ok(resp) => resp.status().is_server_error() || resp.status().as_u16() == 429,
+            Err(_) => true,
+        };
+        {
+            let counters = self.api_counters.entry(endpoint.to_string()).or_default();
+            let counter = if failed { &counters.failures } else { &counters.successes };
+            counter.fetch_add(1, Ordering::Relaxed);
+            let total = if failed { &self.api_totals.failures } else { &self.api_totals.successes };
+            total.fetch_add(1, Ordering::Relaxed);
+        }
+        let mut state = self.circuit_breakers.entry(endpoint.to_string()).or_default();
+        if failed {
+            state.consecutive_failures += 1;
+            state.probe_started_at = None;
+            if state.opened_at.is_some() || state.consecutive_failures >= self.breaker_threshold {
+                warn!(
+                    "🔌 Circuit ouvert sur {} ({} échecs consécutifs), pause de {:?}",
+                    endpoint, state.consecutive_failures, self.breaker_cooldown
+                );
+                state.opened_at = Some(Instant::now());
+            }
+        } else {
+            if state.opened_at.is_some() {
+                info!("🔌 Circuit refermé sur {}", endpoint);
+            }
+            *state = CircuitBreakerState::default();
+        }
+        result
+    }
+
+    /// Nombre d'appels réussis / échoués par endpoint depuis le démarrage : `(endpoint, succès, échecs)`
+    pub fn api_call_counts(&self) -> Vec<(String, u64, u64)> {
+        self.api_counters
+            .iter()
+            .map(|entry| {
+                (
+                    entry.key().clone(),
+                    entry.successes.load(Ordering::Relaxed),
+                    entry.failures.load(Ordering::Relaxed),
+                )
+            })
+            .collect()
+    }
+
+    /// Compteurs globaux d'appels réussis / échoués (monotones), tous endpoints confondus
+    pub fn api_totals(&self) -> Arc<ApiCallCounters> {
+        Arc::clone(&self.api_totals)
     }
 
     /// Logging non-bloquant vers le backend
@@ -155,7 +690,18 @@ impl ApiClient {
         let ctn_prefix = std::env::var("CONTAINER_PREFIX").unwrap_or_else(|_| "".to_string());
         //let ctn_id = format!("{}", ctn_prefix);
         let ctn_id = format!("{}/{}", ctn_prefix, container_id);
+
+        // Backend saturé : on abandonne ce log plutôt que d'empiler les requêtes
+        let permit = match Arc::clone(&self.log_permits).try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                warn!("⚠️ Journalisation saturée, log de l'appel {} abandonné", endpoint);
+                return;
+            }
+        };
+        let backoff = self.retry.backoff();
         spawn(async move {
+            let _permit = permit;
             let log_body = serde_json::json!({
                 "miner_id": miner_id,
                 "container_id": ctn_id,
@@ -166,17 +712,22 @@ impl ApiClient {
                 "url": url_,
                 "api_response": api_response,
             });
-            match client.post(&backend_url)
-                .bearer_auth(token)
-                .json(&log_body)
-                .send()
-                .await
-            {
+            // Erreurs réseau et 5xx rejouées avec backoff, permis conservé pendant les tentatives
+            let (client, backend_url, token, log_body) = (&client, &backend_url, &token, &log_body);
+            let sent = retry::retry(backoff, move || async move {
+                match client.post(backend_url).bearer_auth(token).json(log_body).send().await {
+                    Ok(resp) if resp.status().is_server_error() => Err(format!("status={}", resp.status())),
+                    Ok(resp) => Ok(resp),
+                    Err(e) => Err(format!("err={}", e)),
+                }
+            })
+            .await;
+            match sent {
                 Ok(resp) if resp.status().is_success() => {
                     info!("✅ Logged API call to backend: endpoint={}", endpoint);
                 }
                 Ok(resp) => warn!("⚠️ Failed to log API call (status={}): endpoint={}", resp.status(), endpoint),
-                Err(e) => warn!("⚠️ Error sending log to backend: endpoint={} err={}", endpoint, e),
+                Err(e) => warn!("⚠️ Error sending log to backend: endpoint={} {}", endpoint, e),
             }
         });
     }
@@ -199,9 +750,10 @@ impl ApiClient {
     ) -> Result<TermsResponse, Box<dyn Error + Send + Sync>> {
         let url = version.map_or_else(|| format!("{}/TandC", &self.base_url),
                                       |v| format!("{}/TandC/{}", &self.base_url, v));
-        let ua = format!("scavenger_miner/1.0 - github.com/whosbax/midnight-scavenger");
+        let ua = self.user_agent.clone();
 
-        let resp = self.http_client.get(&url).header("User-Agent", ua).send().await?;
+        self.circuit_check("/TandC")?;
+        let resp = self.circuit_record("/TandC", self.send(self.http_client.get(&url).header("User-Agent", ua).timeout(self.timeouts.terms)).await)?;
         if !resp.status().is_success() {
             let status = resp.status();
             let text = resp.text().await.unwrap_or_default();
@@ -217,6 +769,24 @@ impl ApiClient {
         Ok(result)
     }
 
+    /// Conditions d'utilisation courantes, récupérées au plus une fois par `terms_ttl`
+    /// (`TERMS_CACHE_TTL_SECS`, 3600 par défaut) et partagées par tous les enregistrements
+    pub async fn get_terms_cached(
+        &self,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<TermsResponse, Box<dyn Error + Send + Sync>> {
+        if let Some((terms, fetched_at)) = self.terms_cache.lock().as_ref() {
+            if fetched_at.elapsed() < self.terms_ttl {
+                debug!("📜 Conditions d'utilisation v{} servies depuis le cache", terms.version);
+                return Ok(terms.clone());
+            }
+        }
+        let terms = self.get_terms(None, miner_id, container_id).await?;
+        *self.terms_cache.lock() = Some((terms.clone(), Instant::now()));
+        Ok(terms)
+    }
+
     pub async fn register_address(
         &self,
         address: &str,
@@ -225,38 +795,108 @@ impl ApiClient {
         miner_id: Option<String>,
         container_id: Option<String>
     ) -> Result<RegisterResponse, Box<dyn Error + Send + Sync>> {
+        Ok(self.register(address, signature, pubkey, miner_id, container_id).await?)
+    }
+
+    /// Enregistre plusieurs adresses `(adresse, signature, pubkey)` en parallèle, au plus
+    /// `REGISTER_CONCURRENCY` (5 par défaut) à la fois. Les résultats suivent l'ordre des entrées.
+    pub async fn batch_register(
+        &self,
+        addresses: &[(String, String, String)],
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Vec<Result<RegisterResponse, ApiError>> {
+        let concurrency = std::env::var("REGISTER_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|c| *c > 0)
+            .unwrap_or(5);
+        let permits = tokio::sync::Semaphore::new(concurrency);
+
+        future::join_all(addresses.iter().map(|(address, signature, pubkey)| {
+            let permits = &permits;
+            let miner_id = miner_id.clone();
+            let container_id = container_id.clone();
+            async move {
+                let _permit = permits.acquire().await.expect("semaphore never closed");
+                self.register(address, signature, pubkey, miner_id, container_id).await
+            }
+        }))
+        .await
+    }
+
+    async fn register(
+        &self,
+        address: &str,
+        signature: &str,
+        pubkey: &str,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<RegisterResponse, ApiError> {
         let url = format!("{}/register/{}/{}/{}", &self.base_url, address, signature, pubkey);
-        let ua = format!("scavenger_miner/1.0 - github.com/whosbax/midnight-scavenger");
+        let ua = self.user_agent.clone();
 
-        let resp = self.http_client.post(&url).header("User-Agent", ua).json(&serde_json::json!({})).send().await?;
+        self.circuit_check("/register")?;
+        let resp = self
+            .circuit_record(
+                "/register",
+                self.send(
+                    self.http_client
+                        .post(&url)
+                        .header("User-Agent", ua)
+                        .json(&serde_json::json!({}))
+                        .timeout(self.timeouts.register),
+                )
+                .await,
+            )
+            .map_err(|e| ApiError::Http(e.to_string()))?;
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
 
         info!("Register addr({}) with pubk({}) -> response: \n{}", address, pubkey, text);
         if !status.is_success() {
-            return Err(format!("Registration failed POST[{}] [{}]: {}", url, status, text).into());
+            error!("Registration failed POST[{}] [{}]: {}", url, status, text);
+            return Err(ApiError::Status { status: status.as_u16(), body: text });
         }
 
-        let result: RegisterResponse = serde_json::from_str(&text)?;
-        let api_response_value = Some(
-            serde_json::to_value(&result).map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?
-        );
+        let result: RegisterResponse = serde_json::from_str(&text).map_err(|e| ApiError::Decode(e.to_string()))?;
+        let api_response_value = serde_json::to_value(&result).ok();
         self.log_api_call(container_id.as_deref().unwrap_or(""), miner_id.as_deref().unwrap_or(""), address, "/register", &url, Some("Register wallet".to_string()), None, api_response_value).await;
         Ok(result)
     }
 
+    /// Récupère le challenge courant (GET conditionnel : `If-None-Match` avec le dernier ETag reçu,
+    /// la réponse mise en cache est renvoyée sur un `304 Not Modified`).
     pub async fn get_challenge(&self, miner_id: Option<String>, container_id: Option<String>) -> Result<ChallengeResponse, Box<dyn Error + Send + Sync>> {
         let url = format!("{}/challenge", &self.base_url);
-        let ua = format!("scavenger_miner/1.0 - github.com/whosbax/midnight-scavenger");
+        let ua = self.user_agent.clone();
 
-        let resp = self.http_client.get(&url).header("User-Agent", ua).send().await?;
+        let mut request = self.http_client.get(&url).header("User-Agent", ua).timeout(self.timeouts.challenge);
+        if let Some((etag, _)) = self.challenge_cache.lock().as_ref() {
+            request = request.header("If-None-Match", etag.as_str());
+        }
+
+        self.circuit_check("/challenge")?;
+        let resp = self.circuit_record("/challenge", self.send(request).await)?;
+        if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some((_, cached)) = self.challenge_cache.lock().as_ref() {
+                debug!("GET {} : 304, challenge inchangé", url);
+                return Ok(cached.clone());
+            }
+        }
         if !resp.status().is_success() {
             let status = resp.status();
             let text = resp.text().await.unwrap_or_default();
             return Err(format!("GET {} failed [{}]: {}", url, status, text).into());
         }
 
+        let etag = resp
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
         let result: ChallengeResponse = resp.json().await?;
+        *self.challenge_cache.lock() = etag.map(|etag| (etag, result.clone()));
         let api_response_value = Some(
             serde_json::to_value(&result).map_err(|e| Box::new(e) as Box<dyn Error + Send + Sync>)?
         );
@@ -264,6 +904,60 @@ impl ApiClient {
         Ok(result)
     }
 
+    /// Flux SSE des challenges (`GET /challenge/stream`).
+    ///
+    /// Chaque évènement porte un `ChallengeResponse` JSON ; ceux sans challenge sont ignorés.
+    /// Un statut non-2xx est remonté en `ApiError::Status` (404 = endpoint non supporté par le serveur).
+    pub fn get_challenge_stream(
+        &self,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> BoxStream<'static, Result<ChallengeParams, ApiError>> {
+        let url = format!("{}/challenge/stream", &self.base_url);
+        let ua = self.user_agent.clone();
+        debug!("📡 Ouverture du flux {} (miner={:?}, container={:?})", url, miner_id, container_id);
+
+        let request = self
+            .http_client
+            .get(&url)
+            .header("User-Agent", ua)
+            .header("Accept", "text/event-stream")
+            .timeout(CHALLENGE_STREAM_TIMEOUT);
+
+        let connect = async move {
+            let resp = match request.send().await {
+                Ok(resp) => resp,
+                Err(e) => {
+                    return stream::once(future::ready(Err::<ChallengeParams, ApiError>(ApiError::Http(e.to_string())))).boxed();
+                }
+            };
+            let status = resp.status();
+            if !status.is_success() {
+                let body = resp.text().await.unwrap_or_default();
+                return stream::once(future::ready(Err(ApiError::Status { status: status.as_u16(), body }))).boxed();
+            }
+            resp.bytes_stream()
+                .eventsource()
+                .filter_map(|event| {
+                    future::ready(match event {
+                        Ok(event) if event.data.trim().is_empty() => None,
+                        Ok(event) => match serde_json::from_str::<ChallengeResponse>(&event.data) {
+                            Ok(resp) => resp.challenge.map(Ok),
+                            Err(e) => Some(Err(ApiError::Decode(e.to_string()))),
+                        },
+                        Err(e) => Some(Err(ApiError::Http(e.to_string()))),
+                    })
+                })
+                .boxed()
+        };
+
+        stream::once(connect).flatten().boxed()
+    }
+
+    /// Soumet une solution (`POST /solution/{address}/{challenge_id}/{nonce}`, corps vide).
+    ///
+    /// La préimage n'est pas transmise : le serveur la reconstruit à partir de l'adresse, du nonce
+    /// et de son propre challenge. `MinerResult::verify` la contrôle localement avant l'envoi.
     pub async fn submit_solution(
         &self,
         address: &str,
@@ -274,9 +968,20 @@ impl ApiClient {
     ) -> Result<SubmitResponse, Box<dyn Error + Send + Sync>> {
         let url = format!("{}/solution/{}/{}/{}", &self.base_url, address, challenge_id, nonce);
         info!("📬 Soumission de solution addr={} challenge={}", address, challenge_id);
-        let ua = format!("scavenger_miner/1.0 - github.com/whosbax/midnight-scavenger");
+        let ua = self.user_agent.clone();
 
-        let resp = self.http_client.post(&url).header("User-Agent", ua).json(&serde_json::json!({})).send().await?;
+        self.circuit_check("/solution")?;
+        let resp = self.circuit_record(
+            "/solution",
+            self.send(
+                self.http_client
+                    .post(&url)
+                    .header("User-Agent", ua)
+                    .json(&serde_json::json!({}))
+                    .timeout(self.timeouts.submit),
+            )
+            .await,
+        )?;
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
         if !status.is_success() {
@@ -293,6 +998,69 @@ impl ApiClient {
         Ok(result)
     }
 
+    /// Vérifie un nonce auprès de `/verify/{challenge_id}/{nonce}` avant de le soumettre.
+    /// Si le serveur n'expose pas cet endpoint (404), le nonce est supposé valide.
+    pub async fn verify_solution(&self, challenge_id: &str, nonce: &str) -> Result<bool, ApiError> {
+        #[derive(Deserialize)]
+        struct VerifyResponse {
+            valid: bool,
+        }
+
+        let url = format!("{}/verify/{}/{}", &self.base_url, challenge_id, nonce);
+        debug!("🔎 Vérification du nonce {} (challenge {})", nonce, challenge_id);
+        let ua = self.user_agent.clone();
+
+        self.circuit_check("/verify")?;
+        let resp = self
+            .circuit_record("/verify", self.send(self.http_client.get(&url).header("User-Agent", ua).timeout(self.timeouts.verify)).await)
+            .map_err(|e| ApiError::Http(e.to_string()))?;
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            debug!("🔎 Endpoint /verify absent, nonce supposé valide");
+            return Ok(true);
+        }
+        let text = resp.text().await.map_err(|e| ApiError::Http(e.to_string()))?;
+        if !status.is_success() {
+            return Err(ApiError::Status { status: status.as_u16(), body: text });
+        }
+
+        serde_json::from_str::<VerifyResponse>(&text)
+            .map(|r| r.valid)
+            .map_err(|e| ApiError::Decode(e.to_string()))
+    }
+
+    /// Récompenses en attente / confirmées de `address` via `/balance/{address}`.
+    /// Tant que le serveur n'expose pas cet endpoint (404), renvoie un solde nul.
+    pub async fn get_wallet_balance(
+        &self,
+        address: &str,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<BalanceResponse, ApiError> {
+        let url = format!("{}/balance/{}", &self.base_url, address);
+        debug!("💰 Solde de {}", address);
+        let ua = self.user_agent.clone();
+
+        self.circuit_check("/balance")?;
+        let resp = self
+            .circuit_record("/balance", self.send(self.http_client.get(&url).header("User-Agent", ua).timeout(self.timeouts.balance)).await)
+            .map_err(|e| ApiError::Http(e.to_string()))?;
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            debug!("💰 Endpoint /balance absent, solde nul pour {}", address);
+            return Ok(BalanceResponse::zero(address));
+        }
+        let text = resp.text().await.map_err(|e| ApiError::Http(e.to_string()))?;
+        if !status.is_success() {
+            return Err(ApiError::Status { status: status.as_u16(), body: text });
+        }
+
+        let balance: BalanceResponse = serde_json::from_str(&text).map_err(|e| ApiError::Decode(e.to_string()))?;
+        let api_response_value = serde_json::to_value(&balance).ok();
+        self.log_api_call(container_id.as_deref().unwrap_or(""), miner_id.as_deref().unwrap_or(""), address, "/balance", &url, Some("Wallet balance".to_string()), None, api_response_value).await;
+        Ok(balance)
+    }
+
     pub async fn donate_to(
         &self,
         destination_address: &str,
@@ -306,16 +1074,29 @@ impl ApiClient {
             &self.base_url, destination_address, original_address, signature
         );
         debug!("💸 Donation Url {}", url);
-        let ua = format!("scavenger_miner/1.0 - github.com/whosbax/midnight-scavenger");
+        let ua = self.user_agent.clone();
 
-        let resp = self.http_client.post(&url).header("User-Agent", ua).json(&serde_json::json!({})).send().await?;
+        self.circuit_check("/donate_to")?;
+        let resp = self
+            .circuit_record(
+                "/donate_to",
+                self.send(
+                    self.http_client
+                        .post(&url)
+                        .header("User-Agent", ua)
+                        .json(&serde_json::json!({}))
+                        .timeout(self.timeouts.donate),
+                )
+                .await,
+            )
+            .map_err(|e| ApiError::Http(e.to_string()))?;
         let status = resp.status();
         let text = resp.text().await.unwrap_or_default();
         let mut error_status = false;
         if !status.is_success() {
             error_status=true;
             debug!("Raw donation response: {}", text);
-            return Err(format!("Donation failed [{}]: {}", status, text).into());
+            return Err(ApiError::Status { status: status.as_u16(), body: text }.into());
         }
 
         let result: DonateResponse = serde_json::from_str(&text)?;
@@ -328,3 +1109,176 @@ impl ApiClient {
         Ok(result)
     }
 }
+
+/// Opérations de l'API Scavenger utilisées par les mineurs, les donations et les métriques.
+///
+/// Implémenté par `ApiClient` (HTTP) ; les orchestrateurs prennent un `Arc<dyn ApiClientTrait>`
+/// pour pouvoir être exercés sans réseau (voir `MockApiClient`).
+#[async_trait]
+pub trait ApiClientTrait: Send + Sync + 'static {
+    async fn get_terms(
+        &self,
+        version: Option<&str>,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<TermsResponse, Box<dyn Error + Send + Sync>>;
+
+    async fn get_terms_cached(
+        &self,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<TermsResponse, Box<dyn Error + Send + Sync>>;
+
+    async fn register_address(
+        &self,
+        address: &str,
+        signature: &str,
+        pubkey: &str,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<RegisterResponse, Box<dyn Error + Send + Sync>>;
+
+    async fn batch_register(
+        &self,
+        addresses: &[(String, String, String)],
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Vec<Result<RegisterResponse, ApiError>>;
+
+    async fn get_challenge(
+        &self,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<ChallengeResponse, Box<dyn Error + Send + Sync>>;
+
+    fn get_challenge_stream(
+        &self,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> BoxStream<'static, Result<ChallengeParams, ApiError>>;
+
+    async fn submit_solution(
+        &self,
+        address: &str,
+        challenge_id: &str,
+        nonce: &str,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<SubmitResponse, Box<dyn Error + Send + Sync>>;
+
+    async fn verify_solution(&self, challenge_id: &str, nonce: &str) -> Result<bool, ApiError>;
+
+    async fn donate_to(
+        &self,
+        destination_address: &str,
+        original_address: &str,
+        signature: &str,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<DonateResponse, Box<dyn Error + Send + Sync>>;
+
+    async fn get_wallet_balance(
+        &self,
+        address: &str,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<BalanceResponse, ApiError>;
+
+    /// `(endpoint, succès, échecs)` depuis le démarrage, voir `ApiClient::api_call_counts`
+    fn api_call_counts(&self) -> Vec<(String, u64, u64)>;
+}
+
+#[async_trait]
+impl ApiClientTrait for ApiClient {
+    async fn get_terms(
+        &self,
+        version: Option<&str>,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<TermsResponse, Box<dyn Error + Send + Sync>> {
+        ApiClient::get_terms(self, version, miner_id, container_id).await
+    }
+
+    async fn get_terms_cached(
+        &self,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<TermsResponse, Box<dyn Error + Send + Sync>> {
+        ApiClient::get_terms_cached(self, miner_id, container_id).await
+    }
+
+    async fn register_address(
+        &self,
+        address: &str,
+        signature: &str,
+        pubkey: &str,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<RegisterResponse, Box<dyn Error + Send + Sync>> {
+        ApiClient::register_address(self, address, signature, pubkey, miner_id, container_id).await
+    }
+
+    async fn batch_register(
+        &self,
+        addresses: &[(String, String, String)],
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Vec<Result<RegisterResponse, ApiError>> {
+        ApiClient::batch_register(self, addresses, miner_id, container_id).await
+    }
+
+    async fn get_challenge(
+        &self,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<ChallengeResponse, Box<dyn Error + Send + Sync>> {
+        ApiClient::get_challenge(self, miner_id, container_id).await
+    }
+
+    fn get_challenge_stream(
+        &self,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> BoxStream<'static, Result<ChallengeParams, ApiError>> {
+        ApiClient::get_challenge_stream(self, miner_id, container_id)
+    }
+
+    async fn submit_solution(
+        &self,
+        address: &str,
+        challenge_id: &str,
+        nonce: &str,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<SubmitResponse, Box<dyn Error + Send + Sync>> {
+        ApiClient::submit_solution(self, address, challenge_id, nonce, miner_id, container_id).await
+    }
+
+    async fn verify_solution(&self, challenge_id: &str, nonce: &str) -> Result<bool, ApiError> {
+        ApiClient::verify_solution(self, challenge_id, nonce).await
+    }
+
+    async fn donate_to(
+        &self,
+        destination_address: &str,
+        original_address: &str,
+        signature: &str,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<DonateResponse, Box<dyn Error + Send + Sync>> {
+        ApiClient::donate_to(self, destination_address, original_address, signature, miner_id, container_id).await
+    }
+
+    async fn get_wallet_balance(
+        &self,
+        address: &str,
+        miner_id: Option<String>,
+        container_id: Option<String>,
+    ) -> Result<BalanceResponse, ApiError> {
+        ApiClient::get_wallet_balance(self, address, miner_id, container_id).await
+    }
+
+    fn api_call_counts(&self) -> Vec<(String, u64, u64)> {
+        ApiClient::api_call_counts(self)
+    }
+}
@@ -0,0 +1,109 @@
+// src/bin/bench.rs
+//! Benchmark local du mineur, sans aucun appel à l'API :
+//!
+//! ```bash
+//! cargo run --release --bin bench -- --duration 30 --threads 8 --difficulty ffff0000
+//! ```
+//!
+//! Même mesure que `--benchmark` (`miner::benchmark`) : les challenges synthétiques sont minés
+//! en boucle pendant `--duration` secondes ; la difficulté par défaut (`ffff0000`) fait trouver
+//! des nonces rapidement, chaque nonce trouvé relançant un nouveau challenge. Affiche le hash rate par thread, le total et le pic de mémoire (RSS).
+
+use std::env;
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+use scavenger_miner::miner::benchmark;
+
+/// Options de la ligne de commande
+struct BenchArgs {
+    duration: Duration,
+    threads: usize,
+    difficulty: String,
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: bench [--duration SECS] [--threads N] [--difficulty HEX]");
+    eprintln!("  --duration    durée de la mesure en secondes (défaut 30)");
+    eprintln!("  --threads     nombre de threads (défaut : nombre de CPU)");
+    eprintln!("  --difficulty  masque de difficulté hexadécimal (défaut ffff0000)");
+    process::exit(2);
+}
+
+fn parse_args() -> BenchArgs {
+    let mut args = BenchArgs {
+        duration: Duration::from_secs(30),
+        threads: num_cpus::get(),
+        difficulty: "ffff0000".to_string(),
+    };
+
+    let mut iter = env::args().skip(1);
+    while let Some(flag) = iter.next() {
+        let value = match flag.as_str() {
+            "-h" | "--help" => usage(),
+            "--duration" | "--threads" | "--difficulty" => iter.next().unwrap_or_else(|| usage()),
+            _ => {
+                eprintln!("Option inconnue : {}", flag);
+                usage()
+            }
+        };
+        match flag.as_str() {
+            "--duration" => match value.parse::<u64>() {
+                Ok(secs) if secs > 0 => args.duration = Duration::from_secs(secs),
+                _ => usage(),
+            },
+            "--threads" => match value.parse::<usize>() {
+                Ok(n) if n > 0 => args.threads = n,
+                _ => usage(),
+            },
+            _ => {
+                if u32::from_str_radix(&value, 16).is_err() {
+                    usage();
+                }
+                args.difficulty = value;
+            }
+        }
+    }
+    args
+}
+
+/// Pic de mémoire résidente du process (`VmHWM`, Linux uniquement), en kio
+fn peak_rss_kib() -> Option<u64> {
+    fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|v| v.trim().trim_end_matches("kB").trim().parse().ok())
+}
+
+fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+    let args = parse_args();
+
+    // La génération de la ROM est exclue de la mesure
+    println!(
+        "Génération de la ROM puis minage sur {} threads pendant {:?} (difficulté {})",
+        args.threads, args.duration, args.difficulty
+    );
+    let report = match benchmark(args.threads, args.duration, &args.difficulty) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Erreur de minage : {}", e);
+            process::exit(1);
+        }
+    };
+    println!("ROM prête en {:.2?}", report.rom_elapsed);
+
+    println!();
+    for (idx, hashes) in report.per_thread.iter().enumerate() {
+        println!("thread {:>3} : {:>10} hashs  {:>10.1} H/s", idx, hashes, report.rate(*hashes));
+    }
+    let total = report.total();
+    println!("total      : {:>10} hashs  {:>10.1} H/s", total, report.rate(total));
+    println!("nonces trouvés : {}", report.nonces_found);
+    match peak_rss_kib() {
+        Some(kib) => println!("pic mémoire (RSS) : {:.1} Mio", kib as f64 / 1024.0),
+        None => println!("pic mémoire (RSS) : indisponible"),
+    }
+}
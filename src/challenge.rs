@@ -0,0 +1,25 @@
+// src/challenge.rs
+use serde::{Deserialize, Serialize};
+
+/// Paramètres d'un challenge Scavenger, tels que renvoyés par `GET /challenge`.
+/// Module sans dépendance HTTP : le mineur et le benchmark l'utilisent sans `api_client`.
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct ChallengeParams {
+    #[serde(rename = "challenge_id")]
+    pub challenge_id: String,
+    pub day: Option<u32>,
+    #[serde(rename = "challenge_number")]
+    pub challenge_number: Option<u32>,
+    #[serde(rename = "issued_at")]
+    pub issued_at: Option<String>,
+    #[serde(rename = "latest_submission")]
+    pub latest_submission: Option<String>,
+    pub difficulty: Option<String>,
+    #[serde(rename = "no_pre_mine")]
+    pub no_pre_mine: Option<String>,
+    #[serde(rename = "no_pre_mine_hour")]
+    pub no_pre_mine_hour: Option<String>,
+    /// Version du format de préimage annoncée par le serveur (absente aujourd'hui), voir `PREIMAGE_VERSION`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preimage_version: Option<String>,
+}
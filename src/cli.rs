@@ -0,0 +1,69 @@
+// src/cli.rs
+use clap::{CommandFactory, FromArgMatches, Parser};
+
+use crate::config::Config;
+
+/// Options de la ligne de commande.
+///
+/// Priorité : argument CLI, puis variable d'environnement (déploiements en conteneur), puis
+/// `config.toml`, puis défaut. Les options reprises de `Config` restent `None` quand elles ne
+/// sont pas données, pour ne pas écraser le fichier ; `--help` affiche leur valeur effective.
+#[derive(Debug, Parser)]
+#[command(name = "scavenger_miner", version, about = "Mineur Scavenger Mine (Midnight)")]
+pub struct Cli {
+    /// URL de base de l'API Scavenger Mine
+    #[arg(long, env = "APP_BASE_URL")]
+    pub base_url: Option<String>,
+
+    /// Nombre total de threads de minage, répartis entre les wallets
+    #[arg(long, env = "MINER_THREADS")]
+    pub threads: Option<usize>,
+
+    /// Nombre de wallets par instance
+    #[arg(long, env = "MAX_WALLETS_PER_INSTANCE")]
+    pub max_wallets: Option<usize>,
+
+    /// Dossier racine des instances (`<config-dir>/<n>/`)
+    #[arg(long, env = "CONFIG_DIR", default_value = "/usr/local/bin/config")]
+    pub config_dir: String,
+
+    /// Niveau de log (error, warn, info, debug, trace)
+    #[arg(long, env = "APP_LOG_LEVEL")]
+    pub log_level: Option<String>,
+
+    /// Mesure le hash rate local sans aucun appel réseau, puis quitte
+    #[arg(long, env = "BENCHMARK_MODE")]
+    pub benchmark: bool,
+
+    /// Traite uniquement les donations, sans miner
+    #[arg(long, env = "ONLY_DONATE")]
+    pub only_donate: bool,
+
+    /// Importe au démarrage une clé existante dans les wallets de l'instance : fichier `payment.skey`
+    /// de cardano-cli, ou fichier contenant une clé secrète ed25519 hex (32 octets) ou `ed25519_sk1...`
+    #[arg(long, env = "IMPORT_KEY_FILE")]
+    pub import_key_file: Option<String>,
+}
+
+impl Cli {
+    /// Analyse la ligne de commande ; l'aide des options reprises de `config` (fichier et
+    /// variables `APP_*` déjà fusionnés) indique la valeur utilisée sans l'option.
+    pub fn parse_with_effective(config: Option<&Config>) -> Self {
+        let mut command = Cli::command();
+        if let Some(config) = config {
+            let effective = [
+                ("base_url", config.base_url.clone()),
+                ("threads", config.threads.to_string()),
+                ("max_wallets", config.max_wallets.to_string()),
+                ("log_level", config.log_level.clone()),
+            ];
+            for (id, value) in effective {
+                command = command.mut_arg(id, |arg| {
+                    let help = arg.get_help().map(|h| h.to_string()).unwrap_or_default();
+                    arg.help(format!("{} [valeur effective : {}]", help, value))
+                });
+            }
+        }
+        Cli::from_arg_matches(&command.get_matches()).unwrap_or_else(|e| e.exit())
+    }
+}
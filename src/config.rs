@@ -4,6 +4,44 @@
 
 use serde::Deserialize;
 use std::error::Error;
+use std::fmt;
+use std::path::Path;
+
+use crate::cli::Cli;
+
+/// Niveaux de log acceptés pour `log_level`
+const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+/// Champ de configuration invalide, détecté par `Config::validate`
+#[derive(Debug)]
+pub enum ConfigError {
+    InvalidBaseUrl { value: String, reason: String },
+    MissingWalletKey(String),
+    UnknownLogLevel(String),
+    NotPositive { name: &'static str },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidBaseUrl { value, reason } => {
+                write!(f, "APP_BASE_URL invalide ({:?}) : {}", value, reason)
+            }
+            ConfigError::MissingWalletKey(path) => {
+                write!(f, "APP_WALLET_KEY_PATH ne pointe pas vers un fichier existant : {}", path)
+            }
+            ConfigError::UnknownLogLevel(level) => write!(
+                f,
+                "APP_LOG_LEVEL inconnu ({:?}), valeurs possibles : {}",
+                level,
+                LOG_LEVELS.join(", ")
+            ),
+            ConfigError::NotPositive { name } => write!(f, "{} doit être strictement positif", name),
+        }
+    }
+}
+
+impl Error for ConfigError {}
 
 /// Top‑level configuration struct for the application.
 ///
@@ -12,23 +50,47 @@ use std::error::Error;
 #[derive(Debug, Deserialize)]
 pub struct Config {
     /// Base URL of the Scavenger Mine API (e.g. https://scavenger.prod.gd.midnighttge.io)
+    #[serde(default = "default_base_url")]
     pub base_url: String,
 
     /// Wallet address (Cardano payment address) to be used for this miner
+    #[allow(dead_code)] // wallets are generated per instance, this one is informational
+    #[serde(default)]
     pub address: String,
 
     /// Path to the wallet private key (or key file) for signing
-    pub wallet_key_path: String,
+    #[serde(default)]
+    pub wallet_key_path: Option<String>,
 
     /// Logging level (e.g. "info", "debug")
     #[serde(default = "default_log_level")]
     pub log_level: String,
+
+    /// Total number of mining threads (`MINER_THREADS` / `--threads`), one per CPU by default
+    #[serde(default = "default_threads")]
+    pub threads: usize,
+
+    /// Number of wallets per instance (`MAX_WALLETS_PER_INSTANCE` / `--max-wallets`)
+    #[serde(default = "default_max_wallets")]
+    pub max_wallets: usize,
+}
+
+fn default_base_url() -> String {
+    "https://scavenger.prod.gd.midnighttge.io".to_string()
 }
 
 fn default_log_level() -> String {
     "info".to_string()
 }
 
+fn default_threads() -> usize {
+    num_cpus::get()
+}
+
+fn default_max_wallets() -> usize {
+    1
+}
+
 impl Config {
     /// Load configuration from file `config.toml` (optional) and ENV variables.
     /// Environment variables take precedence and must use prefix `APP_`.
@@ -44,7 +106,8 @@ impl Config {
             // Optionally load config file. Use "config.toml" at working directory.
             .add_source(config::File::with_name("config").required(false))
             // Merge in environment variables with prefix "APP_"
-            .add_source(config::Environment::with_prefix("APP").separator("_"));
+            // (no key separator: APP_BASE_URL maps to `base_url`, not to `base.url`)
+            .add_source(config::Environment::with_prefix("APP"));
 
         // Build the configuration
         let cfg = builder.build()?;
@@ -54,4 +117,67 @@ impl Config {
 
         Ok(settings)
     }
+
+    /// Apply the command-line options that were actually given (flag or their env variable);
+    /// the others keep the file / `APP_` value.
+    pub fn apply_cli(&mut self, cli: &Cli) {
+        if let Some(base_url) = &cli.base_url {
+            self.base_url = base_url.clone();
+        }
+        if let Some(log_level) = &cli.log_level {
+            self.log_level = log_level.clone();
+        }
+        if let Some(threads) = cli.threads {
+            self.threads = threads;
+        }
+        if let Some(max_wallets) = cli.max_wallets {
+            self.max_wallets = max_wallets;
+        }
+    }
+
+    /// Check every field before the miner starts.
+    ///
+    /// All problems are collected and returned together instead of stopping at the first one.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        match reqwest::Url::parse(&self.base_url) {
+            Ok(url) if url.scheme() != "https" => errors.push(ConfigError::InvalidBaseUrl {
+                value: self.base_url.clone(),
+                reason: format!("schéma {:?}, https attendu", url.scheme()),
+            }),
+            Ok(url) if url.host_str().is_none() => errors.push(ConfigError::InvalidBaseUrl {
+                value: self.base_url.clone(),
+                reason: "hôte manquant".to_string(),
+            }),
+            Ok(_) => {}
+            Err(e) => errors.push(ConfigError::InvalidBaseUrl {
+                value: self.base_url.clone(),
+                reason: e.to_string(),
+            }),
+        }
+
+        if let Some(path) = &self.wallet_key_path {
+            if !Path::new(path).is_file() {
+                errors.push(ConfigError::MissingWalletKey(path.clone()));
+            }
+        }
+
+        if !LOG_LEVELS.contains(&self.log_level.to_lowercase().as_str()) {
+            errors.push(ConfigError::UnknownLogLevel(self.log_level.clone()));
+        }
+
+        if self.max_wallets == 0 {
+            errors.push(ConfigError::NotPositive { name: "MAX_WALLETS_PER_INSTANCE" });
+        }
+        if self.threads == 0 {
+            errors.push(ConfigError::NotPositive { name: "MINER_THREADS" });
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 }
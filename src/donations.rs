@@ -1,42 +1,433 @@
-use std::{collections::HashSet, fs, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use chrono::{DateTime, TimeZone, Utc};
 use serde::{Serialize, Deserialize};
 use log::{warn};
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::wallet_container::FileLock;
+
+/// En-tête des exports CSV du registre
+const CSV_HEADER: &str = "original_wallet,destination_address,timestamp";
+
+/// Erreurs d'import du registre des donations
+#[derive(Debug)]
+pub enum DonationError {
+    Io(io::Error),
+    /// Ligne CSV invalide (numérotée à partir de 1)
+    Parse { line: usize, reason: String },
+    /// Base SQLite du registre (`DONATION_DB_BACKEND=sqlite`) inaccessible
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for DonationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DonationError::Io(e) => write!(f, "erreur d'E/S : {}", e),
+            DonationError::Parse { line, reason } => write!(f, "ligne {} : {}", line, reason),
+            DonationError::Sqlite(e) => write!(f, "erreur SQLite : {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DonationError {}
+
+impl From<io::Error> for DonationError {
+    fn from(e: io::Error) -> Self {
+        DonationError::Io(e)
+    }
+}
+
+impl From<rusqlite::Error> for DonationError {
+    fn from(e: rusqlite::Error) -> Self {
+        DonationError::Sqlite(e)
+    }
+}
+
+/// Schéma du registre SQLite : donations réussies (index sur `orig` pour `is_wallet_assigned`),
+/// échecs consécutifs et blacklist, équivalents des champs du registre JSON
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS donations (
+    orig TEXT NOT NULL,
+    dest TEXT NOT NULL,
+    ts INTEGER NOT NULL,
+    UNIQUE (orig, dest)
+);
+CREATE INDEX IF NOT EXISTS donations_orig_idx ON donations (orig);
+CREATE TABLE IF NOT EXISTS donation_failures (
+    orig TEXT PRIMARY KEY,
+    count INTEGER NOT NULL,
+    last_ts INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS donation_blacklist (
+    addr TEXT PRIMARY KEY
+);
+";
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct DonationRegistry {
-    pub completed: HashSet<(String, String)>, // (original_wallet, destination_address)
+    #[serde(with = "completed_format")]
+    pub completed: HashMap<(String, String), DateTime<Utc>>, // (original_wallet, destination_address) -> date
+    /// Échecs consécutifs par wallet d'origine : (nombre, date du dernier échec)
+    #[serde(default)]
+    pub failed: HashMap<String, (u32, DateTime<Utc>)>,
+    /// Adresses de destination refusées (liste de donation potentiellement altérée)
+    #[serde(default)]
+    pub blacklist: HashSet<String>,
+    /// Base SQLite (`with_sqlite`) : si présente, elle remplace les trois champs ci-dessus
+    #[serde(skip)]
+    db: Option<Connection>,
+}
+
+/// Format JSON de `completed` : liste de `[orig, dest, timestamp]`.
+/// L'ancien format (`[orig, dest]`, issu d'un `HashSet`) reste lisible, daté à l'epoch.
+mod completed_format {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Timed(String, String, DateTime<Utc>),
+        Legacy(String, String),
+    }
+
+    pub fn serialize<S: Serializer>(
+        completed: &HashMap<(String, String), DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(completed.iter().map(|((orig, dest), at)| (orig, dest, at)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<(String, String), DateTime<Utc>>, D::Error> {
+        let entries = Vec::<Entry>::deserialize(deserializer)?;
+        let epoch = Utc.timestamp_opt(0, 0).unwrap();
+        Ok(entries
+            .into_iter()
+            .map(|entry| match entry {
+                Entry::Timed(orig, dest, at) => ((orig, dest), at),
+                Entry::Legacy(orig, dest) => ((orig, dest), epoch),
+            })
+            .collect())
+    }
 }
 
 impl DonationRegistry {
-    /// Charge le registre depuis un fichier JSON (ou crée vide)
+    /// Ouvre (ou crée) un registre SQLite à `db_path`. Chaque modification y est écrite immédiatement,
+    /// `save` n'a alors plus rien à faire ; la base peut être partagée entre instances.
+    pub fn with_sqlite(db_path: &Path) -> Result<Self, DonationError> {
+        let db = Connection::open(db_path)?;
+        db.busy_timeout(Duration::from_secs(5))?;
+        db.execute_batch(SQLITE_SCHEMA)?;
+        Ok(Self { db: Some(db), ..Self::default() })
+    }
+
+    /// Charge le registre depuis un fichier JSON (ou crée vide).
+    /// Un fichier illisible est conservé sous `<nom>.corrupt-<date>` avant de repartir d'un registre vide.
     pub fn load(path: &Path) -> Self {
-        if let Ok(text) = fs::read_to_string(path) {
-            if let Ok(reg) = serde_json::from_str(&text) {
-                return reg;
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("⚠️ Impossible de lire le registre des donations {:?}: {}", path, e);
+                return Self::default();
+            }
+        };
+        match serde_json::from_str(&text) {
+            Ok(reg) => reg,
+            Err(e) => {
+                let mut backup = path.as_os_str().to_owned();
+                backup.push(format!(".corrupt-{}", Utc::now().format("%Y%m%dT%H%M%S")));
+                let backup = PathBuf::from(backup);
+                match fs::rename(path, &backup) {
+                    Ok(()) => warn!("⚠️ Registre des donations corrompu ({}), sauvegardé sous {:?}", e, backup),
+                    Err(re) => warn!("⚠️ Registre des donations corrompu ({}), sauvegarde impossible: {}", e, re),
+                }
+                Self::default()
             }
         }
-        Self::default()
     }
 
-    /// Sauvegarde le registre sur disque
-    pub fn save(&self, path: &Path) {
-        if let Err(e) = fs::write(path, serde_json::to_string_pretty(self).unwrap()) {
+    /// Sauvegarde le registre sur disque, sous verrou (`<nom>.lock`) partagé entre instances.
+    ///
+    /// Le fichier courant est d'abord relu et fusionné (les donations d'autres instances ne sont
+    /// pas écrasées), puis réécrit via un fichier temporaire renommé atomiquement.
+    pub fn save(&mut self, path: &Path) {
+        if self.db.is_some() {
+            return;
+        }
+        let _lock = match FileLock::acquire(&path.with_extension("lock"), Duration::from_secs(5)) {
+            Ok(lock) => lock,
+            Err(e) => {
+                warn!("⚠️ Registre des donations non sauvegardé: {}", e);
+                return;
+            }
+        };
+
+        self.merge(Self::load(path));
+
+        let tmp = path.with_extension("tmp");
+        let result = fs::write(&tmp, serde_json::to_string_pretty(self).unwrap())
+            .and_then(|_| fs::rename(&tmp, path));
+        if let Err(e) = result {
             warn!("⚠️ Impossible d’écrire le registre des donations: {}", e);
         }
     }
 
-    /// Vérifie si une donation a déjà été effectuée pour une paire spécifique
-    pub fn already_done(&self, orig: &str, dest: &str) -> bool {
-        self.completed.contains(&(orig.to_string(), dest.to_string()))
+    /// Copie du registre JSON à sauvegarder hors verrou (`save` sur la copie) ;
+    /// `None` pour un registre SQLite, dont les modifications sont déjà écrites.
+    pub fn snapshot(&self) -> Option<Self> {
+        if self.db.is_some() {
+            return None;
+        }
+        Some(Self {
+            completed: self.completed.clone(),
+            failed: self.failed.clone(),
+            blacklist: self.blacklist.clone(),
+            db: None,
+        })
+    }
+
+    /// Vérifie si une donation a déjà été effectuée pour une paire spécifique.
+    /// Une erreur SQLite (base verrouillée...) est remontée : l'appelant ne doit pas donner à l'aveugle.
+    pub fn already_done(&self, orig: &str, dest: &str) -> Result<bool, DonationError> {
+        match &self.db {
+            Some(db) => Ok(db
+                .query_row("SELECT 1 FROM donations WHERE orig = ?1 AND dest = ?2", params![orig, dest], |_| Ok(()))
+                .optional()?
+                .is_some()),
+            None => Ok(self.completed.contains_key(&(orig.to_string(), dest.to_string()))),
+        }
+    }
+
+    /// Vérifie si un wallet a déjà été associé à une adresse de donation (erreur SQLite remontée)
+    pub fn is_wallet_assigned(&self, orig: &str) -> Result<bool, DonationError> {
+        match &self.db {
+            Some(db) => Ok(db
+                .query_row("SELECT 1 FROM donations WHERE orig = ?1 LIMIT 1", params![orig], |_| Ok(()))
+                .optional()?
+                .is_some()),
+            None => Ok(self.completed.keys().any(|(o, _)| o == orig)),
+        }
+    }
+
+    /// Date de la dernière donation effectuée depuis `orig`
+    pub fn last_donation_time(&self, orig: &str) -> Option<DateTime<Utc>> {
+        match &self.db {
+            Some(db) => sql_or_warn(
+                db.query_row("SELECT MAX(ts) FROM donations WHERE orig = ?1", params![orig], |row| {
+                    row.get::<_, Option<i64>>(0)
+                })
+                .map(|ts| ts.and_then(|ts| Utc.timestamp_opt(ts, 0).single())),
+                None,
+            ),
+            None => self
+                .completed
+                .iter()
+                .filter(|((o, _), _)| o == orig)
+                .map(|(_, at)| *at)
+                .max(),
+        }
+    }
+
+    /// Nombre de donations enregistrées
+    pub fn completed_count(&self) -> usize {
+        match &self.db {
+            Some(db) => sql_or_warn(
+                db.query_row("SELECT COUNT(*) FROM donations", [], |row| row.get::<_, i64>(0))
+                    .map(|n| n as usize),
+                0,
+            ),
+            None => self.completed.len(),
+        }
     }
 
-    /// Vérifie si un wallet a déjà été associé à une adresse de donation
-    pub fn is_wallet_assigned(&self, orig: &str) -> bool {
-        self.completed.iter().any(|(o, _)| o == orig)
+    /// Nombre d'adresses blacklistées
+    pub fn blacklist_count(&self) -> usize {
+        match &self.db {
+            Some(db) => sql_or_warn(
+                db.query_row("SELECT COUNT(*) FROM donation_blacklist", [], |row| row.get::<_, i64>(0))
+                    .map(|n| n as usize),
+                0,
+            ),
+            None => self.blacklist.len(),
+        }
     }
 
     /// Enregistre une donation comme réussie
     pub fn mark_done(&mut self, orig: &str, dest: &str) {
-        self.completed.insert((orig.to_string(), dest.to_string()));
+        self.mark_done_at(orig, dest, Utc::now());
+    }
+
+    /// Enregistre une donation comme réussie à une date donnée
+    pub fn mark_done_at(&mut self, orig: &str, dest: &str, at: DateTime<Utc>) {
+        if let Some(db) = &self.db {
+            let result = db
+                .execute(
+                    "INSERT INTO donations (orig, dest, ts) VALUES (?1, ?2, ?3)
+                     ON CONFLICT (orig, dest) DO UPDATE SET ts = MAX(ts, excluded.ts)",
+                    params![orig, dest, at.timestamp()],
+                )
+                .and_then(|_| db.execute("DELETE FROM donation_failures WHERE orig = ?1", params![orig]));
+            sql_or_warn(result.map(|_| ()), ());
+            return;
+        }
+        self.completed.insert((orig.to_string(), dest.to_string()), at);
+        self.failed.remove(orig);
+    }
+
+    /// Enregistre un échec de donation pour `orig` et renvoie le nombre d'échecs consécutifs
+    pub fn record_failure(&mut self, orig: &str) -> u32 {
+        if let Some(db) = &self.db {
+            return sql_or_warn(
+                db.query_row(
+                    "INSERT INTO donation_failures (orig, count, last_ts) VALUES (?1, 1, ?2)
+                     ON CONFLICT (orig) DO UPDATE SET count = count + 1, last_ts = excluded.last_ts
+                     RETURNING count",
+                    params![orig, Utc::now().timestamp()],
+                    |row| row.get::<_, u32>(0),
+                ),
+                1,
+            );
+        }
+        let entry = self.failed.entry(orig.to_string()).or_insert((0, Utc::now()));
+        entry.0 += 1;
+        entry.1 = Utc::now();
+        entry.0
+    }
+
+    /// Vrai si `orig` a échoué au moins `max_failures` fois et que son dernier échec date de moins de `cooldown`
+    pub fn in_failure_backoff(&self, orig: &str, max_failures: u32, cooldown: chrono::Duration) -> bool {
+        let failure = match &self.db {
+            Some(db) => sql_or_warn(
+                db.query_row(
+                    "SELECT count, last_ts FROM donation_failures WHERE orig = ?1",
+                    params![orig],
+                    |row| Ok((row.get::<_, u32>(0)?, row.get::<_, i64>(1)?)),
+                )
+                .optional()
+                .map(|row| row.and_then(|(count, ts)| Some((count, Utc.timestamp_opt(ts, 0).single()?)))),
+                None,
+            ),
+            None => self.failed.get(orig).copied(),
+        };
+        failure.is_some_and(|(count, last)| count >= max_failures && Utc::now() - last < cooldown)
+    }
+
+    /// Interdit toute donation vers `addr` (persisté à la prochaine sauvegarde).
+    /// Renvoie `false` si l'adresse était déjà blacklistée.
+    pub fn add_to_blacklist(&mut self, addr: &str) -> bool {
+        match &self.db {
+            Some(db) => sql_or_warn(
+                db.execute("INSERT OR IGNORE INTO donation_blacklist (addr) VALUES (?1)", params![addr.trim()])
+                    .map(|inserted| inserted > 0),
+                false,
+            ),
+            None => self.blacklist.insert(addr.trim().to_string()),
+        }
+    }
+
+    /// Vrai si les donations vers `addr` sont interdites (erreur SQLite remontée)
+    pub fn is_blacklisted(&self, addr: &str) -> Result<bool, DonationError> {
+        match &self.db {
+            Some(db) => Ok(db
+                .query_row("SELECT 1 FROM donation_blacklist WHERE addr = ?1", params![addr.trim()], |_| Ok(()))
+                .optional()?
+                .is_some()),
+            None => Ok(self.blacklist.contains(addr.trim())),
+        }
+    }
+
+    /// Exporte l'historique en CSV (`original_wallet,destination_address,timestamp`), trié par date croissante
+    pub fn export_csv<W: Write>(&self, mut writer: W) -> Result<(), io::Error> {
+        let mut rows: Vec<((String, String), DateTime<Utc>)> = match &self.db {
+            Some(db) => {
+                let mut stmt = db.prepare("SELECT orig, dest, ts FROM donations").map_err(io::Error::other)?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok(((row.get::<_, String>(0)?, row.get::<_, String>(1)?), row.get::<_, i64>(2)?))
+                    })
+                    .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+                    .map_err(io::Error::other)?;
+                rows.into_iter()
+                    .filter_map(|(pair, ts)| Some((pair, Utc.timestamp_opt(ts, 0).single()?)))
+                    .collect()
+            }
+            None => self.completed.iter().map(|(pair, at)| (pair.clone(), *at)).collect(),
+        };
+        rows.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+
+        writeln!(writer, "{}", CSV_HEADER)?;
+        for ((orig, dest), at) in rows {
+            writeln!(writer, "{},{},{}", orig, dest, at.to_rfc3339())?;
+        }
+        writer.flush()
     }
+
+    /// Lit un export CSV produit par `export_csv` (en-tête optionnel, lignes vides ignorées)
+    pub fn import_csv<R: Read>(reader: R) -> Result<Self, DonationError> {
+        let mut registry = Self::default();
+        for (idx, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || (idx == 0 && line == CSV_HEADER) {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [orig, dest, at] = fields[..] else {
+                return Err(DonationError::Parse {
+                    line: idx + 1,
+                    reason: format!("3 colonnes attendues, {} trouvées", fields.len()),
+                });
+            };
+            if orig.is_empty() || dest.is_empty() {
+                return Err(DonationError::Parse { line: idx + 1, reason: "adresse vide".into() });
+            }
+            let at = DateTime::parse_from_rfc3339(at)
+                .map_err(|e| DonationError::Parse { line: idx + 1, reason: format!("date invalide ({})", e) })?
+                .with_timezone(&Utc);
+
+            registry.mark_done_at(orig, dest, at);
+        }
+        Ok(registry)
+    }
+
+    /// Fusionne un autre registre dans celui-ci (la date la plus récente l'emporte, les blacklists
+    /// s'additionnent). Les échecs des wallets ayant abouti à une donation sont oubliés.
+    pub fn merge(&mut self, other: DonationRegistry) {
+        for (pair, at) in other.completed {
+            self.completed
+                .entry(pair)
+                .and_modify(|existing| *existing = (*existing).max(at))
+                .or_insert(at);
+        }
+        for (orig, failure) in other.failed {
+            self.failed
+                .entry(orig)
+                .and_modify(|existing| {
+                    if failure.1 > existing.1 {
+                        *existing = failure;
+                    }
+                })
+                .or_insert(failure);
+        }
+        self.blacklist.extend(other.blacklist);
+        let completed = &self.completed;
+        self.failed.retain(|orig, _| !completed.keys().any(|(o, _)| o == orig));
+    }
+}
+
+/// Résultat d'une requête sur le registre SQLite ; une erreur est journalisée et remplacée par `fallback`
+fn sql_or_warn<T>(result: rusqlite::Result<T>, fallback: T) -> T {
+    result.unwrap_or_else(|e| {
+        warn!("⚠️ Registre des donations SQLite : {}", e);
+        fallback
+    })
 }
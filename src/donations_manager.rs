@@ -1,13 +1,17 @@
-use std::{fs, path::Path, sync::Arc};
+use std::{fs, path::{Path, PathBuf}, sync::Arc};
 use log::{info, warn, error, debug};
-use rand::{seq::SliceRandom, rngs::StdRng, SeedableRng};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::seq::SliceRandom;
 use std::collections::HashMap;
-use crate::api_client::ApiClient;
-use crate::wallet::Wallet;
-use crate::WalletContainer;
-use crate::donations::DonationRegistry;
-use parking_lot::RwLock;
+use crate::api_client::{ApiClientTrait, ApiError};
+use crate::wallet::{AddressError, AddressType, MnemonicLength, Wallet};
+use crate::wallet_container::{wallet_address_type, wallet_passphrase, WalletContainer};
+use crate::donations::{DonationError, DonationRegistry};
+use crate::retry::{self, RetryConfig};
+use futures::{stream, StreamExt};
+use parking_lot::Mutex;
 use std::str::FromStr;
+use tokio_util::sync::CancellationToken;
 
 
 /// Charge ou crée la liste d’adresses de donation
@@ -17,6 +21,19 @@ pub fn load_or_create_donate_addresses(config_root: &str, use_mainnet: bool, ins
     let donate_seeds_path = Path::new(config_root).join("donate_list_seed.txt");
 
     let mut donate_addresses: Vec<String> = Vec::new();
+    // Une liste existante mais entièrement invalide n'est pas écrasée par une liste générée
+    let mut had_entries = false;
+    let fallback = std::env::var("DONATE_FALLBACK_ADDR")
+        .ok()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .filter(|a| {
+            let valid = Wallet::is_valid_bech32_address(a, use_mainnet);
+            if !valid {
+                warn!("⚠️ [{}] DONATE_FALLBACK_ADDR invalide, ignorée : {}", instance_id, a);
+            }
+            valid
+        });
 
     if donate_list_path.exists() {
         debug!("📄 [{}] Fichier donate_list.txt trouvé : {:?}", instance_id, donate_list_path);
@@ -26,7 +43,20 @@ pub fn load_or_create_donate_addresses(config_root: &str, use_mainnet: bool, ins
                 .filter(|l| !l.trim().is_empty())
                 .map(|l| l.trim().to_string())
                 .collect();
+            had_entries = !donate_addresses.is_empty();
+            donate_addresses.retain(|addr| {
+                let valid = Wallet::is_valid_bech32_address(addr, use_mainnet);
+                if !valid {
+                    warn!("⚠️ [{}] Adresse de donation invalide ignorée : {}", instance_id, addr);
+                }
+                valid
+            });
             info!("💰 [{}] Liste de donation chargée ({} adresses)", instance_id, donate_addresses.len());
+            if let Some(fallback) = &fallback {
+                if !donate_addresses.contains(fallback) {
+                    info!("🧩 [{}] DONATE_FALLBACK_ADDR ignorée : donate_list.txt existe déjà et ne la contient pas", instance_id);
+                }
+            }
         } else {
             warn!("⚠️ [{}] Impossible de lire la liste de donation, tentative de recréation...", instance_id);
         }
@@ -34,23 +64,32 @@ pub fn load_or_create_donate_addresses(config_root: &str, use_mainnet: bool, ins
         warn!("⚠️ [{}] Aucun fichier donate_list.txt trouvé", instance_id);
     }
 
-    if donate_addresses.is_empty() {
+    if donate_addresses.is_empty() && had_entries {
+        warn!(
+            "⚠️ [{}] Aucune adresse valide dans donate_list.txt, fichier conservé et donations désactivées",
+            instance_id
+        );
+    } else if donate_addresses.is_empty() {
         warn!("⚠️ [{}] Pas de liste de donation trouvée, création automatique...", instance_id);
 
         let mut seeds = Vec::new();
         let mut addresses = Vec::new();
 
         for i in 0..3 {
-            let w = Wallet::generate(use_mainnet);
+            let w = Wallet::generate(use_mainnet, MnemonicLength::default(), AddressType::default());
             debug!("🪙 [{}] Wallet de donation {} généré: {}", instance_id, i + 1, w.address);
             seeds.push(w.mnemonic.clone().unwrap_or_default());
             addresses.push(w.address.clone());
         }
 
-        // Adresse fallback connue
-        let fallback = "addr1q8cd35r4dcrl4k4prmqwjutyrl677xyjw7re82x6vm4t7vtmrd3ueldxpq74m47dtr03ppesr5ral6plt7acy5gjph5surek0h".to_string();
-        addresses.push(fallback.clone());
-        debug!("🧩 [{}] Adresse fallback ajoutée : {}", instance_id, fallback);
+        // Adresse fallback optionnelle (DONATE_FALLBACK_ADDR), aucune par défaut
+        match fallback.clone() {
+            Some(fallback) => {
+                info!("🧩 [{}] Adresse fallback de donation ajoutée (DONATE_FALLBACK_ADDR) : {}", instance_id, fallback);
+                addresses.push(fallback);
+            }
+            None => info!("🧩 [{}] Aucune adresse fallback de donation (DONATE_FALLBACK_ADDR non défini)", instance_id),
+        }
 
         if let Err(e) = fs::write(&donate_list_path, addresses.join("\n")) {
             warn!("❌ [{}] Impossible d’écrire donate_list.txt: {}", instance_id, e);
@@ -67,154 +106,593 @@ pub fn load_or_create_donate_addresses(config_root: &str, use_mainnet: bool, ins
     donate_addresses
 }
 
+/// Charge `donate_weights.txt` (une paire `adresse:poids` par ligne) et renvoie les poids
+/// alignés sur `donate_addresses` (0 pour une adresse absente du fichier).
+/// `None` (tirage uniforme) si le fichier n'existe pas ou est invalide : poids négatif ou non
+/// numérique, ou aucun poids strictement positif.
+pub fn load_donate_weights(config_root: &str, donate_addresses: &[String], instance_id: &str) -> Option<WeightedIndex<f64>> {
+    let weights_path = Path::new(config_root).join("donate_weights.txt");
+    let contents = fs::read_to_string(&weights_path).ok()?;
+
+    let mut by_address: HashMap<String, f64> = HashMap::new();
+    for (line, raw) in contents.lines().enumerate() {
+        let raw = raw.trim();
+        if raw.is_empty() || raw.starts_with('#') {
+            continue;
+        }
+        let parsed = raw
+            .rsplit_once(':')
+            .and_then(|(addr, w)| Some((addr.trim(), w.trim().parse::<f64>().ok()?)));
+        match parsed {
+            Some((addr, w)) if w.is_finite() && w >= 0.0 => {
+                by_address.insert(addr.to_string(), w);
+            }
+            _ => {
+                warn!("⚠️ [{}] donate_weights.txt ligne {} invalide (`adresse:poids` positif attendu), tirage uniforme", instance_id, line + 1);
+                return None;
+            }
+        }
+    }
 
+    for addr in by_address.keys().filter(|a| !donate_addresses.contains(a)) {
+        warn!("⚠️ [{}] donate_weights.txt : {} absente de donate_list.txt, ignorée", instance_id, addr);
+    }
 
-pub async fn process_donations_for_wallets(
-    client: Arc<ApiClient>,
-    wallets_path: &str,
-    donate_addresses: &[String],
-    instance_id: &str,
-    uniq_inst_id: &str,
-) {
-    info!("🚀 [{}] Démarrage du processus de donation...", instance_id);
-
-    let base_path = Path::new("./config");
-
-    // --- Ajout des statistiques locales ---
-    let mut total_attempts = 0usize;
-    let mut total_success = 0usize;
-    let mut total_fail = 0usize;
-    let mut error_stats: HashMap<String, usize> = HashMap::new();
-
-    let donate_registry_path = Path::new("/usr/local/bin/config/donations_log.json");
-    let mut donation_registry = DonationRegistry::load(donate_registry_path);
-    info!("📒 [{}] Registre de donations chargé : {} entrées", instance_id, donation_registry.completed.len());
-
-    // Lister tous les dossiers qui correspondent à un numéro entier
-    let mut valid_ids: Vec<usize> = Vec::new();
-    if let Ok(entries) = fs::read_dir(base_path) {
-        for entry in entries.filter_map(Result::ok) {
-            if let Some(name) = entry.file_name().to_str() {
-                if let Ok(id) = usize::from_str(name) {
-                    valid_ids.push(id);
+    let weights: Vec<f64> = donate_addresses
+        .iter()
+        .map(|addr| by_address.get(addr).copied().unwrap_or(0.0))
+        .collect();
+    match WeightedIndex::new(&weights) {
+        Ok(index) => {
+            info!("⚖️ [{}] Pondération des donations chargée ({} adresses pondérées)", instance_id, weights.iter().filter(|w| **w > 0.0).count());
+            Some(index)
+        }
+        Err(e) => {
+            warn!("⚠️ [{}] donate_weights.txt inutilisable ({}), tirage uniforme", instance_id, e);
+            None
+        }
+    }
+}
+
+/// Résultat d'une tentative de donation pour un wallet
+enum DonationOutcome {
+    Skipped,
+    /// Trop d'échecs récents pour ce wallet : nouvelle tentative après le cooldown
+    BackedOff,
+    Success,
+    Failed(String),
+    /// `DONATION_DRY_RUN` : donation journalisée mais ni envoyée ni enregistrée
+    DryRun(PlannedDonation),
+}
+
+/// Donation qui aurait été envoyée en mode `DONATION_DRY_RUN`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedDonation {
+    pub source: String,
+    pub destination: String,
+}
+
+/// Donations prévues par un cycle en mode `DONATION_DRY_RUN` (vide hors dry run)
+#[derive(Debug, Clone, Default)]
+pub struct DryRunReport {
+    pub planned: Vec<PlannedDonation>,
+}
+
+/// Message signé pour `/donate_to`, v1 (format actuel du serveur) ; `{dest}` = adresse de destination
+pub const DONATION_MESSAGE_TEMPLATE_V1: &str = "Assign accumulated Scavenger rights to: {dest}";
+
+/// Substitue l'adresse de destination dans un modèle de message de donation
+pub fn donation_message(template: &str, dest: &str) -> String {
+    template.replace("{dest}", dest)
+}
+
+/// Modèle de message à partir de `DONATION_MESSAGE_TEMPLATE` : `DONATION_MESSAGE_TEMPLATE_V1` si absent
+/// ou vide, `Err` avec la valeur reçue si elle ne contient pas `{dest}`.
+pub fn message_template(raw: Option<String>) -> Result<String, String> {
+    match raw {
+        Some(template) if template.contains("{dest}") => Ok(template),
+        Some(template) if !template.is_empty() => Err(template),
+        _ => Ok(DONATION_MESSAGE_TEMPLATE_V1.to_string()),
+    }
+}
+
+/// Traite les donations de plusieurs wallets en parallèle (au plus `concurrency` à la fois).
+pub struct DonationProcessor {
+    client: Arc<dyn ApiClientTrait>,
+    donate_addresses: Vec<String>,
+    /// Poids alignés sur `donate_addresses` ; `None` = tirage uniforme
+    donate_weights: Option<WeightedIndex<f64>>,
+    registry: Arc<Mutex<DonationRegistry>>,
+    registry_path: PathBuf,
+    concurrency: usize,
+    max_failures: u32,
+    failure_cooldown: chrono::Duration,
+    /// Nouvelles tentatives d'un `/donate_to` échoué (`DONATION_RETRY_*`)
+    retry: RetryConfig,
+    /// `DONATION_DRY_RUN=true` : aucun appel à `/donate_to` ni mise à jour du registre
+    dry_run: bool,
+    /// Modèle du message signé (`DONATION_MESSAGE_TEMPLATE`, contient `{dest}`)
+    message_template: String,
+    instance_id: String,
+    uniq_inst_id: String,
+}
+
+impl DonationProcessor {
+    /// `concurrency` vaut `DONATION_CONCURRENCY` (4 par défaut) ; le registre est chargé depuis `registry_path`.
+    /// Un wallet ayant échoué `DONATION_MAX_FAILURES` fois (5) n'est retenté qu'après
+    /// `DONATION_FAILURE_COOLDOWN_SECS` (86400) depuis son dernier échec.
+    /// Chaque donation est retentée avec backoff exponentiel (`DONATION_RETRY_MAX_ATTEMPTS`, 2 par défaut)
+    /// avant d'être comptée comme un échec.
+    /// Les adresses de `DONATION_BLACKLIST` (séparées par des virgules) sont ajoutées à la blacklist
+    /// du registre ; l'adresse fallback n'y figure pas par défaut mais peut y être ajoutée.
+    /// Avec `DONATION_DRY_RUN=true`, les donations sont seulement journalisées (voir `DryRunReport`).
+    /// Le message signé suit `DONATION_MESSAGE_TEMPLATE` s'il contient `{dest}`, sinon `DONATION_MESSAGE_TEMPLATE_V1`.
+    pub fn new(
+        client: Arc<dyn ApiClientTrait>,
+        donate_addresses: Vec<String>,
+        donate_weights: Option<WeightedIndex<f64>>,
+        registry_path: PathBuf,
+        instance_id: &str,
+        uniq_inst_id: &str,
+    ) -> Self {
+        let concurrency = std::env::var("DONATION_CONCURRENCY")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|c| *c > 0)
+            .unwrap_or(4);
+        let max_failures = std::env::var("DONATION_MAX_FAILURES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|m| *m > 0)
+            .unwrap_or(5);
+        let failure_cooldown = chrono::Duration::seconds(
+            std::env::var("DONATION_FAILURE_COOLDOWN_SECS")
+                .ok()
+                .and_then(|v| v.parse::<i64>().ok())
+                .unwrap_or(86_400),
+        );
+        let mut registry = match std::env::var("DONATION_DB_BACKEND").unwrap_or_default().to_lowercase().as_str() {
+            "sqlite" => {
+                let db_path = registry_path.with_extension("sqlite");
+                match DonationRegistry::with_sqlite(&db_path) {
+                    Ok(registry) => {
+                        info!("🗄️ [{}] Registre de donations SQLite : {:?}", instance_id, db_path);
+                        registry
+                    }
+                    Err(e) => {
+                        error!("❌ [{}] Registre SQLite {:?} inutilisable ({}), repli sur le JSON", instance_id, db_path, e);
+                        DonationRegistry::load(&registry_path)
+                    }
                 }
             }
+            _ => DonationRegistry::load(&registry_path),
+        };
+        info!("📒 [{}] Registre de donations chargé : {} entrées", instance_id, registry.completed_count());
+
+        let blacklist = std::env::var("DONATION_BLACKLIST").unwrap_or_default();
+        let mut blacklisted = 0;
+        for addr in blacklist.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+            if registry.add_to_blacklist(addr) {
+                blacklisted += 1;
+            }
+        }
+        if blacklisted > 0 {
+            info!("🚫 [{}] {} adresse(s) ajoutée(s) à la blacklist des donations", instance_id, blacklisted);
+            registry.save(&registry_path);
+        }
+        let blacklist_count = registry.blacklist_count();
+        if blacklist_count > 0 {
+            info!("🚫 [{}] Blacklist des donations : {} adresse(s)", instance_id, blacklist_count);
+        }
+        let dry_run = std::env::var("DONATION_DRY_RUN")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+        if dry_run {
+            info!("🧪 [{}] DONATION_DRY_RUN actif : aucune donation ne sera envoyée", instance_id);
+        }
+        let message_template = match message_template(std::env::var("DONATION_MESSAGE_TEMPLATE").ok()) {
+            Ok(template) => template,
+            Err(template) => {
+                error!(
+                    "❌ [{}] DONATION_MESSAGE_TEMPLATE sans {{dest}} ignoré ({:?}), modèle v1 utilisé",
+                    instance_id, template
+                );
+                DONATION_MESSAGE_TEMPLATE_V1.to_string()
+            }
+        };
+        info!("✍️ [{}] Message de donation signé : {:?}", instance_id, message_template);
+
+        Self {
+            client,
+            donate_addresses,
+            donate_weights,
+            registry: Arc::new(Mutex::new(registry)),
+            registry_path,
+            concurrency,
+            max_failures,
+            failure_cooldown,
+            retry: RetryConfig::from_env("DONATION", 2),
+            dry_run,
+            message_template,
+            instance_id: instance_id.to_string(),
+            uniq_inst_id: uniq_inst_id.to_string(),
         }
     }
 
-    // Trier les ids dans l'ordre croissant
-    valid_ids.sort();
-    debug!("📒 [{}] Fetched {} miners for donations", instance_id, valid_ids.len());
-    for id in valid_ids {
-        let id_str = id.to_string();
+    /// Charge les wallets de tous les mineurs de `base_path` (`<id>/miner-<id>/wallets`), triés par id
+    fn collect_wallets(&self, base_path: &Path) -> Vec<Wallet> {
+        // Lister tous les dossiers qui correspondent à un numéro entier
+        let mut valid_ids: Vec<usize> = Vec::new();
+        if let Ok(entries) = fs::read_dir(base_path) {
+            for entry in entries.filter_map(Result::ok) {
+                if let Some(name) = entry.file_name().to_str() {
+                    if let Ok(id) = usize::from_str(name) {
+                        valid_ids.push(id);
+                    }
+                }
+            }
+        }
+
+        // Trier les ids dans l'ordre croissant
+        valid_ids.sort();
+        debug!("📒 [{}] Fetched {} miners for donations", self.instance_id, valid_ids.len());
 
-        // Construit les chemins d'intérêt
-        let seeds_path = base_path.join(&id_str).join(format!("miner-{id}/wallets/seeds.txt"));
-        let keys_path = base_path.join(&id_str).join(format!("miner-{id}/wallets/keys.hex"));
+        let mut all_wallets = Vec::new();
+        for id in valid_ids {
+            let id_str = id.to_string();
 
-        let mut rng = StdRng::from_entropy();
+            // Construit les chemins d'intérêt
+            let seeds_path = base_path.join(&id_str).join(format!("miner-{id}/wallets/seeds.txt"));
+            let keys_path = base_path.join(&id_str).join(format!("miner-{id}/wallets/keys.hex"));
 
-        if seeds_path.exists() && keys_path.exists() {
+            if !(seeds_path.exists() && keys_path.exists()) {
+                debug!("⏭️ Config incomplète ou absente pour miner-{id}");
+                continue;
+            }
             debug!("🔧 Config valide pour miner-{id}:");
             debug!("   -> {:?}", seeds_path);
             debug!("   -> {:?}", keys_path);
 
-            match Wallet::load_many_from_files(&seeds_path, &keys_path, true) {
+            let address_type = wallet_address_type();
+            match Wallet::load_many_from_files(&seeds_path, &keys_path, wallet_passphrase().as_deref(), true, address_type) {
                 Ok(w) => {
-                    let container = WalletContainer::new(w, seeds_path.clone(), keys_path.clone(), true);
-                    let w_list = Arc::new(container);
-                    let wallets = w_list.read_all();
-
-                    debug!("💼 [{}] {} wallets chargés pour rediriger les donations", instance_id, wallets.len());
-
-                    for (_idx, wallet) in wallets.into_iter().enumerate() {
-                        debug!("🔓 [{}] Wallet chargé: {}", instance_id, wallet.address);
-
-                        if donation_registry.is_wallet_assigned(&wallet.address) {
-                            debug!("🔁 [{}] Wallet {} déjà assigné à une donation, skip.", instance_id, wallet.address);
-                            continue;
-                        }
-
-                        if let Some(dest) = donate_addresses.choose(&mut rng) {
-                            debug!("🎯 [{}] Adresse de destination choisie: {}", instance_id, dest);
-                            if dest == &wallet.address {
-                                debug!("⛔ [{}] Auto-donation détectée, ignorée pour {}", instance_id, wallet.address);
-                                continue;
-                            }
-
-                            let message = format!("Assign accumulated Scavenger rights to: {}", dest);
-                            let pubkey = wallet.public_key_hex();
-                            let signature = wallet.sign_cip30(&message);
-                            let signature_8 = match wallet.sign_cip8(&message, &[]) {
-                                Ok(sig) => sig,
-                                Err(err) => {
-                                    eprintln!("Erreur signature CIP8 : {:?}", err);
-                                    return;
-                                },
-                            };
-                            debug!("✍️ Start donation      ");
-                            debug!("   ✍️ Entreprise        : [{}]", wallet.address);
-                            debug!("   ✍️ Shelley Base      : [{}]", wallet.shelley_addr);
-                            debug!("   ✍️ Donate to addr    : [{}]", dest);
-                            debug!("   ✍️ Pub key Hex       : [{}]", pubkey);
-                            debug!("   ✍️ Message plain text: [{}]", message);
-                            debug!("   ✍️ CIP_30 sig        : [{}]", signature);
-                            debug!("   ✍️ CIP_8  sig        : [{}]", signature_8);
-
-                            info!("✍️ [{}] Signature créée pour donation {} → {}", instance_id, wallet.address, dest);
-
-                            total_attempts += 1;
-
-                            match client
-                                .donate_to(dest, &wallet.address, &signature, Some(instance_id.to_string()), Some(uniq_inst_id.to_string()))
-                                .await
-                            {
-                                Ok(resp) => {
-                                    total_success += 1;
-                                    info!(
-                                        "✅ [{}] Donation réussie de {} → {} | status: {:?}",
-                                        instance_id, wallet.address, dest, resp.status
-                                    );
-                                    donation_registry.mark_done(&wallet.address, dest);
-                                    donation_registry.save(donate_registry_path);
-                                    debug!("🧾 [{}] Registre de donation mis à jour", instance_id);
-                                }
-                                Err(e) => {
-                                    total_fail += 1;
-                                    let err_msg = e.to_string();
-                                    *error_stats.entry(err_msg).or_insert(0) += 1;
-                                    debug!("⚠️ [{}] Échec donation {} → {} : {}", instance_id, wallet.address, dest, e);
-                                }
-                            }
-                        } else {
-                            warn!("⚠️ [{}] Aucune adresse de donation valide disponible", instance_id);
-                        }
-                    }
-
+                    let container = WalletContainer::new(w, seeds_path.clone(), keys_path.clone(), true, address_type);
+                    let wallets = container.read_all();
+                    debug!("💼 [{}] {} wallets chargés pour rediriger les donations", self.instance_id, wallets.len());
+                    all_wallets.extend(wallets);
                 }
-
                 Err(e) => {
-                    error!("❌ [{}] Impossible de charger wallet {:?} : {}", instance_id, seeds_path, e);
+                    error!("❌ [{}] Impossible de charger wallet {:?} : {}", self.instance_id, seeds_path, e);
                 }
             }
-        } else {
-            debug!("⏭️ Config incomplète ou absente pour miner-{id}");
+        }
+        all_wallets
+    }
+
+    /// Adresse de destination : tirage pondéré si `donate_weights.txt` est chargé, uniforme sinon
+    fn pick_destination(&self) -> Option<&String> {
+        let mut rng = rand::thread_rng();
+        match &self.donate_weights {
+            Some(weights) => self.donate_addresses.get(weights.sample(&mut rng)),
+            None => self.donate_addresses.choose(&mut rng),
+        }
+    }
+
+    /// Donation d'un wallet vers une adresse tirée au hasard, enregistrée dans le registre si réussie
+    async fn donate_one(&self, wallet: Wallet, cancel: &CancellationToken) -> DonationOutcome {
+        let ctx = format!("[{}|{}]", self.instance_id, wallet.address);
+
+        // Arrêt demandé : les donations en cours se terminent, on n'en commence pas d'autre
+        if cancel.is_cancelled() {
+            return DonationOutcome::Skipped;
+        }
+        let (address, max_failures, cooldown) = (wallet.address.clone(), self.max_failures, self.failure_cooldown);
+        let guards = self
+            .with_registry(move |registry| {
+                Ok((registry.is_wallet_assigned(&address)?, registry.in_failure_backoff(&address, max_failures, cooldown)))
+            })
+            .await;
+        match guards {
+            Ok((true, _)) => {
+                debug!("🔁 {} Wallet déjà assigné à une donation, skip.", ctx);
+                return DonationOutcome::Skipped;
+            }
+            Ok((_, true)) => {
+                debug!("⏸️ {} Trop d'échecs de donation récents, skip jusqu'à la fin du cooldown.", ctx);
+                return DonationOutcome::BackedOff;
+            }
+            Ok(_) => {}
+            // Registre illisible : sans garantie de ne pas donner deux fois, le wallet attend le prochain cycle
+            Err(e) => {
+                warn!("⚠️ {} Registre des donations indisponible ({}), wallet ignoré", ctx, e);
+                return DonationOutcome::Skipped;
+            }
+        }
+
+        let dest = match self.pick_destination() {
+            Some(dest) => dest.clone(),
+            None => {
+                warn!("⚠️ {} Aucune adresse de donation valide disponible", ctx);
+                return DonationOutcome::Skipped;
+            }
+        };
+        debug!("🎯 {} Adresse de destination choisie: {}", ctx, dest);
+        if dest == wallet.address {
+            debug!("⛔ {} Auto-donation détectée, ignorée", ctx);
+            return DonationOutcome::Skipped;
+        }
+        let blacklisted = {
+            let dest = dest.clone();
+            self.with_registry(move |registry| registry.is_blacklisted(&dest)).await
+        };
+        match blacklisted {
+            Ok(false) => {}
+            Ok(true) => {
+                warn!("🚫 {} Adresse de destination blacklistée ({}), donation ignorée", ctx, dest);
+                return DonationOutcome::Skipped;
+            }
+            Err(e) => {
+                warn!("⚠️ {} Blacklist des donations illisible ({}), donation vers {} ignorée", ctx, e, dest);
+                return DonationOutcome::Skipped;
+            }
+        }
+
+        let message = donation_message(&self.message_template, &dest);
+        let pubkey = wallet.public_key_hex();
+        let signature = wallet.sign_cip30(&message);
+        let signature_8 = match wallet.sign_cip8(&message, &[]) {
+            Ok(sig) => sig,
+            // Adresse corrompue : rien à retenter, le wallet est ignoré
+            Err(err) if err.is::<AddressError>() => {
+                warn!("⚠️ {} Wallet ignoré, {}", ctx, err);
+                return DonationOutcome::Skipped;
+            }
+            Err(err) => {
+                error!("❌ {} Erreur signature CIP8 : {:?}", ctx, err);
+                return DonationOutcome::Failed(format!("CIP8: {}", err));
+            }
+        };
+        debug!("✍️ Start donation      ");
+        debug!("   ✍️ Entreprise        : [{}]", wallet.address);
+        debug!("   ✍️ Shelley Base      : [{}]", wallet.shelley_addr);
+        debug!("   ✍️ Stake address     : [{}]", wallet.stake_address().unwrap_or_default());
+        debug!("   ✍️ Donate to addr    : [{}]", dest);
+        debug!("   ✍️ Pub key Hex       : [{}]", pubkey);
+        debug!("   ✍️ Message plain text: [{}]", message);
+        debug!("   ✍️ CIP_30 sig        : [{}]", signature);
+        debug!("   ✍️ CIP_8  sig        : [{}]", signature_8);
+
+        info!("✍️ {} Signature créée pour donation → {}", ctx, dest);
+
+        if self.dry_run {
+            info!("🧪 DRY RUN: would donate from {} to {}", wallet.address, dest);
+            return DonationOutcome::DryRun(PlannedDonation { source: wallet.address.clone(), destination: dest });
+        }
+
+        // Seules les erreurs transitoires sont rejouées : un refus 4xx se reproduirait à chaque essai
+        let donation = retry::retry_if(
+            self.retry.backoff(),
+            || {
+                self.client.donate_to(
+                    &dest,
+                    &wallet.address,
+                    &signature,
+                    Some(self.instance_id.clone()),
+                    Some(self.uniq_inst_id.clone()),
+                )
+            },
+            |e| e.downcast_ref::<ApiError>().is_some_and(ApiError::is_transient),
+        );
+        match donation.await {
+            Ok(resp) => {
+                info!("✅ {} Donation réussie → {} | status: {:?}", ctx, dest, resp.status);
+                let (orig, to) = (wallet.address.clone(), dest.clone());
+                let snapshot = self
+                    .with_registry(move |registry| {
+                        registry.mark_done(&orig, &to);
+                        Ok(registry.snapshot())
+                    })
+                    .await;
+                match snapshot {
+                    Ok(snapshot) => self.persist_registry(snapshot).await,
+                    Err(e) => error!("❌ {} Donation réussie mais non enregistrée : {}", ctx, e),
+                }
+                debug!("🧾 {} Registre de donation mis à jour", ctx);
+                DonationOutcome::Success
+            }
+            Err(e) => {
+                let orig = wallet.address.clone();
+                let recorded = self
+                    .with_registry(move |registry| {
+                        let failures = registry.record_failure(&orig);
+                        Ok((failures, registry.snapshot()))
+                    })
+                    .await;
+                let failures = match recorded {
+                    Ok((failures, snapshot)) => {
+                        self.persist_registry(snapshot).await;
+                        failures
+                    }
+                    Err(e) => {
+                        error!("❌ {} Échec de donation non enregistré : {}", ctx, e);
+                        0
+                    }
+                };
+                debug!("⚠️ {} Échec donation → {} ({} échecs consécutifs) : {}", ctx, dest, failures, e);
+                DonationOutcome::Failed(e.to_string())
+            }
         }
     }
 
-    // --- Résumé des stats pour miner-{id} ---
-    info!("📊 Résumé donations :");
-    info!("   Tentatives totales : {}", total_attempts);
-    info!("   Succès             : {}", total_success);
-    info!("   Échecs             : {}", total_fail);
+    /// Exécute `f` sous le mutex du registre dans un thread bloquant : avec SQLite, une requête peut
+    /// attendre jusqu'à 5 s (`busy_timeout`) le verrou d'une autre instance sans bloquer le runtime.
+    async fn with_registry<T, F>(&self, f: F) -> Result<T, DonationError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut DonationRegistry) -> Result<T, DonationError> + Send + 'static,
+    {
+        let registry = Arc::clone(&self.registry);
+        tokio::task::spawn_blocking(move || f(&mut registry.lock()))
+            .await
+            .unwrap_or_else(|e| Err(DonationError::Io(std::io::Error::other(e))))
+    }
 
-    if !error_stats.is_empty() {
-        info!("   Erreurs distinctes :");
-        for (err, count) in error_stats {
-            info!("     - {} ({}x)", err, count);
+    /// Sauvegarde une copie du registre dans un thread bloquant (verrou fichier, relecture, écriture),
+    /// sans tenir le mutex : les autres donations du cycle ne l'attendent pas.
+    /// Les entrées relues sur disque (autres instances) sont ensuite fusionnées en mémoire.
+    async fn persist_registry(&self, snapshot: Option<DonationRegistry>) {
+        // Registre SQLite : modifications déjà écrites
+        let Some(mut snapshot) = snapshot else {
+            return;
+        };
+        let path = self.registry_path.clone();
+        let saved = tokio::task::spawn_blocking(move || {
+            snapshot.save(&path);
+            snapshot
+        })
+        .await;
+        match saved {
+            Ok(saved) => self.registry.lock().merge(saved),
+            Err(e) => error!("❌ [{}] Sauvegarde du registre de donations interrompue : {}", self.instance_id, e),
         }
     }
-    info!("🏁 [{}] Fin du cycle de donation", instance_id);
+
+    /// Cycle complet : tous les wallets de `base_path`, `concurrency` donations simultanées au plus.
+    /// Si `cancel` est annulé, les donations en cours se terminent et les suivantes sont ignorées.
+    /// Retourne les donations prévues en mode dry run (rapport vide sinon).
+    pub async fn run(&self, base_path: &Path, cancel: &CancellationToken) -> DryRunReport {
+        info!("🚀 [{}] Démarrage du processus de donation (concurrence {})...", self.instance_id, self.concurrency);
+
+        let wallets = self.collect_wallets(base_path);
+        let outcomes: Vec<DonationOutcome> = stream::iter(wallets)
+            .map(|wallet| self.donate_one(wallet, cancel))
+            .buffer_unordered(self.concurrency)
+            .collect()
+            .await;
+
+        // --- Résumé des stats ---
+        let mut total_attempts = 0usize;
+        let mut total_success = 0usize;
+        let mut total_fail = 0usize;
+        let mut total_backed_off = 0usize;
+        let mut error_stats: HashMap<String, usize> = HashMap::new();
+        let mut report = DryRunReport::default();
+        for outcome in outcomes {
+            match outcome {
+                DonationOutcome::Skipped => {}
+                DonationOutcome::BackedOff => total_backed_off += 1,
+                DonationOutcome::Success => {
+                    total_attempts += 1;
+                    total_success += 1;
+                }
+                DonationOutcome::Failed(err) => {
+                    total_attempts += 1;
+                    total_fail += 1;
+                    *error_stats.entry(err).or_insert(0) += 1;
+                }
+                DonationOutcome::DryRun(planned) => report.planned.push(planned),
+            }
+        }
+        if cancel.is_cancelled() {
+            info!("🛑 [{}] Arrêt demandé, cycle de donation interrompu", self.instance_id);
+        }
+
+        info!("📊 Résumé donations :");
+        info!("   Tentatives totales : {}", total_attempts);
+        info!("   Succès             : {}", total_success);
+        info!("   Échecs             : {}", total_fail);
+        info!("   En pause (échecs)  : {}", total_backed_off);
+        if self.dry_run {
+            info!("   Prévues (dry run)  : {}", report.planned.len());
+            for planned in &report.planned {
+                info!("     - {} → {}", planned.source, planned.destination);
+            }
+        }
+
+        if !error_stats.is_empty() {
+            info!("   Erreurs distinctes :");
+            for (err, count) in error_stats {
+                info!("     - {} ({}x)", err, count);
+            }
+        }
+        info!("🏁 [{}] Fin du cycle de donation", self.instance_id);
+        report
+    }
 }
 
+/// Cycle de donation sur tous les wallets des mineurs de `config_root` (voir `DonationProcessor`) ;
+/// poids (`donate_weights.txt`) et registre (`donations_log.json`) sont lus dans ce même dossier.
+/// Si `cancel` est annulé, le cycle s'arrête après les donations en cours.
+/// Retourne les donations prévues si `DONATION_DRY_RUN` est actif.
+pub async fn process_donations_for_wallets(
+    client: Arc<dyn ApiClientTrait>,
+    config_root: &str,
+    donate_addresses: &[String],
+    instance_id: &str,
+    uniq_inst_id: &str,
+    cancel: &CancellationToken,
+) -> DryRunReport {
+    let donate_weights = load_donate_weights(config_root, donate_addresses, instance_id);
+    let processor = DonationProcessor::new(
+        client,
+        donate_addresses.to_vec(),
+        donate_weights,
+        Path::new(config_root).join("donations_log.json"),
+        instance_id,
+        uniq_inst_id,
+    );
+    processor.run(Path::new(config_root), cancel).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_api_client::MockApiClient;
+
+    /// Processeur sur un registre vide propre au test
+    fn processor(client: Arc<MockApiClient>, name: &str, dest: &str) -> DonationProcessor {
+        let dir = std::env::temp_dir().join(format!("scavenger-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        DonationProcessor::new(client, vec![dest.to_string()], None, dir.join("donations_log.json"), "test", "uniq")
+    }
+
+    #[tokio::test]
+    async fn wallet_with_corrupt_address_is_skipped() {
+        let client = Arc::new(MockApiClient::new());
+        let dest = Wallet::generate(false, MnemonicLength::default(), AddressType::default()).address;
+        let processor = processor(Arc::clone(&client), "corrupt-address", &dest);
+
+        let mut wallet = Wallet::generate(false, MnemonicLength::default(), AddressType::default());
+        wallet.address = "garbage!".to_string();
+        let outcome = processor.donate_one(wallet, &CancellationToken::new()).await;
 
+        assert!(matches!(outcome, DonationOutcome::Skipped));
+        assert_eq!(client.call_count("donate_to"), 0);
+        assert_eq!(processor.registry.lock().failed.len(), 0);
+    }
+
+    #[test]
+    fn donation_message_substitutes_dest_exactly() {
+        let dest = "addr_test1qz2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3n0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgs68faae";
+        assert_eq!(
+            donation_message(DONATION_MESSAGE_TEMPLATE_V1, dest),
+            format!("Assign accumulated Scavenger rights to: {}", dest)
+        );
+        // Les guillemets autour de `{dest}` sont conservés tels quels, sans espace ajouté
+        assert_eq!(
+            donation_message("Assign accumulated Scavenger rights to: \"{dest}\"", dest),
+            format!("Assign accumulated Scavenger rights to: \"{}\"", dest)
+        );
+        assert_eq!(donation_message("'{dest}'", "addr1x"), "'addr1x'");
+    }
+
+    #[test]
+    fn message_template_requires_dest() {
+        let custom = message_template(Some("Donate \"{dest}\"".to_string())).unwrap();
+        assert_eq!(donation_message(&custom, "addr_test1dest"), "Donate \"addr_test1dest\"");
+        assert_eq!(message_template(None).unwrap(), DONATION_MESSAGE_TEMPLATE_V1);
+        assert_eq!(message_template(Some(String::new())).unwrap(), DONATION_MESSAGE_TEMPLATE_V1);
+        assert_eq!(message_template(Some("sans adresse".to_string())), Err("sans adresse".to_string()));
+    }
+}
@@ -0,0 +1,130 @@
+// src/health.rs
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use chrono::Utc;
+use log::{info, warn};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::stats_client::WalletCounters;
+
+/// Signaux de vie du miner, mis à jour par les boucles de minage et lus par `/healthz` et `/readyz`
+#[derive(Default)]
+pub struct HealthState {
+    /// Dernière activité des boucles de minage (timestamp Unix en secondes, 0 = jamais)
+    last_activity: AtomicI64,
+    /// Dernière récupération réussie d'un challenge (timestamp Unix en secondes, 0 = jamais)
+    last_challenge_fetch: AtomicI64,
+    wallets_loaded: AtomicBool,
+    /// Total de hashs vu à la dernière sonde, pour détecter des threads de minage qui progressent
+    last_hash_total: AtomicU64,
+}
+
+impl HealthState {
+    /// Signale une itération de la boucle de minage
+    pub fn touch(&self) {
+        self.last_activity.store(Utc::now().timestamp(), Ordering::Relaxed);
+    }
+
+    /// Signale un challenge récupéré avec succès (vaut aussi activité)
+    pub fn mark_challenge_fetched(&self) {
+        let now = Utc::now().timestamp();
+        self.last_challenge_fetch.store(now, Ordering::Relaxed);
+        self.last_activity.store(now, Ordering::Relaxed);
+    }
+
+    pub fn mark_wallets_loaded(&self) {
+        self.wallets_loaded.store(true, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone)]
+struct HealthServerState {
+    health: Arc<HealthState>,
+    wallet_counters: WalletCounters,
+    max_idle: Duration,
+}
+
+/// Démarre le serveur de santé (`GET /healthz`, `GET /readyz`) sur `0.0.0.0:port`, arrêté à l'annulation de `shutdown`.
+/// `/healthz` répond 503 si aucune activité (boucle de minage ou hashs calculés) depuis `max_idle`.
+/// Un port indisponible est journalisé sans interrompre le miner.
+pub fn start_health_server(
+    port: u16,
+    health: Arc<HealthState>,
+    wallet_counters: WalletCounters,
+    max_idle: Duration,
+    shutdown: CancellationToken,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let state = HealthServerState { health, wallet_counters, max_idle };
+        let app = Router::new()
+            .route("/healthz", get(healthz))
+            .route("/readyz", get(readyz))
+            .with_state(state);
+        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+        let listener = match tokio::net::TcpListener::bind(addr).await {
+            Ok(l) => l,
+            Err(e) => {
+                warn!("⚠️ Serveur de santé non démarré sur {}: {}", addr, e);
+                return;
+            }
+        };
+        info!("🩺 Sondes de santé sur http://{}/healthz et /readyz", addr);
+        if let Err(e) = axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown.cancelled_owned())
+            .await
+        {
+            warn!("⚠️ Serveur de santé arrêté en erreur: {}", e);
+        }
+    })
+}
+
+/// Liveness : une boucle de minage a tourné, ou les threads ont calculé des hashs, depuis moins de `max_idle`
+async fn healthz(State(state): State<HealthServerState>) -> impl IntoResponse {
+    // Un minage long ne repasse pas par la boucle : des hashs en hausse suffisent à prouver que les threads vivent
+    let hash_total: u64 = state
+        .wallet_counters
+        .read()
+        .values()
+        .map(|c| c.load(Ordering::Relaxed))
+        .sum();
+    if state.health.last_hash_total.swap(hash_total, Ordering::Relaxed) != hash_total {
+        state.health.touch();
+    }
+
+    let last_activity = state.health.last_activity.load(Ordering::Relaxed);
+    let idle_secs = Utc::now().timestamp() - last_activity;
+    let alive = last_activity > 0 && idle_secs <= state.max_idle.as_secs() as i64;
+    let status = if alive { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "status": if alive { "ok" } else { "stale" },
+            "last_activity": last_activity,
+            "idle_secs": idle_secs,
+        })),
+    )
+}
+
+/// Readiness : wallets chargés et au moins un challenge récupéré
+async fn readyz(State(state): State<HealthServerState>) -> impl IntoResponse {
+    let wallets_loaded = state.health.wallets_loaded.load(Ordering::Relaxed);
+    let last_challenge_fetch = state.health.last_challenge_fetch.load(Ordering::Relaxed);
+    let ready = wallets_loaded && last_challenge_fetch > 0;
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+
+    (
+        status,
+        Json(serde_json::json!({
+            "status": if ready { "ready" } else { "not_ready" },
+            "wallets_loaded": wallets_loaded,
+            "last_challenge_fetch": last_challenge_fetch,
+        })),
+    )
+}
@@ -0,0 +1,23 @@
+// src/lib.rs
+//! Modules du mineur Scavenger, partagés par le binaire principal, `bench` et les tests d'intégration.
+
+pub mod api_client;
+pub mod challenge;
+pub mod cli;
+pub mod config;
+pub mod donations;
+pub mod donations_manager;
+pub mod health;
+pub mod logging;
+pub mod metrics;
+pub mod miner;
+pub mod retry;
+pub mod stats_client;
+pub mod supervisor;
+pub mod wallet;
+pub mod wallet_container;
+
+/// Client API simulé des tests (tests unitaires, et tests du binaire via la feature `test-utils`)
+#[cfg(any(test, feature = "test-utils"))]
+#[allow(dead_code)] // boîte à outils des tests, toutes les méthodes ne servent pas partout
+pub mod mock_api_client;
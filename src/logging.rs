@@ -0,0 +1,64 @@
+// src/logging.rs
+//! Format JSON des logs (`APP_LOG_FORMAT=json` ou `LOG_FORMAT=json`) : un objet par ligne, pour Loki / Elasticsearch / Datadog.
+//!
+//! Les messages restent ceux des `info!`/`warn!`... du code ; le contexte qu'ils embarquent
+//! (préfixe `[instance|wallet-N|addr1...]`, identifiant de challenge `**D..C..**`) est extrait
+//! dans les champs `wallet` et `challenge_id` quand il est présent.
+
+use serde_json::{json, Map, Value};
+
+/// Une ligne de log JSON : `timestamp`, `level`, `instance`, `message`, `target`, et si trouvés
+/// `wallet`, `challenge_id`. `ts` et `msg` reprennent l'horodatage et le message pour les
+/// pipelines configurés sur le premier format.
+pub fn json_line(ts: &str, level: log::Level, instance: &str, target: &str, msg: &str) -> String {
+    let mut fields = Map::new();
+    fields.insert("timestamp".into(), json!(ts));
+    fields.insert("level".into(), json!(level.as_str()));
+    fields.insert("instance".into(), json!(instance));
+    fields.insert("message".into(), json!(msg));
+    fields.insert("target".into(), json!(target));
+    fields.insert("ts".into(), json!(ts));
+    fields.insert("msg".into(), json!(msg));
+    if let Some(wallet) = wallet_field(msg) {
+        fields.insert("wallet".into(), json!(wallet));
+    }
+    if let Some(challenge_id) = challenge_id_field(msg) {
+        fields.insert("challenge_id".into(), json!(challenge_id));
+    }
+    Value::Object(fields).to_string()
+}
+
+/// Adresse (ou son préfixe) du wallet concerné : 3e segment d'un préfixe `[instance|wallet-N|addr1...]`,
+/// sinon premier mot commençant par `addr1` / `addr_test1`
+fn wallet_field(msg: &str) -> Option<&str> {
+    let from_prefix = msg.split('[').skip(1).find_map(|part| {
+        let inner = part.split(']').next()?;
+        let mut segments = inner.split('|');
+        match (segments.next(), segments.next(), segments.next()) {
+            (Some(_), Some(tag), Some(addr)) if tag.starts_with("wallet-") => Some(addr),
+            _ => None,
+        }
+    });
+    from_prefix.or_else(|| {
+        msg.split(|c: char| c.is_whitespace() || "[]()|,;:=\"'".contains(c))
+            .find(|word| word.starts_with("addr1") || word.starts_with("addr_test1"))
+    })
+}
+
+/// Identifiant de challenge : `**...**` (format de l'API), sinon valeur de `challenge_id=`
+fn challenge_id_field(msg: &str) -> Option<&str> {
+    if let Some(start) = msg.find("**") {
+        if let Some(len) = msg[start + 2..].find("**") {
+            if len > 0 {
+                return Some(&msg[start..start + 2 + len + 2]);
+            }
+        }
+    }
+    let start = msg.find("challenge_id=")? + "challenge_id=".len();
+    let value = msg[start..]
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .next()?
+        .trim_matches(|c| c == '"' || c == '(' || c == ')')
+        .trim_start_matches("Some(");
+    (!value.is_empty()).then_some(value)
+}
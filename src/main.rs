@@ -1,35 +1,48 @@
-mod api_client;
-mod miner;
-mod wallet;
-mod wallet_container;
-mod donations;
-mod donations_manager;
-mod stats_client;
+use scavenger_miner::{
+    api_client, cli, config, donations_manager, health, logging, metrics, miner, stats_client, supervisor, wallet,
+    wallet_container,
+};
 
 use std::{
+    collections::HashSet,
     env,
     error::Error,
-    fs::{self, File},
+    fs,
     path::{Path, PathBuf},
     sync::Arc,
-    sync::atomic::AtomicU64,
+    sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     time::{Duration, Instant},
 };
-use chrono::{NaiveDate, Utc};
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
 use num_cpus;
+use tokio_util::sync::CancellationToken;
 use tokio::time::sleep;
-use log::{info, LevelFilter};
+use log::{debug, error, info, warn, LevelFilter};
 use env_logger::Builder;
 use std::io::Write;
 use rand::{Rng, distributions::Alphanumeric};
 
-use api_client::ApiClient;
-use miner::{mine, MinerConfig};
-use wallet_container::WalletContainer;
+use api_client::{ApiClient, ApiClientTrait, ApiError, ChallengeParams, ChallengeResponse};
+use cli::Cli;
+use config::Config;
+use futures::{stream::BoxStream, StreamExt};
+use miner::{
+    benchmark, get_or_create_rom, hash_params, is_rom_cached, mine_with_deadline, rom_cache_capacity, rom_seed,
+    MinerConfig, MinerError, NonceStrategy,
+    VERIFICATION_FAILURES_TOTAL,
+};
+use wallet::Wallet;
+use wallet_container::{AddressRegistry, WalletContainer};
 use donations_manager::{load_or_create_donate_addresses, process_donations_for_wallets};
-use stats_client::start_stats_reporter;
+use stats_client::{StatsConfig, StatsReporter, ThreadCounters, WalletCounters};
+use metrics::{start_metrics_server, MetricsState};
+use health::{start_health_server, HealthState};
 use std::process;
 
+/// Attente par défaut entre deux pollings de `/challenge` quand le serveur n'annonce pas
+/// le prochain challenge (`CHALLENGE_POLL_INTERVAL_SECS`)
+const DEFAULT_CHALLENGE_POLL: Duration = Duration::from_secs(10);
+
 fn generate_random_string() -> String {
     let length = 10;
     rand::thread_rng()
@@ -45,23 +58,55 @@ fn init_wallet_container(
     use_mainnet: bool,
     max_wallets: usize,
     instance_id: &str,
+    config_root: &str,
 ) -> Result<Arc<WalletContainer>, Box<dyn std::error::Error>> {
     let seed_path = format!("{}/seeds.txt", config_dir);
     let key_path = format!("{}/keys.hex", config_dir);
 
+    // Registre d'adresses commun à toutes les instances (opt-in)
+    let registry = std::env::var("WALLET_DEDUP_REGISTRY")
+        .map(|v| v.to_lowercase() == "true")
+        .unwrap_or(false)
+        .then(|| AddressRegistry::new(Path::new(config_root).join("wallet_addresses.txt")));
+
     info!(
         "🔑 [{}] Initialisation du WalletContainer (max {} wallets)",
         instance_id, max_wallets
     );
-    let container = WalletContainer::load_or_create(seed_path, key_path, use_mainnet, max_wallets)?;
+    let container = WalletContainer::load_or_create(seed_path, key_path, use_mainnet, max_wallets, registry.as_ref())?;
     Ok(Arc::new(container))
 }
 
-fn init_logger(instance_id: &str) {
+/// Importe dans le container la clé de `path` : `payment.skey` (JSON cardano-cli), `ed25519_sk1...` ou hex brut
+fn import_key_file(
+    container: &WalletContainer,
+    path: &str,
+    use_mainnet: bool,
+    instance_id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Import de clé {} : {}", path, e))?;
+    let contents = contents.trim();
+    let wallet = if contents.starts_with('{') {
+        Wallet::from_cardano_skey(contents, use_mainnet)
+    } else if contents.starts_with("ed25519_sk1") {
+        Wallet::from_bech32_private_key(contents, use_mainnet)
+    } else {
+        Wallet::from_secret_hex(contents, use_mainnet)
+    }
+    .map_err(|e| format!("Import de clé {} : {}", path, e))?;
+
+    let address = wallet.address.clone();
+    if container.import_wallet(wallet)? {
+        info!("📥 [{}] Wallet {} importé depuis {}", instance_id, address, path);
+    } else {
+        info!("📥 [{}] Wallet {} déjà présent, import de {} ignoré", instance_id, address, path);
+    }
+    Ok(())
+}
+
+fn init_logger(instance_id: &str, log_level: &str) {
     let instance_ = instance_id.to_string();
-    let log_level = env::var("APP_LOG_LEVEL")
-        .unwrap_or_else(|_| "info".to_string())
-        .to_lowercase();
+    let log_level = log_level.to_lowercase();
 
     let level_filter = match log_level.as_str() {
         "error" => LevelFilter::Error,
@@ -71,8 +116,29 @@ fn init_logger(instance_id: &str) {
         _ => LevelFilter::Info,
     };
 
-    Builder::new()
-        .format(move |buf, record| {
+    // APP_LOG_FORMAT=json (ou LOG_FORMAT=json) : un objet JSON par ligne ; format lisible par défaut
+    let json_format = env::var("APP_LOG_FORMAT")
+        .or_else(|_| env::var("LOG_FORMAT"))
+        .map(|v| v.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+
+    let mut builder = Builder::new();
+    if json_format {
+        builder.format(move |buf, record| {
+            writeln!(
+                buf,
+                "{}",
+                logging::json_line(
+                    &Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                    record.level(),
+                    &instance_,
+                    record.target(),
+                    &record.args().to_string(),
+                )
+            )
+        });
+    } else {
+        builder.format(move |buf, record| {
             writeln!(
                 buf,
                 "[{}][{}][{}] {}",
@@ -81,25 +147,171 @@ fn init_logger(instance_id: &str) {
                 instance_,
                 record.args()
             )
-        })
-        .filter(None, level_filter)
-        .init();
+        });
+    }
+    builder.filter(None, level_filter).init();
 
     info!("Logger initialisé ({}) avec niveau {}", instance_id, log_level);
 }
 
+/// Durée de validité d'un `in_use.lock` non rafraîchi (`LOCK_STALE_AGE_SECS`, défaut 300).
+/// L'ancien nom `INSTANCE_LOCK_TTL_SECS` reste accepté.
+fn instance_lock_ttl() -> Duration {
+    Duration::from_secs(
+        env::var("LOCK_STALE_AGE_SECS")
+            .or_else(|_| env::var("INSTANCE_LOCK_TTL_SECS"))
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(300),
+    )
+}
+
+fn hostname() -> String {
+    env::var("HOSTNAME")
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Écrit `<pid> <timestamp> <hostname>` dans le fichier lock
+fn write_instance_lock(lock_file: &Path) -> std::io::Result<()> {
+    fs::write(
+        lock_file,
+        format!("{} {} {}\n", process::id(), Utc::now().timestamp(), hostname()),
+    )
+}
+
+/// Un lock est obsolète s'il n'a pas été rafraîchi depuis `ttl`, ou si son processus
+/// propriétaire (sur le même hôte) n'existe plus.
+fn is_lock_stale(lock_file: &Path, ttl: Duration) -> bool {
+    let content = match fs::read_to_string(lock_file) {
+        Ok(c) => c,
+        Err(_) => return false,
+    };
+    let mut parts = content.split_whitespace();
+    let pid = parts.next().and_then(|v| v.parse::<u32>().ok());
+    let written_at = parts.next().and_then(|v| v.parse::<i64>().ok());
+    let host = parts.next();
+
+    // Âge du dernier rafraîchissement : mtime du fichier (réécrit à chaque heartbeat),
+    // à défaut l'horodatage écrit dans le lock
+    let age_secs = fs::metadata(lock_file)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .map(|d| d.as_secs() as i64)
+        .or_else(|| written_at.map(|ts| Utc::now().timestamp() - ts))
+        .unwrap_or(0);
+    if age_secs > ttl.as_secs() as i64 {
+        return true;
+    }
+
+    // Les PID ne sont comparables que sur le même hôte (chaque conteneur a son espace de PID).
+    // Notre propre PID ne peut pas encore détenir de lock : c'est un reste d'une exécution précédente.
+    if let (Some(pid), Some(host)) = (pid, host) {
+        if host == hostname()
+            && (pid == process::id() || !Path::new(&format!("/proc/{}", pid)).exists())
+        {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Détient le `in_use.lock` de l'instance et le supprime à la libération
+/// (arrêt sur signal, retour de `main`, ou panic lorsque le binaire est compilé en mode unwind).
+struct InstanceLock {
+    path: PathBuf,
+    released: AtomicBool,
+}
+
+impl InstanceLock {
+    fn new(path: PathBuf) -> Self {
+        Self { path, released: AtomicBool::new(false) }
+    }
+
+    /// Réécrit le lock (heartbeat), sauf s'il a déjà été libéré
+    fn refresh(&self) -> std::io::Result<()> {
+        if self.released.load(Ordering::Acquire) {
+            return Ok(());
+        }
+        write_instance_lock(&self.path)
+    }
+
+    fn release(&self) {
+        if self.released.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        match fs::remove_file(&self.path) {
+            Ok(()) => info!("🔓 Lock libéré : {}", self.path.display()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("⚠️ Impossible de supprimer le lock {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Date de fin de minage (`MINING_END_DATE`, format `AAAA-MM-JJ`), incluse.
+/// Date historique si la variable est absente ; erreur si elle est illisible.
+fn mining_end_date() -> Result<NaiveDate, String> {
+    match env::var("MINING_END_DATE") {
+        Ok(v) => NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d")
+            .map_err(|e| format!("MINING_END_DATE invalide ({:?}, format attendu AAAA-MM-JJ): {}", v, e)),
+        Err(_) => Ok(NaiveDate::from_ymd_opt(2025, 11, 21).unwrap()),
+    }
+}
+
+/// Attend Ctrl-C (SIGINT) ou SIGTERM
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm = signal(SignalKind::terminate()).expect("Impossible d'installer le handler SIGTERM");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => info!("🛑 SIGINT reçu"),
+            _ = sigterm.recv() => info!("🛑 SIGTERM reçu"),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        info!("🛑 Ctrl-C reçu");
+    }
+}
+
+/// Attend `duration`, en se réveillant plus tôt si l'arrêt est demandé.
+/// Retourne `true` si l'arrêt est demandé.
+async fn sleep_or_shutdown(duration: Duration, shutdown: &CancellationToken) -> bool {
+    tokio::select! {
+        _ = sleep(duration) => shutdown.is_cancelled(),
+        _ = shutdown.cancelled() => true,
+    }
+}
+
 /// Trouve ou crée un dossier d’instance dispo
 fn get_instance_dir(base_dir: &str) -> (String, PathBuf) {
     fs::create_dir_all(base_dir).unwrap_or_else(|e| {
         panic!("❌ Impossible de créer le dossier racine {}: {}", base_dir, e)
     });
+    let lock_ttl = instance_lock_ttl();
 
     for i in 1..=10000 {
         let inst_dir = Path::new(base_dir).join(format!("{}", i));
         let lock_file = inst_dir.join("in_use.lock");
 
         if inst_dir.exists() && lock_file.exists() {
-            continue;
+            if !is_lock_stale(&lock_file, lock_ttl) {
+                continue;
+            }
+            warn!("♻️ Lock obsolète récupéré : {}", lock_file.display());
         }
 
         if !inst_dir.exists() {
@@ -107,7 +319,7 @@ fn get_instance_dir(base_dir: &str) -> (String, PathBuf) {
                 .unwrap_or_else(|e| panic!("❌ Impossible de créer le dossier {}: {}", inst_dir.display(), e));
         }
 
-        File::create(&lock_file)
+        write_instance_lock(&lock_file)
             .unwrap_or_else(|e| panic!("❌ Impossible de créer le fichier lock {}: {}", lock_file.display(), e));
 
         let inst_name = format!("miner-{}", i);
@@ -118,34 +330,169 @@ fn get_instance_dir(base_dir: &str) -> (String, PathBuf) {
     panic!("❌ Aucun dossier d'instance disponible dans {}", base_dir);
 }
 
+/// Mode benchmark (`--benchmark` / `BENCHMARK_MODE=true`) : mesure le hash rate local sans aucun appel réseau.
+///
+/// `benchmark` mine ses challenges synthétiques (difficulté impossible) pendant
+/// `BENCHMARK_DURATION_SECS` (30 par défaut) sur `num_threads` threads, chacun avec son compteur.
+fn run_benchmark(num_threads: usize) {
+    let duration = Duration::from_secs(
+        env::var("BENCHMARK_DURATION_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30),
+    );
+
+    // La génération de la ROM (~1 Gio) est exclue de la mesure
+    info!("🏋️ Benchmark : génération de la ROM puis minage sur {} threads pendant {:?}", num_threads.max(1), duration);
+    // Masque 0 : il faudrait un préfixe de 32 bits nuls, introuvable en pratique
+    let report = match benchmark(num_threads, duration, "00000000") {
+        Ok(report) => report,
+        Err(e) => {
+            error!("🏋️ Benchmark interrompu: {}", e);
+            return;
+        }
+    };
+    if report.nonces_found > 0 {
+        warn!("🏋️ {} nonce(s) trouvé(s) pendant le benchmark", report.nonces_found);
+    }
+
+    let total = report.total();
+    info!("🏋️ ROM prête en {:.2?}", report.rom_elapsed);
+    info!("🏁 Benchmark terminé en {:.1}s", report.elapsed.as_secs_f64());
+    info!("   Hashs totaux : {}", total);
+    info!("   Hash rate    : {:.2} H/s", report.rate(total));
+    for (idx, hashes) in report.per_thread.iter().enumerate() {
+        info!(
+            "   Thread {:>3}   : {} hashs ({:.2} H/s, {:.1}%)",
+            idx,
+            hashes,
+            report.rate(*hashes),
+            if total > 0 { *hashes as f64 * 100.0 / total as f64 } else { 0.0 }
+        );
+    }
+}
+
+/// Journalise le solde de chaque adresse (`ENABLE_BALANCE_CHECK`) ; un solde indisponible
+/// n'empêche pas les suivants. Retourne le nombre de soldes obtenus.
+async fn log_wallet_balances(
+    client: Arc<dyn ApiClientTrait>,
+    addresses: Vec<String>,
+    instance_id: String,
+    uniq_inst_id: Arc<String>,
+) -> usize {
+    let mut fetched = 0;
+    for address in addresses {
+        match client
+            .get_wallet_balance(&address, Some(instance_id.clone()), Some((*uniq_inst_id).clone()))
+            .await
+        {
+            Ok(balance) => {
+                fetched += 1;
+                info!(
+                    "💰 [{}] {} : {} en attente, {} confirmés (maj {})",
+                    instance_id, address, balance.pending_rewards, balance.confirmed_rewards, balance.last_updated
+                );
+            }
+            Err(e) => warn!("⚠️ [{}] Solde de {} indisponible: {}", instance_id, address, e),
+        }
+    }
+    fetched
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let config_root = "/usr/local/bin/config";
+    let started_at = Instant::now();
+    // La configuration est chargée avant la ligne de commande pour que `--help` l'affiche
+    let loaded_config = Config::load();
+    let cli = Cli::parse_with_effective(loaded_config.as_ref().ok());
+    let mut app_config = match loaded_config {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("❌ Configuration illisible : {}", e);
+            process::exit(1);
+        }
+    };
+    app_config.apply_cli(&cli);
+    if cli.benchmark {
+        init_logger("benchmark", &app_config.log_level);
+        let num_threads = app_config.threads;
+        tokio::task::spawn_blocking(move || run_benchmark(num_threads)).await?;
+        return Ok(());
+    }
+
+    // Validation de la configuration : toutes les erreurs sont affichées avant de quitter
+    if let Err(errors) = app_config.validate() {
+        eprintln!("❌ Configuration invalide ({} erreur(s)) :", errors.len());
+        for e in &errors {
+            eprintln!("   - {}", e);
+        }
+        process::exit(1);
+    }
+
+    let config_root = cli.config_dir.as_str();
     let (instance_id, config_dir) = get_instance_dir(config_root);
     let uniq_inst_id = Arc::new(generate_random_string());
-    init_logger(&instance_id);
+    init_logger(&instance_id, &app_config.log_level);
+
+    let instance_lock = Arc::new(InstanceLock::new(config_dir.join("in_use.lock")));
+
+    // Rafraîchit périodiquement le lock pour qu'il ne soit pas considéré obsolète
+    let lock_refresh = std::cmp::max(instance_lock_ttl() / 3, Duration::from_secs(1));
+    let lock_weak = Arc::downgrade(&instance_lock);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(lock_refresh);
+        loop {
+            ticker.tick().await;
+            let lock = match lock_weak.upgrade() {
+                Some(lock) => lock,
+                None => break,
+            };
+            if let Err(e) = lock.refresh() {
+                warn!("⚠️ Impossible de rafraîchir le lock {}: {}", lock.path.display(), e);
+            }
+        }
+    });
 
     let wallet_dir = config_dir.join(&instance_id).join("wallets");
     fs::create_dir_all(&wallet_dir)?;
 
     info!("🚀 Démarrage du Scavenger Miner [{}]", instance_id);
 
-    let base_url = env::var("APP_BASE_URL")
-        .unwrap_or_else(|_| "https://scavenger.prod.gd.midnighttge.io".to_string());
+    // Validation de la configuration avant de lancer quoi que ce soit
+    let end_date = match mining_end_date() {
+        Ok(date) => date,
+        Err(e) => {
+            error!("❌ {}", e);
+            return Err(e.into());
+        }
+    };
+
+    let nonce_strategy = match NonceStrategy::from_env() {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            error!("❌ {}", e);
+            return Err(e.into());
+        }
+    };
+
+    let base_url = app_config.base_url.clone();
     let use_mainnet = true;
 
     let client = Arc::new(ApiClient::new(&base_url)?);
-    let max_wallets: usize = env::var("MAX_WALLETS_PER_INSTANCE")
-        .ok()
-        .and_then(|v| v.parse::<usize>().ok())
-        .unwrap_or(1);
+    let max_wallets = app_config.max_wallets;
 
-    let wallet_container = init_wallet_container(wallet_dir.to_str().unwrap(), use_mainnet, max_wallets, &instance_id)?;
+    let wallet_container = init_wallet_container(wallet_dir.to_str().unwrap(), use_mainnet, max_wallets, &instance_id, config_root)?;
+    if let Some(path) = cli.import_key_file.as_deref() {
+        import_key_file(&wallet_container, path, use_mainnet, &instance_id)?;
+    }
     let wallets = wallet_container.read_all();
     info!("💼 [{}] {} wallets chargés", instance_id, wallets.len());
 
+    // Arrêt gracieux : jeton annulé sur SIGINT/SIGTERM et transmis à toutes les tâches
+    let shutdown = CancellationToken::new();
+
     // --- Donations ---
-    let wallets_path = wallet_dir.clone();
+    let donation_config_root = config_root.to_string();
     let client_clone = Arc::clone(&client);
     let instance_id_clone = instance_id.clone();
     let uniq_inst_id_clone = Arc::clone(&uniq_inst_id);
@@ -154,16 +501,24 @@ async fn main() -> Result<(), Box<dyn Error>> {
         Err(_) => 7200, 
     };
 
-    let args: Vec<String> = env::args().collect();
-    
-    let only_donate = args.contains(&"--only-donate".to_string()) || 
-                       env::var("ONLY_DONATE").unwrap_or_else(|_| "false".to_string()) == "true"; 
+    let only_donate = cli.only_donate;
+
+    // DONATIONS_ENABLED=false : aucune tâche de donation, aucun fichier de donation créé
+    let donations_enabled = env::var("DONATIONS_ENABLED")
+        .map(|v| v.to_lowercase() != "false")
+        .unwrap_or(true);
+    if !donations_enabled {
+        if only_donate {
+            return Err("--only-donate / ONLY_DONATE incompatible avec DONATIONS_ENABLED=false".into());
+        }
+        info!("🚫 [{}] Donations désactivées (DONATIONS_ENABLED=false)", instance_id);
+    }
 
     if only_donate {
         // Exécuter seulement les donations
         info!("🚨 Mode DONATION SEULEMENT activé. Le minage est désactivé.");
         
-        let wallets_path = wallet_dir.clone();
+        let donation_config_root = donation_config_root.clone();
         let client_clone = Arc::clone(&client);
         let instance_id_clone = instance_id.clone();
         let uniq_inst_id_clone = Arc::clone(&uniq_inst_id);
@@ -171,6 +526,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
             Ok(val) => val.parse::<u64>().unwrap_or(7200),
             Err(_) => 7200,
         };
+        let instance_lock = Arc::clone(&instance_lock);
+        let shutdown = shutdown.clone();
 
         tokio::spawn(async move {
             loop {
@@ -178,141 +535,644 @@ async fn main() -> Result<(), Box<dyn Error>> {
                 let client_ref = Arc::clone(&client_clone);
                 let uniq_inst_id_ref = Arc::clone(&uniq_inst_id_clone);
                 let donate_addresses = load_or_create_donate_addresses(
-                    "/usr/local/bin/config",
+                    &donation_config_root,
                     use_mainnet,
                     &instance_id_clone,
                 );
                 process_donations_for_wallets(
                     client_ref,
-                    &wallets_path.to_str().unwrap(),
+                    &donation_config_root,
                     &donate_addresses,
                     &instance_id_clone,
                     &uniq_inst_id_ref,
+                    &shutdown,
                 )
                 .await;
 
                 // Terminer immédiatement après avoir effectué la donation
                 info!("🚨 Donation terminée, arrêt du programme.");
+                // process::exit ne lance pas les destructeurs : libération explicite
+                instance_lock.release();
                 process::exit(0); // Terminates the process with a success code (0)
             }
         });
 }    
-    tokio::spawn(async move {
-        loop {
-            info!("💰 Donate process run every {}s", sleep_duration);
-            let client_ref = Arc::clone(&client_clone);
-            let uniq_inst_id_ref = Arc::clone(&uniq_inst_id_clone);
-            let donate_addresses = load_or_create_donate_addresses(
-                "/usr/local/bin/config",
-                use_mainnet,
-                &instance_id_clone,
-            );
-            process_donations_for_wallets(
-                client_ref,
-                &wallets_path.to_str().unwrap(),
-                &donate_addresses,
-                &instance_id_clone,
-                &uniq_inst_id_ref,
-            )
-            .await;
-            sleep(Duration::from_secs(sleep_duration)).await;
-        }
+    let donation_shutdown = shutdown.clone();
+    let donation_handle = donations_enabled.then(|| {
+        tokio::spawn(async move {
+            loop {
+                info!("💰 Donate process run every {}s", sleep_duration);
+                let client_ref = Arc::clone(&client_clone);
+                let uniq_inst_id_ref = Arc::clone(&uniq_inst_id_clone);
+                let donate_addresses = load_or_create_donate_addresses(
+                    &donation_config_root,
+                    use_mainnet,
+                    &instance_id_clone,
+                );
+                process_donations_for_wallets(
+                    client_ref,
+                    &donation_config_root,
+                    &donate_addresses,
+                    &instance_id_clone,
+                    &uniq_inst_id_ref,
+                    &donation_shutdown,
+                )
+                .await;
+                if sleep_or_shutdown(Duration::from_secs(sleep_duration), &donation_shutdown).await {
+                    break;
+                }
+            }
+        })
     });
 
-    let total_threads = env::var("MINER_THREADS")
-        .ok()
-        .and_then(|s| s.parse::<usize>().ok())
-        .unwrap_or_else(num_cpus::get);
+    let total_threads = app_config.threads;
     let threads_per_wallet = std::cmp::max(total_threads / wallets.len(), 1);
 
-    let end_date = NaiveDate::from_ymd_opt(2025, 11, 21).unwrap();
-    let hash_counter = Arc::new(AtomicU64::new(0));
+    // Mode adaptatif : chaque wallet lance ADAPTIVE_MAX_THREADS threads, dont seule une partie
+    // calcule selon la charge de la machine
+    let adaptive_threads = env::var("ADAPTIVE_THREADS")
+        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        .unwrap_or(false);
+    let adaptive_max_threads = env::var("ADAPTIVE_MAX_THREADS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(threads_per_wallet);
+    let adaptive_min_threads = env::var("ADAPTIVE_MIN_THREADS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1)
+        .clamp(1, adaptive_max_threads);
+    let threads_per_wallet = if adaptive_threads { adaptive_max_threads } else { threads_per_wallet };
+    let active_threads: Option<Arc<AtomicUsize>> =
+        adaptive_threads.then(|| Arc::new(AtomicUsize::new(adaptive_max_threads)));
+
+    // Fin du minage : lendemain 00:00 UTC de MINING_END_DATE, remplacée par `mining_period_ends` du serveur dès qu'il est connu
+    let default_mining_ends_at: DateTime<Utc> = end_date
+        .succ_opt()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|d| Utc.from_utc_datetime(&d))
+        .unwrap_or_else(Utc::now);
+    info!("📅 Fin du minage prévue le {} (UTC)", end_date);
+    let challenge_max_age = Duration::from_secs(
+        env::var("CHALLENGE_MAX_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300),
+    );
+    let wallet_counters: WalletCounters = Arc::default();
+    let thread_counters: ThreadCounters = Arc::default();
+
+    // --- Sondes de santé (liveness / readiness) pour l'orchestrateur ---
+    let health = Arc::new(HealthState::default());
+    let health_port = env::var("HEALTH_PORT")
+        .ok()
+        .and_then(|v| v.parse::<u16>().ok())
+        .unwrap_or(8081);
+    let health_max_idle = Duration::from_secs(
+        env::var("HEALTH_MAX_IDLE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(300),
+    );
+    start_health_server(
+        health_port,
+        Arc::clone(&health),
+        wallet_counters.clone(),
+        health_max_idle,
+        shutdown.clone(),
+    );
+    info!("🎲 [{}] Stratégie de nonces : {:?}", instance_id, nonce_strategy);
+
+    let verify_before_submit = env::var("VERIFY_BEFORE_SUBMIT")
+        .map(|v| v.to_lowercase() == "true")
+        .unwrap_or(false);
+    // Temps de minage accordé à chaque challenge quand plusieurs sont ouverts en même temps
+    let challenge_time_budget = Duration::from_secs(
+        env::var("CHALLENGE_TIME_BUDGET_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(600),
+    );
+    let challenge_poll_interval = env::var("CHALLENGE_POLL_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|s| *s > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_CHALLENGE_POLL);
+    // Marge ajoutée à l'attente du prochain challenge annoncé (horloges décalées, publication tardive)
+    let challenge_poll_margin = Duration::from_secs(
+        env::var("CHALLENGE_POLL_MARGIN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(2),
+    );
+    let challenge_stream_enabled = env::var("CHALLENGE_STREAM_ENABLED")
+        .map(|v| v.to_lowercase() != "false")
+        .unwrap_or(true);
+
+    let wallet_count = wallets.len();
+
+    // Relance des tâches de minage qui paniquent (backoff exponentiel)
+    let supervisor_max_retries = env::var("SUPERVISOR_MAX_RETRIES")
+        .ok()
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(5);
+    let supervisor_base_delay = Duration::from_secs(
+        env::var("SUPERVISOR_BASE_DELAY_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|s| *s > 0)
+            .unwrap_or(5),
+    );
 
     // --- Lancement des mineurs ---
-    for (idx, wallet) in wallets.into_iter().enumerate() {
-        let client_clone = client.clone();
-        let instance_clone = instance_id.clone();
-        let hash_counter_clone = hash_counter.clone();
-        let uniq_inst_id_clone = Arc::clone(&uniq_inst_id);
-        let wallet_idx = idx + 1;
+    // Une tâche par wallet ; aussi appelée pour les wallets ajoutés à chaud (voir plus bas),
+    // qui s'enregistrent eux-mêmes (`register`)
+    let spawn_wallet_miner = {
+        let client = Arc::clone(&client);
+        let instance_id = instance_id.clone();
+        let uniq_inst_id = Arc::clone(&uniq_inst_id);
+        let wallet_counters = wallet_counters.clone();
+        let thread_counters = thread_counters.clone();
+        let active_threads = active_threads.clone();
+        let shutdown = shutdown.clone();
+        let wallet_container = Arc::clone(&wallet_container);
+        let health = Arc::clone(&health);
+        move |idx: usize, wallet: Wallet, register: bool| -> tokio::task::JoinHandle<()> {
+            health.mark_wallets_loaded();
+            let client_clone = client.clone();
+            let instance_clone = instance_id.clone();
+            // Compteur propre au wallet, agrégé par le reporter de stats
+            let hash_counter_clone = Arc::new(AtomicU64::new(0));
+            wallet_counters
+                .write()
+                .insert(wallet.address.clone(), Arc::clone(&hash_counter_clone));
+            // Un compteur par thread de minage du wallet, pour le hash rate par thread
+            let wallet_thread_counters: Vec<Arc<AtomicU64>> =
+                (0..threads_per_wallet).map(|_| Arc::new(AtomicU64::new(0))).collect();
+            thread_counters.write().extend(wallet_thread_counters.iter().cloned());
+            let uniq_inst_id_clone = Arc::clone(&uniq_inst_id);
+            let shutdown = shutdown.clone();
+            let wallet_idx = idx + 1;
+            let wallet_container = Arc::clone(&wallet_container);
+            let active_threads = active_threads.clone();
+            let health = Arc::clone(&health);
 
-        tokio::spawn(async move {
             let wallet_prefix = format!("[{}|wallet-{}|{}]", instance_clone, wallet_idx, &wallet.address[..10]);
-            info!("{} ⛏️ Miner lancé avec {} threads", wallet_prefix, threads_per_wallet);
-
-            let container_id_str = (*uniq_inst_id_clone).clone();
-
-            if let Ok(terms) =
-                client_clone.get_terms(None, Some(instance_clone.clone()), Some(container_id_str.clone())).await
-            {
-                let signature = wallet.sign_cip30(&terms.message);
-                let pubkey = wallet.public_key_hex();
-                let _ = client_clone
-                    .register_address(
-                        &wallet.address,
-                        &signature,
-                        &pubkey,
-                        Some(instance_clone.clone()),
-                        Some(container_id_str.clone()),
-                    )
-                    .await;
-            }
+            supervisor::spawn_supervised(
+                {
+                    let wallet_prefix = wallet_prefix.clone();
+                    move || {
+                        let client_clone = Arc::clone(&client_clone);
+                        let instance_clone = instance_clone.clone();
+                        let wallet = wallet.clone();
+                        let wallet_prefix = wallet_prefix.clone();
+                        let uniq_inst_id_clone = Arc::clone(&uniq_inst_id_clone);
+                        let shutdown = shutdown.clone();
+                        let wallet_container = Arc::clone(&wallet_container);
+                        let hash_counter_clone = Arc::clone(&hash_counter_clone);
+                        let wallet_thread_counters = wallet_thread_counters.clone();
+                        let active_threads = active_threads.clone();
+                        let health = Arc::clone(&health);
+                        async move {
+                            info!("{} ⛏️ Miner lancé avec {} threads", wallet_prefix, threads_per_wallet);
 
-            loop {
-                if Utc::now().date_naive() > end_date {
-                    sleep(Duration::from_secs(3600)).await;
-                    continue;
-                }
+                            let container_id_str = (*uniq_inst_id_clone).clone();
 
-                if let Ok(resp) =
-                    client_clone.get_challenge(Some(instance_clone.clone()), Some(container_id_str.clone())).await
-                {
-                    if let Some(challenge) = resp.challenge {
-                        let miner_config = MinerConfig {
-                            address: wallet.address.clone(),
-                            challenge: Arc::new(challenge.clone()),
-                        };
-
-                        let start = Instant::now();
-
-                        // ✅ Spawn CPU-intensive mining task in blocking thread pool
-                        match tokio::task::spawn_blocking({
-                            let miner_config = miner_config.clone();
-                            let hash_counter = hash_counter_clone.clone();
-                            move || mine(miner_config, threads_per_wallet, Some(hash_counter))
-                        })
-                        .await
-                        {
-                            Ok(Ok(result)) => {
-                                let duration = start.elapsed();
-                                info!(
-                                    "{} 💎 Nonce trouvé={} ({:.2?})",
-                                    wallet_prefix, result.nonce, duration
-                                );
-
-                                let _ = client_clone
-                                    .submit_solution(
-                                        &wallet.address,
-                                        &challenge.challenge_id,
-                                        &result.nonce,
-                                        Some(instance_clone.clone()),
-                                        Some(container_id_str.clone()),
-                                    )
-                                    .await;
-                            }
-                            Ok(Err(err_msg)) => {
-                                info!("{} ⚠️ Minage terminé sans résultat: {}", wallet_prefix, err_msg);
+                            // Les wallets du démarrage sont déjà enregistrés (batch_register)
+                            if register {
+                                if let Ok(terms) =
+                                    client_clone.get_terms_cached(Some(instance_clone.clone()), Some(container_id_str.clone())).await
+                                {
+                                    let signature = wallet.sign_cip30(&terms.message);
+                                    let pubkey = wallet.public_key_hex();
+                                    let _ = client_clone
+                                        .register_address(
+                                            &wallet.address,
+                                            &signature,
+                                            &pubkey,
+                                            Some(instance_clone.clone()),
+                                            Some(container_id_str.clone()),
+                                        )
+                                        .await;
+                                }
                             }
-                            Err(join_err) => {
-                                info!("{} ⚠️ spawn_blocking error: {:?}", wallet_prefix, join_err);
+
+                            let mut use_stream = challenge_stream_enabled;
+                            let mut challenge_stream: Option<BoxStream<'static, Result<ChallengeParams, ApiError>>> = None;
+                            let mut mining_ends_at = default_mining_ends_at;
+                            // Challenges soumis avec succès : inutile de les re-miner
+                            let mut submitted_challenges: HashSet<String> = HashSet::new();
+
+                            loop {
+                                let mut next_poll = challenge_poll_interval;
+                                // Au moins une solution acceptée pendant ce cycle
+                                let mut submitted_this_round = false;
+                                // Dernière réponse de /challenge (polling), pour caler l'attente sur le prochain challenge
+                                let mut poll_schedule: Option<ChallengeResponse> = None;
+
+                                if shutdown.is_cancelled() {
+                                    break;
+                                }
+                                health.touch();
+
+                                // Wallet retiré des fichiers (rechargement à chaud) : arrêt au cycle suivant
+                                if wallet_container.get_by_address(&wallet.address).is_none() {
+                                    info!("{} ➖ Wallet retiré du container, arrêt du mineur", wallet_prefix);
+                                    break;
+                                }
+
+                                if Utc::now() >= mining_ends_at {
+                                    sleep_or_shutdown(Duration::from_secs(3600), &shutdown).await;
+                                    continue;
+                                }
+
+                                // Source du challenge : flux SSE si le serveur le supporte, sinon polling de /challenge
+                                let received = if use_stream {
+                                    let stream = challenge_stream.get_or_insert_with(|| {
+                                        client_clone.get_challenge_stream(Some(instance_clone.clone()), Some(container_id_str.clone()))
+                                    });
+                                    let next = tokio::select! {
+                                        next = stream.next() => next,
+                                        _ = shutdown.cancelled() => break,
+                                    };
+                                    match next {
+                                        Some(Ok(challenge)) => {
+                                            health.mark_challenge_fetched();
+                                            // Le prochain évènement arrivera via le flux : pas d'attente
+                                            next_poll = Duration::ZERO;
+                                            vec![challenge]
+                                        }
+                                        Some(Err(ApiError::Status { status: 404, .. })) => {
+                                            info!("{} 📡 /challenge/stream indisponible (404), passage en polling", wallet_prefix);
+                                            use_stream = false;
+                                            challenge_stream = None;
+                                            next_poll = Duration::ZERO;
+                                            Vec::new()
+                                        }
+                                        Some(Err(e)) => {
+                                            warn!("{} 📡 Erreur du flux de challenges ({}), reconnexion", wallet_prefix, e);
+                                            challenge_stream = None;
+                                            Vec::new()
+                                        }
+                                        None => {
+                                            info!("{} 📡 Flux de challenges fermé, reconnexion", wallet_prefix);
+                                            challenge_stream = None;
+                                            Vec::new()
+                                        }
+                                    }
+                                } else {
+                                    client_clone
+                                        .get_challenge(Some(instance_clone.clone()), Some(container_id_str.clone()))
+                                        .await
+                                        .map(|resp| {
+                                            health.mark_challenge_fetched();
+                                            if let Some(ends_at) = resp.mining_period_end() {
+                                                mining_ends_at = ends_at;
+                                            }
+                                            let open = resp.clone().open_challenges();
+                                            poll_schedule = Some(resp);
+                                            open
+                                        })
+                                        .unwrap_or_default()
+                                };
+
+                                let mut to_mine: Vec<ChallengeParams> = received
+                                    .into_iter()
+                                    .filter(|c| {
+                                        if submitted_challenges.contains(&c.challenge_id) {
+                                            debug!("{} 🔁 Challenge {} déjà soumis, minage ignoré", wallet_prefix, c.challenge_id);
+                                            return false;
+                                        }
+                                        let expired = c.is_expired(challenge_max_age);
+                                        if expired {
+                                            warn!(
+                                                "{} ⌛ Challenge {} expiré (émis à {:?}), minage ignoré",
+                                                wallet_prefix, c.challenge_id, c.issued_at
+                                            );
+                                        }
+                                        !expired
+                                    })
+                                    .collect();
+
+                                // Plusieurs challenges ouverts : ROM déjà en cache d'abord, puis regroupés par ROM
+                                // (une seule génération par seed) et par date limite ; chacun a un budget de temps.
+                                let budget = (to_mine.len() > 1).then_some(challenge_time_budget);
+                                if budget.is_some() {
+                                    to_mine.sort_by_cached_key(|c| {
+                                        let seed = rom_seed(c).ok();
+                                        (!seed.as_deref().is_some_and(is_rom_cached), seed, c.latest_submission_at())
+                                    });
+                                    let distinct_roms: HashSet<Vec<u8>> = to_mine.iter().filter_map(|c| rom_seed(c).ok()).collect();
+                                    info!(
+                                        "{} 🧩 {} challenges ouverts ({} ROM distinctes), {:?} max chacun",
+                                        wallet_prefix,
+                                        to_mine.len(),
+                                        distinct_roms.len(),
+                                        challenge_time_budget
+                                    );
+                                    if distinct_roms.len() > rom_cache_capacity() {
+                                        warn!(
+                                            "{} ⚠️ ROM_CACHE_SIZE={} < {} ROM distinctes : des ROM seront régénérées à chaque cycle",
+                                            wallet_prefix,
+                                            rom_cache_capacity(),
+                                            distinct_roms.len()
+                                        );
+                                    }
+                                }
+
+                                for challenge in to_mine {
+                                    if shutdown.is_cancelled() {
+                                        break;
+                                    }
+
+                                    // Arrêt du minage à la date limite de soumission ou à la fin du budget,
+                                    // ainsi qu'à l'arrêt du programme
+                                    let deadline = [
+                                        budget,
+                                        challenge.latest_submission_at().map(|at| (at - Utc::now()).to_std().unwrap_or_default()),
+                                    ]
+                                    .into_iter()
+                                    .flatten()
+                                    .min();
+                                    // L'échéance est gérée par `mine_with_deadline`, le watchdog ne relaie que l'arrêt
+                                    let challenge_stop = Arc::new(AtomicBool::new(false));
+                                    let watchdog = tokio::spawn({
+                                        let challenge_stop = Arc::clone(&challenge_stop);
+                                        let shutdown = shutdown.clone();
+                                        async move {
+                                            shutdown.cancelled().await;
+                                            challenge_stop.store(true, Ordering::Release);
+                                        }
+                                    });
+
+                                    let miner_config = MinerConfig::new(wallet.address.clone(), Arc::new(challenge.clone()));
+
+                                    let start = Instant::now();
+
+                                    // ✅ Spawn CPU-intensive mining task in blocking thread pool
+                                    match tokio::task::spawn_blocking({
+                                        let miner_config = miner_config.clone();
+                                        let hash_counter = hash_counter_clone.clone();
+                                        let thread_counters = wallet_thread_counters.clone();
+                                        let stop_flag = Arc::clone(&challenge_stop);
+                                        let active_threads = active_threads.clone();
+                                        move || {
+                                            mine_with_deadline(
+                                                miner_config,
+                                                threads_per_wallet,
+                                                Some(hash_counter),
+                                                Some(thread_counters),
+                                                active_threads,
+                                                stop_flag,
+                                                deadline,
+                                                |i| nonce_strategy.generator(i, threads_per_wallet),
+                                            )
+                                        }
+                                    })
+                                    .await
+                                    {
+                                        Ok(Ok(result)) => {
+                                            let duration = start.elapsed();
+                                            info!(
+                                                "{} 💎 Nonce trouvé={} ({:.2?})",
+                                                wallet_prefix, result.nonce, duration
+                                            );
+
+                                            // Revalidation locale avant soumission (ROM normalement déjà en cache)
+                                            let verified = tokio::task::spawn_blocking({
+                                                let result = result.clone();
+                                                let miner_config = miner_config.clone();
+                                                move || {
+                                                    let (nb_loops, nb_instrs) = hash_params();
+                                                    rom_seed(&miner_config.challenge).is_ok_and(|seed| {
+                                                        let rom = get_or_create_rom(&seed);
+                                                        result.verify(&miner_config, nb_loops, nb_instrs, &rom)
+                                                    })
+                                                }
+                                            })
+                                            .await
+                                            .unwrap_or(false);
+                                            // Pré-vérification serveur optionnelle ; en cas d'erreur on soumet quand même
+                                            let server_valid = if verified && verify_before_submit {
+                                                match client_clone.verify_solution(&challenge.challenge_id, &result.nonce).await {
+                                                    Ok(valid) => valid,
+                                                    Err(e) => {
+                                                        warn!("{} ⚠️ Vérification serveur du nonce impossible ({}), soumission quand même", wallet_prefix, e);
+                                                        true
+                                                    }
+                                                }
+                                            } else {
+                                                true
+                                            };
+                                            if !verified {
+                                                let failures = VERIFICATION_FAILURES_TOTAL.fetch_add(1, Ordering::Relaxed) + 1;
+                                                warn!(
+                                                    "{} ⚠️ Nonce {} invalide à la revalidation, soumission ignorée (échecs: {})",
+                                                    wallet_prefix, result.nonce, failures
+                                                );
+                                            } else if !server_valid {
+                                                warn!(
+                                                    "{} ⚠️ Nonce {} refusé par /verify, soumission ignorée",
+                                                    wallet_prefix, result.nonce
+                                                );
+                                            } else if client_clone
+                                                .submit_solution(
+                                                    &wallet.address,
+                                                    &challenge.challenge_id,
+                                                    &result.nonce,
+                                                    Some(instance_clone.clone()),
+                                                    Some(container_id_str.clone()),
+                                                )
+                                                .await
+                                                .is_ok()
+                                            {
+                                                submitted_challenges.insert(challenge.challenge_id.clone());
+                                                submitted_this_round = true;
+                                            }
+                                        }
+                                        Ok(Err(MinerError::NoResultFound)) => {
+                                            info!("{} ⚠️ Minage terminé sans résultat, pause de 10s", wallet_prefix);
+                                            next_poll = Duration::from_secs(10);
+                                        }
+                                        Ok(Err(MinerError::DeadlineExceeded)) => {
+                                            info!(
+                                                "{} ⏱️ Budget ou date limite atteint pour le challenge {}, challenge suivant",
+                                                wallet_prefix, challenge.challenge_id
+                                            );
+                                        }
+                                        Ok(Err(MinerError::ThreadPanicked)) => {
+                                            error!("{} 💥 Un thread de minage a paniqué, pause de 60s", wallet_prefix);
+                                            next_poll = Duration::from_secs(60);
+                                        }
+                                        Ok(Err(MinerError::Cancelled)) => {
+                                            info!("{} 🛑 Minage interrompu (arrêt demandé)", wallet_prefix);
+                                        }
+                                        Ok(Err(MinerError::InvalidConfig(msg))) => {
+                                            warn!("{} ⚠️ Configuration de minage invalide ({}), pause de 60s", wallet_prefix, msg);
+                                            next_poll = Duration::from_secs(60);
+                                        }
+                                        Ok(Err(MinerError::MissingRomSeed(id))) => {
+                                            // Aucun thread lancé ni ROM générée : challenge ignoré, sans brûler de CPU
+                                            warn!(
+                                                "{} ⚠️ Challenge {} sans no_pre_mine (seed de ROM), ignoré (voir ALLOW_DEFAULT_ROM_SEED)",
+                                                wallet_prefix, id
+                                            );
+                                        }
+                                        Ok(Err(MinerError::ThreadSpawn(msg))) => {
+                                            error!("{} 💥 Impossible de lancer les threads de minage ({}), pause de 60s", wallet_prefix, msg);
+                                            next_poll = Duration::from_secs(60);
+                                        }
+                                        Err(join_err) => {
+                                            error!("{} ⚠️ spawn_blocking error: {:?}, pause de 60s", wallet_prefix, join_err);
+                                            next_poll = Duration::from_secs(60);
+                                        }
+                                    }
+                                    watchdog.abort();
+                                }
+
+                                // Entre deux challenges : attendre l'ouverture du suivant (+ marge et jitter) plutôt que
+                                // de re-poller /challenge à intervalle fixe ; une pause d'erreur plus longue est conservée.
+                                // Le jitter évite que toutes les instances d'une flotte pollent à la même seconde.
+                                let next_challenge = poll_schedule
+                                    .as_ref()
+                                    .and_then(|resp| resp.next_challenge_start().map(|at| (resp, at)));
+                                match next_challenge {
+                                    // Solution soumise et challenge suivant déjà ouvert : on enchaîne sans attendre
+                                    Some((_, at)) if submitted_this_round && next_poll == challenge_poll_interval && at <= Utc::now() => {
+                                        info!("{} ⏩ Challenge suivant déjà disponible ({}), polling immédiat", wallet_prefix, at.to_rfc3339());
+                                        next_poll = Duration::ZERO;
+                                    }
+                                    Some((resp, at)) => {
+                                        let delay = resp.next_poll_delay(Utc::now(), challenge_poll_margin);
+                                        if next_poll == challenge_poll_interval || delay > next_poll {
+                                            next_poll = delay + Duration::from_millis(rand::thread_rng().gen_range(0..5_000));
+                                            info!(
+                                                "{} ⏳ Prochain challenge à {}, attente de {:.0?}",
+                                                wallet_prefix,
+                                                at.to_rfc3339(),
+                                                next_poll
+                                            );
+                                        }
+                                    }
+                                    // Intervalle fixe : jusqu'à +20 % de jitter
+                                    None if next_poll == challenge_poll_interval => {
+                                        let max_jitter = challenge_poll_interval.as_millis() as u64 / 5;
+                                        next_poll += Duration::from_millis(rand::thread_rng().gen_range(0..=max_jitter));
+                                    }
+                                    None => {}
+                                }
+
+                                if sleep_or_shutdown(next_poll, &shutdown).await {
+                                    break;
+                                }
                             }
+
+                            info!("{} 🛑 Miner arrêté", wallet_prefix);
                         }
                     }
+                },
+                instance_id.clone(),
+                wallet_prefix,
+                supervisor_max_retries,
+                supervisor_base_delay,
+            )
+        }
+    };
+
+    let mut known_wallets: HashSet<String> = wallets.iter().map(|w| w.address.clone()).collect();
+
+    // Enregistrement groupé des wallets avant le minage (REGISTER_CONCURRENCY requêtes simultanées)
+    match client
+        .get_terms_cached(Some(instance_id.clone()), Some((*uniq_inst_id).clone()))
+        .await
+    {
+        Ok(terms) => {
+            let registrations: Vec<(String, String, String)> = wallets
+                .iter()
+                .map(|w| (w.address.clone(), w.sign_cip30(&terms.message), w.public_key_hex()))
+                .collect();
+            let results = client
+                .batch_register(&registrations, Some(instance_id.clone()), Some((*uniq_inst_id).clone()))
+                .await;
+            for ((address, _, _), result) in registrations.iter().zip(results) {
+                if let Err(e) = result {
+                    warn!("⚠️ [{}] Enregistrement de {} échoué: {}", instance_id, address, e);
                 }
+            }
+        }
+        Err(e) => warn!("⚠️ [{}] Conditions d'utilisation indisponibles, wallets non enregistrés: {}", instance_id, e),
+    }
+
+    // Solde des wallets au démarrage (ENABLE_BALANCE_CHECK), sans retarder le minage
+    let balance_check = env::var("ENABLE_BALANCE_CHECK")
+        .map(|v| v.to_lowercase() == "true")
+        .unwrap_or(false);
+    if balance_check {
+        let client: Arc<dyn ApiClientTrait> = client.clone();
+        let addresses: Vec<String> = wallets.iter().map(|w| w.address.clone()).collect();
+        tokio::spawn(log_wallet_balances(client, addresses, instance_id.clone(), Arc::clone(&uniq_inst_id)));
+    }
 
-                sleep(Duration::from_secs(10)).await;
+    let mut miner_handles: Vec<_> = wallets
+        .into_iter()
+        .enumerate()
+        .map(|(idx, wallet)| spawn_wallet_miner(idx, wallet, false))
+        .collect();
+
+    // --- Ajustement du nombre de threads actifs selon la charge (ADAPTIVE_THREADS) ---
+    if let Some(active_threads) = active_threads.clone() {
+        info!(
+            "⚖️ [{}] Threads adaptatifs : {} à {} par wallet",
+            instance_id, adaptive_min_threads, adaptive_max_threads
+        );
+        let wallet_counters = wallet_counters.clone();
+        miner_handles.push(supervisor::spawn_thread_scaler(
+            active_threads,
+            move || wallet_counters.read().len(),
+            adaptive_min_threads,
+            adaptive_max_threads,
+            Duration::from_secs(30),
+            shutdown.clone(),
+        ));
+    }
+
+    // --- Rechargement à chaud des wallets : mineur lancé pour chaque wallet ajouté sur disque ---
+    let wallet_watch_enabled = env::var("WALLET_WATCH_ENABLED")
+        .map(|v| v.to_lowercase() != "false")
+        .unwrap_or(true);
+    if wallet_watch_enabled {
+        match wallet_container.watch_directory() {
+            Ok(()) => {
+                let wallet_container = Arc::clone(&wallet_container);
+                let shutdown = shutdown.clone();
+                let instance_id = instance_id.clone();
+                miner_handles.push(tokio::spawn(async move {
+                    let mut next_idx = known_wallets.len();
+                    let mut added_handles = Vec::new();
+                    while !sleep_or_shutdown(Duration::from_secs(10), &shutdown).await {
+                        let current = wallet_container.read_all();
+                        known_wallets.retain(|addr| current.iter().any(|w| &w.address == addr));
+                        for wallet in current {
+                            if known_wallets.insert(wallet.address.clone()) {
+                                info!("💼 [{}] Nouveau wallet {}, lancement de son mineur", instance_id, wallet.address);
+                                added_handles.push(spawn_wallet_miner(next_idx, wallet, true));
+                                next_idx += 1;
+                            }
+                        }
+                    }
+                    for res in futures::future::join_all(added_handles).await {
+                        if let Err(e) = res {
+                            warn!("⚠️ Tâche de minage terminée en erreur: {:?}", e);
+                        }
+                    }
+                }));
             }
-        });
+            Err(e) => warn!("⚠️ [{}] Surveillance des fichiers de wallets impossible: {}", instance_id, e),
+        }
     }
 
     // --- Stats reporter ---
@@ -320,17 +1180,149 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .unwrap_or_else(|_| "http://stats-backend:8080/insert_stat".to_string());
     let version = env::var("APP_VERSION").unwrap_or_else(|_| "0.1.0".to_string());
 
-    start_stats_reporter(
-        (*uniq_inst_id).clone(),
-        instance_id.clone(),
-        hash_counter.clone(),
+    let stats_reporter = StatsReporter::start(StatsConfig {
+        container_id: (*uniq_inst_id).clone(),
+        miner_id: instance_id.clone(),
+        wallet_counters: wallet_counters.clone(),
+        thread_counters: thread_counters.clone(),
+        api_counters: client.api_totals(),
         server_url,
         version,
-        30,
+        report_interval_secs: 30,
+        shutdown: shutdown.clone(),
+    });
+
+    // --- Métriques Prometheus (pull), en parallèle du reporter (push) ---
+    let metrics_port = env::var("METRICS_PORT")
+        .ok()
+        .and_then(|v| v.parse::<u16>().ok())
+        .unwrap_or(9090);
+    start_metrics_server(
+        metrics_port,
+        MetricsState {
+            instance_id: instance_id.clone(),
+            wallet_counters: wallet_counters.clone(),
+            client: client.clone(),
+            wallet_count,
+            start_time: started_at,
+        },
+        shutdown.clone(),
     );
 
-    info!("🕰️ Boucle de maintien infinie démarrée");
-    loop {
-        sleep(Duration::from_secs(3600)).await;
+    info!("🕰️ En attente d'un signal d'arrêt (Ctrl-C / SIGTERM)");
+    shutdown_signal().await;
+
+    // Arrêt des tâches async (qui lèvent le drapeau d'arrêt de leurs threads de minage) et attente de leur fin
+    info!("🛑 Arrêt demandé, attente de la fin des mineurs...");
+    shutdown.cancel();
+
+    for res in futures::future::join_all(miner_handles).await {
+        if let Err(e) = res {
+            warn!("⚠️ Tâche de minage terminée en erreur: {:?}", e);
+        }
+    }
+
+    // Donation en cours : terminée sur le wallet courant
+    if let Some(donation_handle) = donation_handle {
+        if let Err(e) = donation_handle.await {
+            warn!("⚠️ Tâche de donation terminée en erreur: {:?}", e);
+        }
+    }
+
+    // Flush final des stats (hashs comptés depuis le dernier tick)
+    stats_reporter.shutdown().await;
+
+    instance_lock.release();
+    info!("👋 Arrêt du Scavenger Miner [{}]", instance_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scavenger_miner::api_client::BalanceResponse;
+    use scavenger_miner::mock_api_client::MockApiClient;
+
+    /// Dossier racine d'instances propre au test
+    fn test_root(name: &str) -> PathBuf {
+        let root = env::temp_dir().join(format!("scavenger-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn stale_lock_slot_is_reused() {
+        let root = test_root("stale-lock");
+        let slot = root.join("1");
+        fs::create_dir_all(&slot).unwrap();
+        // Lock récent, même hôte, mais processus propriétaire disparu
+        fs::write(
+            slot.join("in_use.lock"),
+            format!("{} {} {}\n", u32::MAX, Utc::now().timestamp(), hostname()),
+        )
+        .unwrap();
+
+        let (instance_id, dir) = get_instance_dir(root.to_str().unwrap());
+        assert_eq!(instance_id, "miner-1");
+        assert_eq!(dir, slot);
+        let owner = fs::read_to_string(slot.join("in_use.lock")).unwrap();
+        assert!(owner.starts_with(&format!("{} ", process::id())), "{}", owner);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn live_lock_slot_is_skipped() {
+        let root = test_root("live-lock");
+        let slot = root.join("1");
+        fs::create_dir_all(&slot).unwrap();
+        // PID 1 existe toujours sur l'hôte : lock détenu
+        fs::write(slot.join("in_use.lock"), format!("1 {} {}\n", Utc::now().timestamp(), hostname())).unwrap();
+
+        let (instance_id, dir) = get_instance_dir(root.to_str().unwrap());
+        assert_eq!(instance_id, "miner-2");
+        assert_eq!(dir, root.join("2"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn lock_not_refreshed_within_ttl_is_reclaimed() {
+        let root = test_root("old-lock");
+        let slot = root.join("1");
+        fs::create_dir_all(&slot).unwrap();
+        let lock_file = slot.join("in_use.lock");
+        // Autre hôte : seul l'âge du dernier heartbeat (mtime) permet de conclure
+        fs::write(&lock_file, format!("1 {} other-host\n", Utc::now().timestamp())).unwrap();
+        let ttl = Duration::from_secs(300);
+        assert!(!is_lock_stale(&lock_file, ttl));
+
+        let last_heartbeat = std::time::SystemTime::now() - Duration::from_secs(600);
+        fs::File::options().write(true).open(&lock_file).unwrap().set_modified(last_heartbeat).unwrap();
+        assert!(is_lock_stale(&lock_file, ttl));
+
+        let (instance_id, dir) = get_instance_dir(root.to_str().unwrap());
+        assert_eq!(instance_id, "miner-1");
+        assert_eq!(dir, slot);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn balances_are_fetched_through_the_trait() {
+        let mock = Arc::new(MockApiClient::new());
+        mock.set_response("get_wallet_balance", Ok(BalanceResponse::zero("addr_test1wallet")));
+        let addresses = vec!["addr_test1wallet_a".to_string(), "addr_test1wallet_b".to_string()];
+
+        let uniq = Arc::new("uniq".to_string());
+        let fetched = log_wallet_balances(mock.clone(), addresses.clone(), "miner-1".to_string(), uniq.clone()).await;
+        assert_eq!(fetched, 2);
+        assert_eq!(mock.call_count("get_wallet_balance"), 2);
+
+        // Un solde indisponible n'interrompt pas les adresses suivantes
+        mock.set_response::<BalanceResponse>("get_wallet_balance", Err(ApiError::Http("timeout".to_string())));
+        let fetched = log_wallet_balances(mock.clone(), addresses, "miner-1".to_string(), uniq).await;
+        assert_eq!(fetched, 0);
+        assert_eq!(mock.call_count("get_wallet_balance"), 4);
     }
 }
@@ -0,0 +1,113 @@
+// src/metrics.rs
+use std::collections::BTreeMap;
+use std::fmt::Write as FmtWrite;
+use std::net::SocketAddr;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Router};
+use log::{info, warn};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::api_client::ApiClientTrait;
+use crate::miner::VERIFICATION_FAILURES_TOTAL;
+use crate::stats_client::{wallet_prefix, WalletCounters};
+
+/// Données exposées sur `/metrics`
+#[derive(Clone)]
+pub struct MetricsState {
+    pub instance_id: String,
+    pub wallet_counters: WalletCounters,
+    pub client: Arc<dyn ApiClientTrait>,
+    pub wallet_count: usize,
+    pub start_time: Instant,
+}
+
+/// Démarre le serveur Prometheus (`GET /metrics`) sur `0.0.0.0:port`, arrêté à l'annulation de `shutdown`.
+/// Un port indisponible est journalisé sans interrompre le miner.
+pub fn start_metrics_server(port: u16, state: MetricsState, shutdown: CancellationToken) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let app = Router::new().route("/metrics", get(metrics)).with_state(state);
+        let addr = SocketAddr::from(([0, 0, 0, 0], port));
+
+        let listener = match tokio::net::TcpListener::bind(addr).await {
+            Ok(l) => l,
+            Err(e) => {
+                warn!("⚠️ Serveur de métriques non démarré sur {}: {}", addr, e);
+                return;
+            }
+        };
+        info!("📈 Métriques Prometheus sur http://{}/metrics", addr);
+        if let Err(e) = axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown.cancelled_owned())
+            .await
+        {
+            warn!("⚠️ Serveur de métriques arrêté en erreur: {}", e);
+        }
+    })
+}
+
+/// Rendu au format texte Prometheus (version 0.0.4)
+async fn metrics(State(state): State<MetricsState>) -> impl IntoResponse {
+    let instance = &state.instance_id;
+    let mut out = String::new();
+
+    // Libellé = `wallet_prefix` de l'adresse (HRP + début de la partie données), propre à chaque wallet
+    let mut wallet_hashes: BTreeMap<String, u64> = BTreeMap::new();
+    for (address, counter) in state.wallet_counters.read().iter() {
+        *wallet_hashes.entry(wallet_prefix(address)).or_default() += counter.load(Ordering::Relaxed);
+    }
+    let total: u64 = wallet_hashes.values().sum();
+
+    let _ = writeln!(out, "# HELP scavenger_hashes_total Hashs calculés depuis le démarrage.");
+    let _ = writeln!(out, "# TYPE scavenger_hashes_total counter");
+    let _ = writeln!(out, "scavenger_hashes_total{{instance_id=\"{}\"}} {}", instance, total);
+
+    let _ = writeln!(out, "# HELP scavenger_wallet_hashes_total Hashs calculés par wallet (préfixe d'adresse).");
+    let _ = writeln!(out, "# TYPE scavenger_wallet_hashes_total counter");
+    for (prefix, hashes) in &wallet_hashes {
+        let _ = writeln!(
+            out,
+            "scavenger_wallet_hashes_total{{instance_id=\"{}\",wallet=\"{}\"}} {}",
+            instance, prefix, hashes
+        );
+    }
+
+    let _ = writeln!(out, "# HELP scavenger_wallets Nombre de wallets minés par l'instance.");
+    let _ = writeln!(out, "# TYPE scavenger_wallets gauge");
+    let _ = writeln!(out, "scavenger_wallets{{instance_id=\"{}\"}} {}", instance, state.wallet_count);
+
+    let _ = writeln!(out, "# HELP scavenger_api_requests_total Appels à l'API Scavenger par endpoint et résultat.");
+    let _ = writeln!(out, "# TYPE scavenger_api_requests_total counter");
+    for (endpoint, successes, failures) in state.client.api_call_counts() {
+        for (result, count) in [("success", successes), ("failure", failures)] {
+            let _ = writeln!(
+                out,
+                "scavenger_api_requests_total{{instance_id=\"{}\",endpoint=\"{}\",result=\"{}\"}} {}",
+                instance, endpoint, result, count
+            );
+        }
+    }
+
+    let _ = writeln!(out, "# HELP scavenger_verification_failures_total Nonces rejetés par la revalidation locale.");
+    let _ = writeln!(out, "# TYPE scavenger_verification_failures_total counter");
+    let _ = writeln!(
+        out,
+        "scavenger_verification_failures_total{{instance_id=\"{}\"}} {}",
+        instance,
+        VERIFICATION_FAILURES_TOTAL.load(Ordering::Relaxed)
+    );
+
+    let _ = writeln!(out, "# HELP scavenger_uptime_seconds Durée depuis le démarrage du miner.");
+    let _ = writeln!(out, "# TYPE scavenger_uptime_seconds gauge");
+    let _ = writeln!(
+        out,
+        "scavenger_uptime_seconds{{instance_id=\"{}\"}} {}",
+        instance,
+        state.start_time.elapsed().as_secs()
+    );
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], out)
+}
@@ -1,24 +1,40 @@
 // src/miner.rs (optimised, no new deps)
-use parking_lot::{Mutex, RwLock};
+use parking_lot::Mutex;
 use std::sync::{
-    atomic::{AtomicBool, AtomicU64, Ordering},
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     Arc, OnceLock,
 };
 use std::env;
-use std::collections::HashMap;
-use std::fmt::Write as FmtWrite;
+use std::collections::VecDeque;
+use std::fmt;
 use rand::{Rng, thread_rng};
-use crate::api_client::ChallengeParams;
+use crate::challenge::ChallengeParams;
 use ashmaize::{Rom, RomGenerationType, hash};
 use log::{info, debug, warn, error};
 use std::num::ParseIntError;
 use lazy_static::lazy_static;
 
+/// Version du format de préimage calculé par ce mineur :
+/// `nonce (16 hex) + adresse + challenge_id + difficulty + no_pre_mine + latest_submission + no_pre_mine_hour`.
+///
+/// Elle n'est pas incluse dans la préimage (le serveur la reconstruit et rejetterait le hash) ;
+/// elle est comparée à celle que le serveur annoncerait dans le challenge (`preimage_version`).
+pub const PREIMAGE_VERSION: &str = "v1";
+
 /// Configuration du minage
 #[derive(Clone, Debug)]
 pub struct MinerConfig {
     pub address: String,
     pub challenge: Arc<ChallengeParams>,
+    /// Version de préimage attendue par le serveur pour ce challenge, si annoncée
+    pub expected_preimage_version: Option<String>,
+}
+
+impl MinerConfig {
+    pub fn new(address: String, challenge: Arc<ChallengeParams>) -> Self {
+        let expected_preimage_version = challenge.preimage_version.clone();
+        Self { address, challenge, expected_preimage_version }
+    }
 }
 
 /// Résultat du minage
@@ -28,8 +44,113 @@ pub struct MinerResult {
     pub preimage: String,
 }
 
-// Global ROM cache keyed by seed bytes
-static ROM_CACHE: OnceLock<RwLock<HashMap<Vec<u8>, Arc<Rom>>>> = OnceLock::new();
+impl MinerResult {
+    /// Revalide localement un résultat avant soumission : la préimage doit correspondre
+    /// au nonce et au challenge de `config`, et son hash doit respecter le masque de difficulté.
+    pub fn verify(&self, config: &MinerConfig, nb_loops: u32, nb_instrs: u32, rom: &Rom) -> bool {
+        let challenge = &config.challenge;
+        let expected = format!("{}{}", self.nonce, preimage_suffix(&config.address, challenge));
+        if expected != self.preimage {
+            debug!("Préimage inattendue pour le nonce {}", self.nonce);
+            return false;
+        }
+
+        let digest = hash(self.preimage.as_bytes(), rom, nb_loops, nb_instrs);
+        let hash_prefix = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+        (hash_prefix & !difficulty_mask(challenge)) == 0
+    }
+}
+
+/// Partie constante de la préimage, après les 16 caractères hexadécimaux du nonce :
+/// adresse puis champs du challenge. Construite une fois par challenge (minage) et par vérification.
+fn preimage_suffix(address: &str, challenge: &ChallengeParams) -> String {
+    let mut suffix = String::with_capacity(256);
+    suffix.push_str(address);
+    suffix.push_str(&challenge.challenge_id);
+    suffix.push_str(challenge.difficulty.as_deref().unwrap_or_default());
+    suffix.push_str(challenge.no_pre_mine.as_deref().unwrap_or_default());
+    suffix.push_str(challenge.latest_submission.as_deref().unwrap_or_default());
+    suffix.push_str(challenge.no_pre_mine_hour.as_deref().unwrap_or_default());
+    suffix
+}
+
+/// Nombre de résultats rejetés par `MinerResult::verify` depuis le démarrage
+pub static VERIFICATION_FAILURES_TOTAL: AtomicU64 = AtomicU64::new(0);
+
+/// Erreurs de minage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MinerError {
+    /// Un thread de minage a paniqué
+    ThreadPanicked,
+    /// Le délai alloué au minage est dépassé
+    DeadlineExceeded,
+    /// Tous les threads se sont arrêtés sans nonce valide
+    NoResultFound,
+    /// Paramètres de minage invalides
+    InvalidConfig(String),
+    /// Minage interrompu par une demande d'arrêt
+    Cancelled,
+    /// Impossible de créer un thread de minage
+    ThreadSpawn(String),
+    /// Challenge sans `no_pre_mine` : pas de seed de ROM (identifiant du challenge)
+    MissingRomSeed(String),
+}
+
+impl fmt::Display for MinerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MinerError::ThreadPanicked => write!(f, "Thread panicked"),
+            MinerError::DeadlineExceeded => write!(f, "Mining deadline exceeded"),
+            MinerError::NoResultFound => write!(f, "No result found"),
+            MinerError::InvalidConfig(msg) => write!(f, "Invalid miner config: {}", msg),
+            MinerError::Cancelled => write!(f, "Mining cancelled"),
+            MinerError::ThreadSpawn(msg) => write!(f, "Failed to spawn mining thread: {}", msg),
+            MinerError::MissingRomSeed(id) => write!(f, "Challenge {} has no ROM seed (no_pre_mine)", id),
+        }
+    }
+}
+
+impl std::error::Error for MinerError {}
+
+/// Cache LRU des ROM, indexé par seed (~1 GiB par ROM).
+///
+/// `entries` est trié du moins au plus récemment utilisé. Une éviction ne fait que
+/// relâcher l'`Arc` du cache : les mineurs en cours gardent leur clone.
+struct RomCache {
+    entries: VecDeque<(Vec<u8>, Arc<Rom>)>,
+    capacity: usize,
+}
+
+impl RomCache {
+    fn new(capacity: usize) -> Self {
+        Self { entries: VecDeque::with_capacity(capacity), capacity: capacity.max(1) }
+    }
+
+    /// Retourne la ROM du seed et la marque comme la plus récemment utilisée
+    fn get(&mut self, seed: &[u8]) -> Option<Arc<Rom>> {
+        let pos = self.entries.iter().position(|(k, _)| k.as_slice() == seed)?;
+        let entry = self.entries.remove(pos)?;
+        let rom = Arc::clone(&entry.1);
+        self.entries.push_back(entry);
+        Some(rom)
+    }
+
+    /// Insère une ROM en évinçant les moins récemment utilisées au-delà de `capacity`
+    fn insert(&mut self, seed: Vec<u8>, rom: Arc<Rom>) {
+        while self.entries.len() >= self.capacity {
+            if let Some((evicted, _)) = self.entries.pop_front() {
+                info!("🗑️ ROM évincée du cache (seed de {} octets)", evicted.len());
+            }
+        }
+        self.entries.push_back((seed, rom));
+    }
+}
+
+/// Intervalle de vérification d'un thread en pause (mode adaptatif)
+const PAUSED_THREAD_POLL: std::time::Duration = std::time::Duration::from_millis(500);
+
+// Global ROM cache keyed by seed bytes, capped by ROM_CACHE_SIZE (default 1)
+static ROM_CACHE: OnceLock<Mutex<RomCache>> = OnceLock::new();
 fn get_env_var(name: &str, default_value: u32) -> Result<u32, ParseIntError> {
     env::var(name)  
         .unwrap_or_else(|_| default_value.to_string())  
@@ -41,49 +162,355 @@ lazy_static! {
         .parse()
         .unwrap_or(100_000);
 }
-fn get_or_create_rom(seed: &[u8]) -> Arc<Rom> {
-    let cache = ROM_CACHE.get_or_init(|| RwLock::new(HashMap::new()));
-    let key = seed.to_vec();
+/// Paramètres de hash `(nb_loops, nb_instrs)` lus depuis `MINE_NB_LOOPS` / `MINE_NB_INSTRS`
+pub fn hash_params() -> (u32, u32) {
+    (
+        get_env_var("MINE_NB_LOOPS", 4).unwrap_or(4),
+        get_env_var("MINE_NB_INSTRS", 256).unwrap_or(256),
+    )
+}
 
-    // Fast path: read lock to check existing
-    {
-        let read_guard = cache.read();
-        if let Some(existing) = read_guard.get(&key) {
-            return Arc::clone(existing);
-        }
+/// Seed de ROM de repli, utilisé seulement avec `ALLOW_DEFAULT_ROM_SEED=true`
+const DEFAULT_ROM_SEED: &[u8] = b"default-seed";
+
+/// Seed de la ROM d'un challenge (`no_pre_mine`).
+///
+/// Sans `no_pre_mine`, le challenge est malformé : toute solution calculée sur une ROM par défaut
+/// serait refusée, d'où `MinerError::MissingRomSeed`, sauf opt-in explicite (`ALLOW_DEFAULT_ROM_SEED=true`).
+pub fn rom_seed(challenge: &ChallengeParams) -> Result<Vec<u8>, MinerError> {
+    if let Some(seed) = challenge.no_pre_mine.as_ref() {
+        return Ok(seed.as_bytes().to_vec());
+    }
+    let allow_default = env::var("ALLOW_DEFAULT_ROM_SEED")
+        .map(|v| v.to_lowercase() == "true")
+        .unwrap_or(false);
+    if !allow_default {
+        return Err(MinerError::MissingRomSeed(challenge.challenge_id.clone()));
+    }
+    warn!(
+        "⚠️ Challenge {} sans no_pre_mine : ROM par défaut (ALLOW_DEFAULT_ROM_SEED), solutions probablement refusées",
+        challenge.challenge_id
+    );
+    Ok(DEFAULT_ROM_SEED.to_vec())
+}
+
+/// Masque de difficulté (hex) du challenge ; 0 si absent ou illisible
+fn difficulty_mask(challenge: &ChallengeParams) -> u32 {
+    challenge
+        .difficulty
+        .as_ref()
+        .and_then(|d| u32::from_str_radix(d, 16).ok())
+        .unwrap_or(0)
+}
+
+/// Nombre de ROM gardées en cache (`ROM_CACHE_SIZE`, 1 par défaut)
+pub fn rom_cache_capacity() -> usize {
+    get_env_var("ROM_CACHE_SIZE", 1).unwrap_or(1).max(1) as usize
+}
+
+/// Indique si la ROM de `seed` est déjà en cache (sans la promouvoir dans l'ordre LRU)
+pub fn is_rom_cached(seed: &[u8]) -> bool {
+    ROM_CACHE
+        .get()
+        .is_some_and(|cache| cache.lock().entries.iter().any(|(s, _)| s.as_slice() == seed))
+}
+
+/// Taille des ROM générées (`MINE_ROM_SIZE_MB`, 1024 par défaut, pré-ROM de 1/64).
+/// Seule la taille par défaut donne les hashs du serveur : une ROM réduite ne sert qu'aux tests.
+fn rom_size_bytes() -> usize {
+    get_env_var("MINE_ROM_SIZE_MB", 1024).unwrap_or(1024).max(1) as usize * 1024 * 1024
+}
+
+/// Retourne la ROM du seed depuis le cache, en la générant si besoin (opération coûteuse)
+pub fn get_or_create_rom(seed: &[u8]) -> Arc<Rom> {
+    let cache = ROM_CACHE.get_or_init(|| {
+        Mutex::new(RomCache::new(rom_cache_capacity()))
+    });
+
+    // Fast path: ROM already cached
+    if let Some(existing) = cache.lock().get(seed) {
+        return existing;
     }
 
     // Not found: create ROM outside of locks (expensive operation)
+    let size = rom_size_bytes();
     let rom = Arc::new(Rom::new(
         seed,
         RomGenerationType::TwoStep {
-            pre_size: 16 * 1024 * 1024,
+            pre_size: size / 64,
             mixing_numbers: 4,
         },
-        1024 * 1024 * 1024,
+        size,
     ));
 
-    // Insert under write lock (double-check pattern)
+    // Insert under lock (double-check pattern)
     {
-        let mut write_guard = cache.write();
-        if let Some(existing) = write_guard.get(&key) {
-            return Arc::clone(existing);
+        let mut guard = cache.lock();
+        if let Some(existing) = guard.get(seed) {
+            return existing;
         }
-        write_guard.insert(key, Arc::clone(&rom));
+        guard.insert(seed.to_vec(), Arc::clone(&rom));
     }
 
     rom
 }
 
+/// Source des nonces d'un thread de minage
+pub trait NonceGenerator: Send {
+    fn next(&mut self) -> u64;
+}
+
+/// Taille des blocs de nonces réservés d'un coup par `BlockNonceGenerator`
+pub const NONCE_BLOCK_SIZE: u64 = 1 << 40;
+
+// Curseur partagé par tous les threads du process, parti d'une base aléatoire
+static NONCE_CURSOR: OnceLock<Arc<AtomicU64>> = OnceLock::new();
+
+/// Réserve des blocs disjoints de `block_size` nonces sur un curseur atomique partagé puis les
+/// parcourt par incrément de 1 : deux générateurs du même curseur ne visitent jamais le même nonce
+/// (tant que le curseur n'a pas fait le tour des 2^64 valeurs).
+pub struct BlockNonceGenerator {
+    cursor: Arc<AtomicU64>,
+    block_size: u64,
+    current: u64,
+    remaining: u64,
+}
+
+impl BlockNonceGenerator {
+    /// Générateur sur le curseur global du process, par blocs de `NONCE_BLOCK_SIZE`
+    pub fn new() -> Self {
+        let cursor = NONCE_CURSOR.get_or_init(|| Arc::new(AtomicU64::new(thread_rng().gen::<u64>())));
+        Self::with_cursor(Arc::clone(cursor), NONCE_BLOCK_SIZE)
+    }
+
+    /// `block_size` est ramené à au moins 1 ; le premier bloc n'est réservé qu'au premier nonce demandé.
+    pub fn with_cursor(cursor: Arc<AtomicU64>, block_size: u64) -> Self {
+        Self { cursor, block_size: block_size.max(1), current: 0, remaining: 0 }
+    }
+}
+
+impl Default for BlockNonceGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NonceGenerator for BlockNonceGenerator {
+    fn next(&mut self) -> u64 {
+        if self.remaining == 0 {
+            self.current = self.cursor.fetch_add(self.block_size, Ordering::Relaxed);
+            self.remaining = self.block_size;
+        }
+        let nonce = self.current;
+        self.current = self.current.wrapping_add(1);
+        self.remaining -= 1;
+        nonce
+    }
+}
+
+/// `start`, `start + step`, `start + 2 * step`... : séquence déterministe (tests, reproductions)
+pub struct SequentialNonceGenerator {
+    pub start: u64,
+    pub step: u64,
+}
+
+impl NonceGenerator for SequentialNonceGenerator {
+    fn next(&mut self) -> u64 {
+        let nonce = self.start;
+        self.start = self.start.wrapping_add(self.step);
+        nonce
+    }
+}
+
+/// Découpe l'espace des nonces en `total_partitions` plages contiguës et parcourt la plage
+/// `partition_id` : des process (ou threads) de partitions différentes ne se recouvrent jamais.
+pub struct PartitionedNonceGenerator {
+    pub partition_id: u64,
+    pub total_partitions: u64,
+    start: u64,
+    end: u64,
+    current: u64,
+}
+
+impl PartitionedNonceGenerator {
+    /// `partition_id` doit être inférieur à `total_partitions` (au moins 1) : la configuration
+    /// est validée en amont par `NonceStrategy::from_env`.
+    pub fn new(partition_id: u64, total_partitions: u64) -> Self {
+        debug_assert!(
+            partition_id < total_partitions,
+            "partition {} hors de 0..{}",
+            partition_id,
+            total_partitions
+        );
+        let bound = |id: u64| ((id as u128) << 64) / total_partitions as u128;
+        let start = bound(partition_id) as u64;
+        // Borne haute exclusive, 2^64 pour la dernière plage : stockée en inclusif
+        let end = (bound(partition_id + 1) - 1) as u64;
+        Self { partition_id, total_partitions, start, end, current: start }
+    }
+}
+
+impl NonceGenerator for PartitionedNonceGenerator {
+    fn next(&mut self) -> u64 {
+        let nonce = self.current;
+        self.current = if nonce == self.end { self.start } else { nonce + 1 };
+        nonce
+    }
+}
+
+/// Stratégie de nonces des mineurs (`NONCE_STRATEGY`)
+#[derive(Clone, Copy, Debug)]
+pub enum NonceStrategy {
+    /// `random` (défaut) : blocs disjoints de 2^40 nonces réservés sur un curseur partagé,
+    /// parti d'une base aléatoire (`BlockNonceGenerator`)
+    Random,
+    /// `sequential` : thread `i` parcourt `NONCE_START + i`, `+ num_threads`... (reproductible)
+    Sequential { start: u64 },
+    /// `partitioned` : le process `NONCE_PARTITION_ID` parmi `NONCE_PARTITIONS` découpe sa plage
+    /// entre ses threads, sans recouvrement avec les autres process
+    Partitioned { partition_id: u64, total_partitions: u64 },
+}
+
+impl NonceStrategy {
+    /// Lit `NONCE_STRATEGY` et ses paramètres ; une valeur invalide est une erreur de configuration
+    /// plutôt qu'un repli silencieux (deux process sur la même partition mineraient les mêmes nonces).
+    pub fn from_env() -> Result<Self, MinerError> {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    /// `from_env` sur une source de variables quelconque
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self, MinerError> {
+        let number = |name: &str, default: u64| match var(name) {
+            None => Ok(default),
+            Some(v) => v
+                .trim()
+                .parse::<u64>()
+                .map_err(|_| MinerError::InvalidConfig(format!("{} invalide : {:?}", name, v))),
+        };
+        match var("NONCE_STRATEGY").unwrap_or_default().trim().to_lowercase().as_str() {
+            "" | "random" => Ok(NonceStrategy::Random),
+            "sequential" => Ok(NonceStrategy::Sequential { start: number("NONCE_START", 0)? }),
+            "partitioned" => {
+                let partition_id = number("NONCE_PARTITION_ID", 0)?;
+                let total_partitions = number("NONCE_PARTITIONS", 1)?;
+                if total_partitions == 0 {
+                    return Err(MinerError::InvalidConfig("NONCE_PARTITIONS doit être > 0".to_string()));
+                }
+                if partition_id >= total_partitions {
+                    return Err(MinerError::InvalidConfig(format!(
+                        "NONCE_PARTITION_ID ({}) doit être inférieur à NONCE_PARTITIONS ({})",
+                        partition_id, total_partitions
+                    )));
+                }
+                Ok(NonceStrategy::Partitioned { partition_id, total_partitions })
+            }
+            other => Err(MinerError::InvalidConfig(format!(
+                "NONCE_STRATEGY inconnue : {:?} (random, sequential ou partitioned)",
+                other
+            ))),
+        }
+    }
+
+    /// Générateur du thread `thread_index` parmi `num_threads`
+    pub fn generator(&self, thread_index: usize, num_threads: usize) -> Box<dyn NonceGenerator> {
+        let (index, threads) = (thread_index as u64, num_threads.max(1) as u64);
+        match *self {
+            NonceStrategy::Random => Box::new(BlockNonceGenerator::new()),
+            NonceStrategy::Sequential { start } => Box::new(SequentialNonceGenerator {
+                start: start.wrapping_add(index),
+                step: threads,
+            }),
+            NonceStrategy::Partitioned { partition_id, total_partitions } => Box::new(
+                PartitionedNonceGenerator::new(partition_id * threads + index, total_partitions * threads),
+            ),
+        }
+    }
+}
+
+/// Écrit `nonce` en 16 caractères hexadécimaux minuscules (équivalent de `{:016x}`, sans allocation)
+fn write_nonce_hex(dst: &mut [u8], nonce: u64) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    for (i, byte) in dst[..16].iter_mut().enumerate() {
+        *byte = HEX[((nonce >> (60 - 4 * i)) & 0xf) as usize];
+    }
+}
+
+/// Hachage des nonces d'un thread : préimage `{nonce:016x}` + suffixe constant construite une fois,
+/// seul le nonce y est réécrit à chaque hash
+struct PreimageHasher<'a> {
+    rom: &'a Rom,
+    nb_loops: u32,
+    nb_instrs: u32,
+    preimage: Vec<u8>,
+}
+
+impl<'a> PreimageHasher<'a> {
+    fn new(suffix: &str, rom: &'a Rom, nb_loops: u32, nb_instrs: u32) -> Self {
+        let mut preimage = Vec::with_capacity(16 + suffix.len());
+        preimage.extend_from_slice(&[b'0'; 16]);
+        preimage.extend_from_slice(suffix.as_bytes());
+        Self { rom, nb_loops, nb_instrs, preimage }
+    }
+
+    /// Prochain nonce et les 4 premiers octets (big-endian) de son hash
+    fn next(&mut self, generator: &mut dyn NonceGenerator) -> (u64, u32) {
+        let nonce = generator.next();
+        write_nonce_hex(&mut self.preimage, nonce);
+        let digest = hash(&self.preimage, self.rom, self.nb_loops, self.nb_instrs);
+        (nonce, u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]))
+    }
+}
+
+/// Reporte les hashs accumulés localement dans les compteurs partagés puis remet `local` à zéro
+fn flush_counters(global: &Option<Arc<AtomicU64>>, thread: &Option<Arc<AtomicU64>>, local: &mut u64) {
+    if *local == 0 {
+        return;
+    }
+    for counter in [global, thread].into_iter().flatten() {
+        counter.fetch_add(*local, Ordering::Relaxed);
+    }
+    *local = 0;
+}
+
 /// Fonction principale de minage (multi-thread)
 ///
 /// Si `global_counter` est fourni, chaque hash calculé incrémente un compteur partagé
 /// utilisé pour calculer le hashrate global (cross-container via volume partagé).
+///
+/// Si `thread_counters` est fourni (un compteur par thread, `num_threads` au total), le thread
+/// `i` y reporte aussi ses hashs, ce qui permet un hash rate par thread.
+///
+/// Si `stop_flag` est fourni et passe à `true`, les threads s'arrêtent au hash suivant
+/// et `mine` retourne `MinerError::Cancelled`.
+///
+/// `nonce_generator_factory(i)` fournit la source de nonces du thread `i`.
+///
+/// Si `active_threads` est fourni, seuls les threads d'indice inférieur à sa valeur (au moins 1)
+/// calculent des hashs : les autres restent en pause et reprennent dès que la cible remonte.
+/// `num_threads` est alors le nombre maximal de threads.
+///
+/// Les threads sont nommés `miner-<8 premiers caractères de l'adresse>-t<index>`.
 pub fn mine(
     config: MinerConfig,
     num_threads: usize,
     global_counter: Option<Arc<AtomicU64>>,
-) -> Result<MinerResult, String> {
+    thread_counters: Option<Vec<Arc<AtomicU64>>>,
+    active_threads: Option<Arc<AtomicUsize>>,
+    stop_flag: Option<Arc<AtomicBool>>,
+    nonce_generator_factory: impl Fn(usize) -> Box<dyn NonceGenerator>,
+) -> Result<MinerResult, MinerError> {
+    if num_threads == 0 {
+        return Err(MinerError::InvalidConfig("num_threads doit être > 0".to_string()));
+    }
+    if let Some(counters) = &thread_counters {
+        if counters.len() != num_threads {
+            return Err(MinerError::InvalidConfig(format!(
+                "{} compteurs par thread pour {} threads",
+                counters.len(),
+                num_threads
+            )));
+        }
+    }
     info!(
         "🚀 Starting mining: address={}, threads={}, challenge_id={:?}",
         config.address,
@@ -91,17 +518,21 @@ pub fn mine(
         config.challenge.challenge_id
     );
     debug!("MinerConfig details: {:?}", config);
+    if let Some(expected) = config.expected_preimage_version.as_deref() {
+        if expected != PREIMAGE_VERSION {
+            warn!(
+                "⚠️ Format de préimage {} demandé par le serveur pour le challenge {}, ce mineur calcule {} : les solutions risquent d'être refusées",
+                expected, config.challenge.challenge_id, PREIMAGE_VERSION
+            );
+        }
+    }
     debug!("Valeur de LOCAL_BATCH: {}", *LOCAL_BATCH);
     // Clone challenge once
     let challenge = Arc::new((*config.challenge).clone());
     debug!("Cloned challenge params: {:?}", challenge);
 
     // Prepare ROM seed bytes once
-    let rom_seed_bytes: Vec<u8> = challenge
-        .no_pre_mine
-        .as_ref()
-        .map(|s| s.as_bytes().to_vec())
-        .unwrap_or_else(|| b"default-seed".to_vec());
+    let rom_seed_bytes = rom_seed(&challenge)?;
     debug!("ROM seed bytes length: {}", rom_seed_bytes.len());
 
     // Use global cache to avoid regenerating heavy ROM if seed is identical
@@ -114,53 +545,36 @@ pub fn mine(
     debug!("Mining address set to: {}", address);
 
     // Convert difficulty hex into mask
-    let difficulty_mask = challenge
-        .difficulty
-        .as_ref()
-        .and_then(|d| u32::from_str_radix(d, 16).ok())
-        .unwrap_or_else(|| {
-            warn!("No difficulty specified in challenge; using mask = 0");
-            0
-        });
+    if challenge.difficulty.is_none() {
+        warn!("No difficulty specified in challenge; using mask = 0");
+    }
+    let difficulty_mask = difficulty_mask(&challenge);
     info!("Difficulty mask computed: {:#034b}", difficulty_mask);
 
-    // Pre‑extract constant strings
-    let challenge_id = challenge.challenge_id.clone();
-    let difficulty_str = challenge.difficulty.clone().unwrap_or_default();
-    let no_pre_mine_str = challenge.no_pre_mine.clone().unwrap_or_default();
-    let latest_submission_str = challenge.latest_submission.clone().unwrap_or_default();
-    let no_pre_mine_hour_str = challenge.no_pre_mine_hour.clone().unwrap_or_default();
+    // Partie constante de la préimage, partagée par tous les threads (seul le nonce change)
+    let preimage_suffix = Arc::new(preimage_suffix(&address, &challenge));
 
     let mut handles = Vec::with_capacity(num_threads);
     info!("Spawning {} mining threads.", num_threads);
-    let global_nonce_counter = Arc::new(AtomicU64::new(0));
     for thread_index in 0..num_threads {
         let rom = Arc::clone(&rom);
         let address = address.clone();
         let found = Arc::clone(&found_flag);
         let result_ref = Arc::clone(&result);
         let global_counter = global_counter.clone();
-        let global_nonce_counter = Arc::clone(&global_nonce_counter); 
-        // Clone constants for the thread
-        let challenge_id = challenge_id.clone();
-        let difficulty_str = difficulty_str.clone();
-        let no_pre_mine_str = no_pre_mine_str.clone();
-        let latest_submission_str = latest_submission_str.clone();
-        let no_pre_mine_hour_str = no_pre_mine_hour_str.clone();
-
-        let handle = std::thread::spawn(move || {
+        let thread_counter = thread_counters.as_ref().map(|c| Arc::clone(&c[thread_index]));
+        let stop_flag = stop_flag.clone();
+        let active_threads = active_threads.clone();
+        let mut nonce_generator = nonce_generator_factory(thread_index);
+        let preimage_suffix = Arc::clone(&preimage_suffix);
+
+        let thread_name = format!("miner-{}-t{}", address.chars().take(8).collect::<String>(), thread_index);
+        let spawned = std::thread::Builder::new().name(thread_name).spawn(move || {
             debug!("🧵 Thread {} started.", thread_index);
-            //let mut rng = thread_rng();
-            //let mut nonce: u64 = rng.gen::<u64>().wrapping_add(thread_index as u64);
-            let mut nonce: u64 = global_nonce_counter.fetch_add(1, Ordering::Relaxed); 
-            debug!("Thread {} initial nonce: {:016x}", thread_index, nonce);
-            let nb_loops: u32 = get_env_var("MINE_NB_LOOPS", 4).unwrap_or(4); 
-            let nb_instrs: u32 = get_env_var("MINE_NB_INSTRS", 256).unwrap_or(256);  
-
-            // Reusable buffer for preimage construction to avoid allocation each iter
-            let mut preimage_buf = String::with_capacity(256);
-            //let mut preimage_buf = vec![0u8; 256];
-            //let mut buf_len: usize;
+            let (nb_loops, nb_instrs) = hash_params();
+
+            // Préimage réutilisée : seuls les 16 caractères du nonce changent d'un hash à l'autre
+            let mut hasher = PreimageHasher::new(&preimage_suffix, &rom, nb_loops, nb_instrs);
 
             // Local counter to minimise atomic contention
             let mut local_counter: u64 = 0;
@@ -170,32 +584,26 @@ pub fn mine(
                 thread_index, nb_loops, nb_instrs, *LOCAL_BATCH
             );
 
-            while !found.load(Ordering::Acquire) {
-                 // Build preimage into preimage_buf (reuse, avoid format!)
-                 preimage_buf.clear();
-                 // hex nonce (16 hex digits), then concatenated fields
-                 write!(&mut preimage_buf, "{:016x}", nonce).unwrap();
-                 preimage_buf.push_str(&address);
-                 preimage_buf.push_str(&challenge_id);
-                 preimage_buf.push_str(&difficulty_str);
-                 preimage_buf.push_str(&no_pre_mine_str);
-                 preimage_buf.push_str(&latest_submission_str);
-                 preimage_buf.push_str(&no_pre_mine_hour_str);
-
-                 let digest = hash(preimage_buf.as_bytes(), &rom, nb_loops, nb_instrs);
+            while !found.load(Ordering::Acquire)
+                && !stop_flag.as_ref().map_or(false, |f| f.load(Ordering::Relaxed))
+            {
+                // Thread au-delà de la cible adaptative : en pause, nonce conservé pour la reprise
+                if let Some(ref active) = active_threads {
+                    if thread_index >= active.load(Ordering::Relaxed).max(1) {
+                        flush_counters(&global_counter, &thread_counter, &mut local_counter);
+                        std::thread::sleep(PAUSED_THREAD_POLL);
+                        continue;
+                    }
+                }
 
+                let (nonce, hash_prefix) = hasher.next(nonce_generator.as_mut());
 
                 // Increment local counter and flush to global in batches
                 local_counter += 1;
-                if let Some(ref counter) = global_counter {
-                    if local_counter >= *LOCAL_BATCH {
-                        counter.fetch_add(local_counter, Ordering::Relaxed);
-                        local_counter = 0;
-                    }
+                if local_counter >= *LOCAL_BATCH {
+                    flush_counters(&global_counter, &thread_counter, &mut local_counter);
                 }
 
-                let hash_prefix = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
-
                 if (hash_prefix & !difficulty_mask) == 0 {
                     // Found a solution
                     if !found.swap(true, Ordering::AcqRel) {
@@ -203,16 +611,11 @@ pub fn mine(
                             "✅ Thread {} found valid nonce {:016x} | prefix={:032b}",
                             thread_index, nonce, hash_prefix
                         );
-                        if let Some(ref counter) = global_counter {
-                            if local_counter > 0 {
-                                counter.fetch_add(local_counter, Ordering::Relaxed);
-                                local_counter = 0;
-                            }
-                        }
+                        flush_counters(&global_counter, &thread_counter, &mut local_counter);
                         let mut guard = result_ref.lock();
                         *guard = Some(MinerResult {
                             nonce: format!("{:016x}", nonce),
-                            preimage: preimage_buf.clone(),
+                            preimage: format!("{:016x}{}", nonce, preimage_suffix),
                         });
                         debug!("Thread {} wrote result to shared state.", thread_index);
                     } else {
@@ -229,36 +632,38 @@ pub fn mine(
                         thread_index, nonce, hash_prefix
                     );
                 }
-                // Advance nonce by number of threads to avoid collisions
-                //nonce = nonce.wrapping_add(num_threads as u64);
-                nonce = global_nonce_counter.fetch_add(1, Ordering::Relaxed);
             }
 
             // Flush remaining local counter if we exit without finding result
-            if let Some(ref counter) = global_counter {
-                if local_counter > 0 {
-                    counter.fetch_add(local_counter, Ordering::Relaxed);
-                }
-            }
+            flush_counters(&global_counter, &thread_counter, &mut local_counter);
 
             debug!("Thread {} exiting loop.", thread_index);
         });
 
-        handles.push(handle);
+        match spawned {
+            Ok(handle) => handles.push(handle),
+            Err(e) => {
+                // Arrête les threads déjà lancés avant de remonter l'erreur
+                error!("Failed to spawn mining thread {}: {}", thread_index, e);
+                found_flag.store(true, Ordering::Release);
+                for handle in handles {
+                    let _ = handle.join();
+                }
+                return Err(MinerError::ThreadSpawn(e.to_string()));
+            }
+        }
     }
 
     for handle in handles {
         if let Err(_) = handle.join() {
             error!("A mining thread panicked.");
-            return Err("Thread panicked".to_string());
+            return Err(MinerError::ThreadPanicked);
         }
     }
 
     info!("All mining threads joined.");
 
-    let maybe = Arc::try_unwrap(result)
-        .map_err(|_| "Error unwrapping result Arc".to_string())?
-        .into_inner();
+    let maybe = result.lock().take();
 
     match maybe {
         Some(ref r) => {
@@ -268,9 +673,166 @@ pub fn mine(
             );
             Ok(r.clone())
         }
+        None if stop_flag.as_ref().map_or(false, |f| f.load(Ordering::Relaxed)) => {
+            info!("🛑 Mining cancelled.");
+            Err(MinerError::Cancelled)
+        }
         None => {
             warn!("⚠️ Mining completed but no result found.");
-            Err("No result found".to_string())
+            Err(MinerError::NoResultFound)
+        }
+    }
+}
+
+/// `mine` arrêté au plus tard après `deadline` (budget par challenge, date limite de soumission).
+///
+/// L'échéance passe `stop_flag` à `true` comme une demande d'arrêt, mais `mine_with_deadline`
+/// retourne alors `MinerError::DeadlineExceeded` ; `MinerError::Cancelled` reste réservé à un
+/// `stop_flag` levé par l'appelant (arrêt du programme). Sans `deadline`, équivaut à `mine`.
+#[allow(clippy::too_many_arguments)]
+pub fn mine_with_deadline(
+    config: MinerConfig,
+    num_threads: usize,
+    global_counter: Option<Arc<AtomicU64>>,
+    thread_counters: Option<Vec<Arc<AtomicU64>>>,
+    active_threads: Option<Arc<AtomicUsize>>,
+    stop_flag: Arc<AtomicBool>,
+    deadline: Option<std::time::Duration>,
+    nonce_generator_factory: impl Fn(usize) -> Box<dyn NonceGenerator>,
+) -> Result<MinerResult, MinerError> {
+    let expired = Arc::new(AtomicBool::new(false));
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+    let timer = deadline.map(|deadline| {
+        let (stop_flag, expired) = (Arc::clone(&stop_flag), Arc::clone(&expired));
+        std::thread::spawn(move || {
+            // Fin du minage avant l'échéance : `done_tx` est libéré et le minuteur s'arrête
+            if let Err(std::sync::mpsc::RecvTimeoutError::Timeout) = done_rx.recv_timeout(deadline) {
+                expired.store(true, Ordering::Release);
+                stop_flag.store(true, Ordering::Release);
+            }
+        })
+    });
+
+    let result = mine(
+        config,
+        num_threads,
+        global_counter,
+        thread_counters,
+        active_threads,
+        Some(stop_flag),
+        nonce_generator_factory,
+    );
+    drop(done_tx);
+    if let Some(timer) = timer {
+        let _ = timer.join();
+    }
+
+    match result {
+        Err(MinerError::Cancelled) if expired.load(Ordering::Acquire) => {
+            info!("⏱️ Mining deadline exceeded.");
+            Err(MinerError::DeadlineExceeded)
         }
+        other => other,
+    }
+}
+
+/// Seed de ROM des challenges synthétiques du benchmark, identique pour toutes les mesures
+const BENCHMARK_ROM_SEED: &str = "scavenger-benchmark-seed";
+
+/// Challenge synthétique n°`round` du benchmark (masque de difficulté `difficulty`, en hex)
+pub fn benchmark_challenge(round: u64, difficulty: &str) -> ChallengeParams {
+    ChallengeParams {
+        challenge_id: format!("**BENCH{:06}**", round),
+        day: None,
+        challenge_number: None,
+        issued_at: None,
+        latest_submission: Some("2099-12-31T23:59:59Z".to_string()),
+        difficulty: Some(difficulty.to_string()),
+        no_pre_mine: Some(BENCHMARK_ROM_SEED.to_string()),
+        no_pre_mine_hour: Some("0".to_string()),
+        preimage_version: None,
+    }
+}
+
+/// Résultat d'une mesure de `benchmark`
+#[derive(Debug, Clone)]
+pub struct BenchmarkReport {
+    /// Durée de génération de la ROM, exclue de la mesure
+    pub rom_elapsed: std::time::Duration,
+    /// Durée effective de la mesure
+    pub elapsed: std::time::Duration,
+    /// Hashs calculés par chaque thread
+    pub per_thread: Vec<u64>,
+    /// Nonces trouvés, chacun relançant un nouveau challenge synthétique
+    pub nonces_found: u64,
+}
+
+impl BenchmarkReport {
+    pub fn total(&self) -> u64 {
+        self.per_thread.iter().sum()
+    }
+
+    /// Hash rate de `hashes` sur la durée de la mesure (H/s)
+    pub fn rate(&self, hashes: u64) -> f64 {
+        hashes as f64 / self.elapsed.as_secs_f64()
     }
 }
+
+/// Mesure le hash rate local sans aucun appel réseau : les challenges synthétiques de
+/// `benchmark_challenge` sont minés en boucle pendant `duration` sur `num_threads` threads.
+/// La ROM est générée avant la mesure ; l'arrêt à l'échéance coupe aussi le challenge en cours.
+pub fn benchmark(
+    num_threads: usize,
+    duration: std::time::Duration,
+    difficulty: &str,
+) -> Result<BenchmarkReport, MinerError> {
+    let num_threads = num_threads.max(1);
+    let rom_start = std::time::Instant::now();
+    get_or_create_rom(&rom_seed(&benchmark_challenge(0, difficulty))?);
+    let rom_elapsed = rom_start.elapsed();
+
+    let counters: Vec<Arc<AtomicU64>> = (0..num_threads).map(|_| Arc::new(AtomicU64::new(0))).collect();
+    let stop_flag = Arc::new(AtomicBool::new(false));
+    let start = std::time::Instant::now();
+    let timer = {
+        let stop_flag = Arc::clone(&stop_flag);
+        std::thread::spawn(move || {
+            std::thread::sleep(duration);
+            stop_flag.store(true, Ordering::Release);
+        })
+    };
+
+    let mut round = 0u64;
+    let mut nonces_found = 0u64;
+    let outcome = loop {
+        if stop_flag.load(Ordering::Acquire) {
+            break Ok(());
+        }
+        let config = MinerConfig::new("addr1benchmark".to_string(), Arc::new(benchmark_challenge(round, difficulty)));
+        match mine(
+            config,
+            num_threads,
+            None,
+            Some(counters.clone()),
+            None,
+            Some(Arc::clone(&stop_flag)),
+            |_| Box::new(BlockNonceGenerator::new()),
+        ) {
+            Ok(_) => nonces_found += 1,
+            Err(MinerError::Cancelled) => break Ok(()),
+            Err(e) => break Err(e),
+        }
+        round += 1;
+    };
+    let elapsed = start.elapsed();
+    // Une erreur de minage est remontée sans attendre l'échéance du minuteur
+    outcome?;
+    let _ = timer.join();
+
+    Ok(BenchmarkReport {
+        rom_elapsed,
+        elapsed,
+        per_thread: counters.iter().map(|c| c.load(Ordering::Acquire)).collect(),
+        nonces_found,
+    })
+}
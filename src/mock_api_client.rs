@@ -0,0 +1,153 @@
+// src/mock_api_client.rs
+use std::any::Any;
+use std::collections::HashMap;
+use std::error::Error;
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use parking_lot::Mutex;
+
+use crate::api_client::{
+    ApiClientTrait, ApiError, BalanceResponse, ChallengeParams, ChallengeResponse, DonateResponse, RegisterResponse,
+    SubmitResponse, TermsResponse,
+};
+
+/// `ApiClientTrait` sans réseau pour les tests : chaque méthode renvoie la réponse configurée
+/// par `set_response` sous son nom (`"get_challenge"`, `"donate_to"`...), clonée à chaque appel.
+///
+/// Une méthode sans réponse configurée échoue en `ApiError::Http`. Les appels sont enregistrés
+/// dans l'ordre (`calls`) pour vérifier ce qu'un orchestrateur a tenté.
+#[derive(Default)]
+pub struct MockApiClient {
+    /// `Result<T, ApiError>` par nom de méthode, `T` étant le type de succès de la méthode
+    responses: Mutex<HashMap<String, Box<dyn Any + Send + Sync>>>,
+    calls: Mutex<Vec<String>>,
+}
+
+impl MockApiClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Réponse de `method` pour tous les appels suivants (remplace la précédente)
+    pub fn set_response<T: Clone + Send + Sync + 'static>(&self, method: &str, response: Result<T, ApiError>) {
+        self.responses.lock().insert(method.to_string(), Box::new(response));
+    }
+
+    /// Noms des méthodes appelées, dans l'ordre
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().clone()
+    }
+
+    /// Nombre d'appels à `method`
+    pub fn call_count(&self, method: &str) -> usize {
+        self.calls.lock().iter().filter(|c| c.as_str() == method).count()
+    }
+
+    fn respond<T: Clone + 'static>(&self, method: &str) -> Result<T, ApiError> {
+        self.calls.lock().push(method.to_string());
+        match self.responses.lock().get(method) {
+            Some(response) => match response.downcast_ref::<Result<T, ApiError>>() {
+                Some(response) => response.clone(),
+                None => Err(ApiError::Decode(format!("MockApiClient: réponse de {} de type inattendu", method))),
+            },
+            None => Err(ApiError::Http(format!("MockApiClient: aucune réponse configurée pour {}", method))),
+        }
+    }
+}
+
+#[async_trait]
+impl ApiClientTrait for MockApiClient {
+    async fn get_terms(
+        &self,
+        _version: Option<&str>,
+        _miner_id: Option<String>,
+        _container_id: Option<String>,
+    ) -> Result<TermsResponse, Box<dyn Error + Send + Sync>> {
+        Ok(self.respond("get_terms")?)
+    }
+
+    async fn get_terms_cached(
+        &self,
+        _miner_id: Option<String>,
+        _container_id: Option<String>,
+    ) -> Result<TermsResponse, Box<dyn Error + Send + Sync>> {
+        Ok(self.respond("get_terms_cached")?)
+    }
+
+    async fn register_address(
+        &self,
+        _address: &str,
+        _signature: &str,
+        _pubkey: &str,
+        _miner_id: Option<String>,
+        _container_id: Option<String>,
+    ) -> Result<RegisterResponse, Box<dyn Error + Send + Sync>> {
+        Ok(self.respond("register_address")?)
+    }
+
+    async fn batch_register(
+        &self,
+        addresses: &[(String, String, String)],
+        _miner_id: Option<String>,
+        _container_id: Option<String>,
+    ) -> Vec<Result<RegisterResponse, ApiError>> {
+        addresses.iter().map(|_| self.respond("batch_register")).collect()
+    }
+
+    async fn get_challenge(
+        &self,
+        _miner_id: Option<String>,
+        _container_id: Option<String>,
+    ) -> Result<ChallengeResponse, Box<dyn Error + Send + Sync>> {
+        Ok(self.respond("get_challenge")?)
+    }
+
+    /// Flux d'un seul élément : la réponse configurée pour `"get_challenge_stream"`
+    fn get_challenge_stream(
+        &self,
+        _miner_id: Option<String>,
+        _container_id: Option<String>,
+    ) -> BoxStream<'static, Result<ChallengeParams, ApiError>> {
+        stream::iter([self.respond("get_challenge_stream")]).boxed()
+    }
+
+    async fn submit_solution(
+        &self,
+        _address: &str,
+        _challenge_id: &str,
+        _nonce: &str,
+        _miner_id: Option<String>,
+        _container_id: Option<String>,
+    ) -> Result<SubmitResponse, Box<dyn Error + Send + Sync>> {
+        Ok(self.respond("submit_solution")?)
+    }
+
+    async fn verify_solution(&self, _challenge_id: &str, _nonce: &str) -> Result<bool, ApiError> {
+        self.respond("verify_solution")
+    }
+
+    async fn donate_to(
+        &self,
+        _destination_address: &str,
+        _original_address: &str,
+        _signature: &str,
+        _miner_id: Option<String>,
+        _container_id: Option<String>,
+    ) -> Result<DonateResponse, Box<dyn Error + Send + Sync>> {
+        Ok(self.respond("donate_to")?)
+    }
+
+    async fn get_wallet_balance(
+        &self,
+        _address: &str,
+        _miner_id: Option<String>,
+        _container_id: Option<String>,
+    ) -> Result<BalanceResponse, ApiError> {
+        self.respond("get_wallet_balance")
+    }
+
+    fn api_call_counts(&self) -> Vec<(String, u64, u64)> {
+        Vec::new()
+    }
+}
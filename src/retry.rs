@@ -0,0 +1,125 @@
+// src/retry.rs
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+
+/// Paramètres de retry d'un composant (`ApiClient`, donations), lus depuis `<PREFIX>_RETRY_*`
+#[derive(Clone, Debug)]
+pub struct RetryConfig {
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Nombre de nouvelles tentatives après le premier essai (0 = aucun retry)
+    pub max_attempts: u32,
+    /// Part aléatoire du délai, ± `jitter_fraction` (0.0 à 1.0)
+    pub jitter_fraction: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            max_attempts: 0,
+            jitter_fraction: 0.2,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Lit `<prefix>_RETRY_MAX_ATTEMPTS`, `<prefix>_RETRY_BASE_DELAY_MS`, `<prefix>_RETRY_MAX_DELAY_MS`
+    /// et `<prefix>_RETRY_JITTER` ; `max_attempts` est la valeur par défaut du nombre de retries.
+    pub fn from_env(prefix: &str, max_attempts: u32) -> Self {
+        let var = |name: &str| std::env::var(format!("{}_RETRY_{}", prefix, name)).ok();
+        let defaults = Self::default();
+        Self {
+            base_delay: var("BASE_DELAY_MS")
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.base_delay),
+            max_delay: var("MAX_DELAY_MS")
+                .and_then(|v| v.parse::<u64>().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.max_delay),
+            max_attempts: var("MAX_ATTEMPTS").and_then(|v| v.parse::<u32>().ok()).unwrap_or(max_attempts),
+            jitter_fraction: var("JITTER")
+                .and_then(|v| v.parse::<f64>().ok())
+                .map(|j| j.clamp(0.0, 1.0))
+                .unwrap_or(defaults.jitter_fraction),
+        }
+    }
+
+    /// Nouveau backoff, au début de sa séquence
+    pub fn backoff(&self) -> ExponentialBackoff {
+        ExponentialBackoff {
+            base_delay: self.base_delay,
+            max_delay: self.max_delay,
+            max_attempts: self.max_attempts,
+            jitter_fraction: self.jitter_fraction,
+            attempt: 0,
+        }
+    }
+}
+
+/// Délais `base_delay * 2^n` (plafonnés à `max_delay`, avec jitter) pour les `max_attempts` retries
+#[derive(Clone, Debug)]
+pub struct ExponentialBackoff {
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub max_attempts: u32,
+    pub jitter_fraction: f64,
+    attempt: u32,
+}
+
+impl ExponentialBackoff {
+    /// Délai avant le prochain retry, `None` quand les tentatives sont épuisées
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.attempt >= self.max_attempts {
+            return None;
+        }
+        let delay = self
+            .base_delay
+            .checked_mul(2u32.saturating_pow(self.attempt))
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        self.attempt += 1;
+
+        if self.jitter_fraction <= 0.0 {
+            return Some(delay);
+        }
+        let jitter = rand::thread_rng().gen_range(-self.jitter_fraction..=self.jitter_fraction);
+        Some(delay.mul_f64(1.0 + jitter))
+    }
+
+    /// Retries déjà consommés
+    pub fn attempts(&self) -> u32 {
+        self.attempt
+    }
+}
+
+/// Exécute `f` et le relance selon `backoff` tant qu'il échoue
+pub async fn retry<F, Fut, T, E>(backoff: ExponentialBackoff, f: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_if(backoff, f, |_| true).await
+}
+
+/// Comme `retry`, mais seules les erreurs pour lesquelles `should_retry` est vrai sont rejouées
+pub async fn retry_if<F, Fut, T, E, P>(mut backoff: ExponentialBackoff, mut f: F, should_retry: P) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    P: Fn(&E) -> bool,
+{
+    loop {
+        match f().await {
+            Err(e) if should_retry(&e) => match backoff.next_delay() {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return Err(e),
+            },
+            other => return other,
+        }
+    }
+}
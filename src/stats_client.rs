@@ -1,31 +1,286 @@
 // src/stats_client.rs
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use parking_lot::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
+use tokio::task::JoinHandle;
 use tokio::time::{interval, Duration, Instant};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use reqwest::Client;
-use log::{info, warn};
+use log::{debug, info, warn};
 use chrono::Utc;
+use crate::api_client::ApiCallCounters;
 
-#[derive(Serialize)]
-struct StatsPayload<'a> {
+/// Compteurs de hash par wallet, indexés par adresse complète.
+/// Ils ne sont jamais remis à zéro (lus aussi par `/metrics`) : le reporter travaille par différence.
+pub type WalletCounters = Arc<RwLock<HashMap<String, Arc<AtomicU64>>>>;
+
+/// Compteurs de hash par thread de minage (wallet par wallet, dans l'ordre de lancement),
+/// monotones eux aussi.
+pub type ThreadCounters = Arc<RwLock<Vec<Arc<AtomicU64>>>>;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct StatsPayload {
     container_id: String,
-    miner_id: &'a str,
+    miner_id: String,
+    /// Wallet de la mesure ; absent pour la mesure agrégée de l'instance
+    #[serde(default)]
+    wallet_addr: Option<String>,
     timestamp: String,
     hash_rate: f64,
+    /// Moyenne mobile exponentielle des dernières mesures de `hash_rate` (voir `smoothed_hash_rate`)
+    #[serde(default)]
+    hash_rate_ema: f64,
     uptime_secs: u64,
-    version: &'a str,
+    version: String,
+    #[serde(default)]
+    wallet_rates: HashMap<String, f64>,
+    #[serde(default)]
+    thread_rates: Vec<f64>,
+    /// Appels API réussis / échoués depuis la mesure précédente
+    #[serde(default)]
+    api_success: u64,
+    #[serde(default)]
+    api_failure: u64,
+    /// `api_failure / (api_success + api_failure)`, 0 sans appel
+    #[serde(default)]
+    api_error_rate: f64,
 }
 
-/// Lancement du reporter de stats
-pub fn start_stats_reporter(
-    container_id: String,
-    miner_id: String,
-    hash_counter: Arc<AtomicU64>,
-    server_url: String,
-    version: String,
-    report_interval_secs: u64,
-) {
+/// Nombre de mesures de hash rate gardées pour le lissage
+const HASH_RATE_WINDOW: usize = 5;
+/// Poids de la mesure la plus récente dans la moyenne mobile exponentielle
+const HASH_RATE_EMA_ALPHA: f64 = 0.3;
+
+/// Nombre de caractères gardés après le séparateur Bech32 dans `wallet_prefix`
+const WALLET_PREFIX_DATA_CHARS: usize = 12;
+
+/// Clé de `wallet_rates` (et libellé Prometheus) d'un wallet : HRP Bech32 suivi des 12 premiers
+/// caractères de la partie données (`addr_test1qz2fxv2umyht`). Le HRP seul (`addr_test1`) est
+/// commun à toutes les adresses d'un réseau et confondrait tous les wallets.
+pub fn wallet_prefix(address: &str) -> String {
+    let (hrp, data) = address.rfind('1').map_or(("", address), |sep| address.split_at(sep + 1));
+    let data: String = data.chars().take(WALLET_PREFIX_DATA_CHARS).collect();
+    format!("{}{}", hrp, data)
+}
+
+/// Ajoute `rate` à la fenêtre de lissage, en oubliant la mesure la plus ancienne si elle est pleine
+fn push_rate(window: &mut VecDeque<f64>, rate: f64) {
+    if window.len() == HASH_RATE_WINDOW {
+        window.pop_front();
+    }
+    window.push_back(rate);
+}
+
+/// Moyenne mobile exponentielle des mesures de `window`, de la plus ancienne à la plus récente.
+/// La fenêtre étant bornée, un pic (génération de ROM, pause) n'influence le lissage que `HASH_RATE_WINDOW` ticks.
+fn smoothed_hash_rate(window: &VecDeque<f64>) -> f64 {
+    let mut values = window.iter();
+    let first = values.next().copied().unwrap_or(0.0);
+    values.fold(first, |ema, rate| HASH_RATE_EMA_ALPHA * rate + (1.0 - HASH_RATE_EMA_ALPHA) * ema)
+}
+
+/// Issue de l'envoi d'une mesure
+enum SendOutcome {
+    Sent,
+    /// Refus définitif (4xx hors 429) : inutile de la rejouer
+    Rejected,
+    /// Backend injoignable, timeout, 429 ou 5xx : à rejouer plus tard
+    Failed,
+}
+
+/// Tampon borné des mesures en attente d'envoi (les plus anciennes sont perdues en premier).
+///
+/// Si `STATS_BUFFER_FILE` est défini, le tampon y est persisté en JSON pour survivre aux redémarrages.
+struct StatsBuffer {
+    entries: Mutex<VecDeque<StatsPayload>>,
+    capacity: usize,
+    path: Option<PathBuf>,
+    flushing: AtomicBool,
+}
+
+impl StatsBuffer {
+    fn load(capacity: usize, path: Option<PathBuf>) -> Self {
+        let mut entries: VecDeque<StatsPayload> = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        while entries.len() > capacity {
+            entries.pop_front();
+        }
+        if !entries.is_empty() {
+            info!("📊 {} stats en attente rechargées depuis le disque", entries.len());
+        }
+        Self {
+            entries: Mutex::new(entries),
+            capacity,
+            path,
+            flushing: AtomicBool::new(false),
+        }
+    }
+
+    fn push(&self, payload: StatsPayload) {
+        let mut entries = self.entries.lock();
+        if entries.len() >= self.capacity {
+            entries.pop_front();
+            warn!("📊 Tampon de stats plein ({}), mesure la plus ancienne perdue", self.capacity);
+        }
+        entries.push_back(payload);
+    }
+
+    /// Écrit le tampon sur disque (fichier temporaire puis rename)
+    fn persist(&self) {
+        let path = match &self.path {
+            Some(p) => p,
+            None => return,
+        };
+        let json = match serde_json::to_vec(&*self.entries.lock()) {
+            Ok(j) => j,
+            Err(e) => {
+                warn!("Failed to serialize stats buffer: {}", e);
+                return;
+            }
+        };
+        let tmp = path.with_extension("tmp");
+        if let Err(e) = fs::write(&tmp, json).and_then(|_| fs::rename(&tmp, path)) {
+            warn!("⚠️ Impossible d’écrire le tampon de stats {:?}: {}", path, e);
+        }
+    }
+
+    /// Envoie les mesures en attente, de la plus ancienne à la plus récente ;
+    /// s'arrête au premier échec en remettant la mesure en tête. Un seul flush à la fois.
+    async fn flush(&self, client: &Client, url: &str, bearer_token: &str) {
+        if self.flushing.swap(true, Ordering::AcqRel) {
+            debug!("📊 Flush des stats déjà en cours");
+            return;
+        }
+        let mut sent = 0usize;
+        loop {
+            let payload = match self.entries.lock().pop_front() {
+                Some(p) => p,
+                None => break,
+            };
+            match send_payload(client, url, bearer_token, &payload).await {
+                SendOutcome::Sent => sent += 1,
+                SendOutcome::Rejected => {}
+                SendOutcome::Failed => {
+                    self.entries.lock().push_front(payload);
+                    break;
+                }
+            }
+        }
+        let pending = self.entries.lock().len();
+        if pending > 0 {
+            warn!("📊 {} stats en attente (backend injoignable), nouvel essai au prochain tick", pending);
+        } else if sent > 1 {
+            info!("📊 {} stats en attente envoyées", sent);
+        }
+        self.persist();
+        self.flushing.store(false, Ordering::Release);
+    }
+}
+
+/// Envoi d'une mesure (timeout très court)
+async fn send_payload(client: &Client, url: &str, bearer_token: &str, payload: &StatsPayload) -> SendOutcome {
+    let req = client.post(url)
+        .header("content-type", "application/json")
+        .header("Authorization", format!("Bearer {}", bearer_token))
+        .json(payload);
+
+    match tokio::time::timeout(Duration::from_secs(1), req.send()).await {
+        Ok(Ok(resp)) if resp.status().is_success() => {
+            info!("Stats sent successfully ({} H/s)", payload.hash_rate);
+            SendOutcome::Sent
+        }
+        Ok(Ok(resp)) if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+            warn!("Stats rate-limited by server (kept for retry)");
+            SendOutcome::Failed
+        }
+        Ok(Ok(resp)) if resp.status().is_client_error() => {
+            warn!("Stats rejected by server, status={} (dropped)", resp.status());
+            SendOutcome::Rejected
+        }
+        Ok(Ok(resp)) => {
+            warn!("Stats sent but server returned status={}", resp.status());
+            SendOutcome::Failed
+        }
+        Ok(Err(e)) => {
+            warn!("HTTP error sending stats: {}", e);
+            SendOutcome::Failed
+        }
+        Err(_) => {
+            warn!("Stats send timed out");
+            SendOutcome::Failed
+        }
+    }
+}
+
+/// Paramètres du reporter de stats
+pub struct StatsConfig {
+    pub container_id: String,
+    pub miner_id: String,
+    pub wallet_counters: WalletCounters,
+    pub thread_counters: ThreadCounters,
+    /// Compteurs d'appels API de l'`ApiClient` (`ApiClient::api_totals`)
+    pub api_counters: Arc<ApiCallCounters>,
+    pub server_url: String,
+    pub version: String,
+    pub report_interval_secs: u64,
+    /// Arrêt global : son annulation déclenche aussi le flush final
+    pub shutdown: CancellationToken,
+}
+
+/// Reporter de stats en tâche de fond, arrêté par `shutdown` (ou par l'arrêt global de `StatsConfig`).
+///
+/// Le hash rate global est la somme des compteurs de `wallet_counters`, chacun étant aussi
+/// remonté individuellement dans `wallet_rates`. Le hash rate de chaque thread de
+/// `thread_counters` est remonté dans `thread_rates`.
+///
+/// Sauf `STATS_PER_WALLET=false`, chaque wallet fait aussi l'objet de sa propre mesure
+/// (`wallet_addr` renseigné), envoyée après la mesure agrégée.
+///
+/// Les mesures non envoyées (backend injoignable) sont gardées dans un tampon de
+/// `STATS_BUFFER_CAPACITY` entrées (1000 par défaut) et rejouées avant la mesure courante.
+///
+/// À l'arrêt, une dernière mesure est envoyée (en attendant la réponse) puis la tâche se termine.
+pub struct StatsReporter {
+    shutdown: CancellationToken,
+    handle: JoinHandle<()>,
+}
+
+impl StatsReporter {
+    /// Lance la tâche du reporter
+    pub fn start(config: StatsConfig) -> Self {
+        let shutdown = config.shutdown.child_token();
+        let handle = spawn_reporter(config, shutdown.clone());
+        Self { shutdown, handle }
+    }
+
+    /// Demande l'arrêt et attend la fin du flush final
+    pub async fn shutdown(self) {
+        self.shutdown.cancel();
+        if let Err(e) = self.handle.await {
+            warn!("⚠️ Reporter de stats terminé en erreur: {:?}", e);
+        }
+    }
+}
+
+fn spawn_reporter(config: StatsConfig, shutdown: CancellationToken) -> JoinHandle<()> {
+    let StatsConfig {
+        container_id,
+        miner_id,
+        wallet_counters,
+        thread_counters,
+        api_counters,
+        server_url,
+        version,
+        report_interval_secs,
+        ..
+    } = config;
 
     let client = Client::builder()
         .pool_idle_timeout(Duration::from_secs(15))
@@ -34,85 +289,181 @@ pub fn start_stats_reporter(
 
     let bearer_token = std::env::var("STATS_BEARER_TOKEN").unwrap_or_default();
     let ctn_prefix = std::env::var("CONTAINER_PREFIX").unwrap_or_else(|_| "".to_string());
+    let buffer_capacity = std::env::var("STATS_BUFFER_CAPACITY")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|c| *c > 0)
+        .unwrap_or(1000);
+    let buffer_path = std::env::var("STATS_BUFFER_FILE").ok().filter(|p| !p.is_empty()).map(PathBuf::from);
+    let buffer = Arc::new(StatsBuffer::load(buffer_capacity, buffer_path));
+    let per_wallet = std::env::var("STATS_PER_WALLET")
+        .map(|v| v.to_lowercase() != "false")
+        .unwrap_or(true);
 
     tokio::spawn(async move {
         let mut ticker = interval(Duration::from_secs(report_interval_secs));
         let mut last_instant = Instant::now();
+        let mut last_counts: HashMap<String, u64> = HashMap::new();
+        let mut last_thread_counts: Vec<u64> = Vec::new();
+        let mut last_api_counts = (0u64, 0u64);
+        let mut hash_rate_window: VecDeque<f64> = VecDeque::with_capacity(HASH_RATE_WINDOW);
+        let mut wallet_windows: HashMap<String, VecDeque<f64>> = HashMap::new();
         let start_time = Utc::now();
 
         loop {
-            ticker.tick().await;
+            let final_flush = tokio::select! {
+                _ = ticker.tick() => false,
+                _ = shutdown.cancelled() => true,
+            };
 
             // Mesure claire de l'intervalle écoulé entre deux ticks
             let now = Instant::now();
             let elapsed = now.duration_since(last_instant).as_secs_f64();
             last_instant = now;
 
-            // Lecture atomique & remise à zéro
-            let hashes = hash_counter.swap(0, std::sync::atomic::Ordering::AcqRel) as f64;
-            if hashes == 0.0 {
-                info!("Aucun hash calculé depuis le dernier tick");
-                continue; 
-            }
-
-            let hashrate = if elapsed > 0.0 { hashes / elapsed } else { 0.0 };
-            let uptime = (Utc::now() - start_time).num_seconds().max(0) as u64;
-            //let ctn_id = format!("{}", ctn_prefix);
-            let ctn_id = format!("{}/{}", ctn_prefix, container_id.clone());
-            let payload = StatsPayload {
-                container_id: ctn_id.clone(),
-                miner_id: &miner_id,
-                timestamp: Utc::now().to_rfc3339(),
-                hash_rate: hashrate,
-                uptime_secs: uptime,
-                version: &version,
+            // Hashs calculés depuis le tick précédent, par wallet
+            let wallet_hashes: Vec<(String, f64)> = wallet_counters
+                .read()
+                .iter()
+                .map(|(address, counter)| {
+                    let total = counter.load(Ordering::Acquire);
+                    let previous = last_counts.insert(address.clone(), total).unwrap_or(0);
+                    (address.clone(), total.saturating_sub(previous) as f64)
+                })
+                .collect();
+            let hashes: f64 = wallet_hashes.iter().map(|(_, h)| h).sum();
+            let thread_hashes: Vec<f64> = {
+                let counters = thread_counters.read();
+                last_thread_counts.resize(counters.len(), 0);
+                counters
+                    .iter()
+                    .zip(last_thread_counts.iter_mut())
+                    .map(|(counter, previous)| {
+                        let total = counter.load(Ordering::Acquire);
+                        let delta = total.saturating_sub(*previous);
+                        *previous = total;
+                        delta as f64
+                    })
+                    .collect()
             };
-            info!(
-                "📥  stat: miner_id={} hash_rate={} timestamp={}",
-                payload.miner_id,
-                payload.hash_rate,
-                payload.timestamp
+
+            let api_counts = (
+                api_counters.successes.load(Ordering::Relaxed),
+                api_counters.failures.load(Ordering::Relaxed),
             );
+            let api_success = api_counts.0.saturating_sub(last_api_counts.0);
+            let api_failure = api_counts.1.saturating_sub(last_api_counts.1);
+            last_api_counts = api_counts;
 
             let call_api_enabled = std::env::var("ENABLE_STATS_BACKEND")
                 .unwrap_or_else(|_| "false".to_string())
                 .to_lowercase() == "true";
-            
-            if !call_api_enabled {                
+
+            if hashes == 0.0 {
+                // Rien de neuf, mais les mesures en attente peuvent être rejouées
+                info!("Aucun hash calculé depuis le dernier tick");
+            } else {
+                let rate = |h: f64| if elapsed > 0.0 { h / elapsed } else { 0.0 };
+                let hashrate = rate(hashes);
+                push_rate(&mut hash_rate_window, hashrate);
+                let mut wallet_rates: HashMap<String, f64> = HashMap::new();
+                for (address, h) in &wallet_hashes {
+                    *wallet_rates.entry(wallet_prefix(address)).or_default() += rate(*h);
+                }
+                let thread_rates: Vec<f64> = thread_hashes.into_iter().map(rate).collect();
+                let uptime = (Utc::now() - start_time).num_seconds().max(0) as u64;
+                //let ctn_id = format!("{}", ctn_prefix);
+                let ctn_id = format!("{}/{}", ctn_prefix, container_id.clone());
+                let payload = StatsPayload {
+                    container_id: ctn_id.clone(),
+                    miner_id: miner_id.clone(),
+                    wallet_addr: None,
+                    timestamp: Utc::now().to_rfc3339(),
+                    hash_rate: hashrate,
+                    hash_rate_ema: smoothed_hash_rate(&hash_rate_window),
+                    uptime_secs: uptime,
+                    version: version.clone(),
+                    wallet_rates,
+                    thread_rates,
+                    api_success,
+                    api_failure,
+                    api_error_rate: if api_success + api_failure > 0 {
+                        api_failure as f64 / (api_success + api_failure) as f64
+                    } else {
+                        0.0
+                    },
+                };
+                info!(
+                    "📥  stat: miner_id={} hash_rate={} hash_rate_ema={:.2} timestamp={}",
+                    payload.miner_id,
+                    payload.hash_rate,
+                    payload.hash_rate_ema,
+                    payload.timestamp
+                );
+                // Une mesure par wallet, sans les détails propres à l'instance (threads, appels API)
+                let wallet_payloads: Vec<StatsPayload> = if per_wallet {
+                    wallet_hashes
+                        .iter()
+                        .map(|(address, h)| {
+                            let window = wallet_windows.entry(address.clone()).or_default();
+                            push_rate(window, rate(*h));
+                            StatsPayload {
+                                wallet_addr: Some(address.clone()),
+                                hash_rate: rate(*h),
+                                hash_rate_ema: smoothed_hash_rate(window),
+                                wallet_rates: HashMap::new(),
+                                thread_rates: Vec::new(),
+                                api_success: 0,
+                                api_failure: 0,
+                                api_error_rate: 0.0,
+                                ..payload.clone()
+                            }
+                        })
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                if call_api_enabled {
+                    buffer.push(payload);
+                    for wallet_payload in wallet_payloads {
+                        buffer.push(wallet_payload);
+                    }
+                }
+            }
+
+            if !call_api_enabled {
+                // On passe au tick suivant : le backend peut être réactivé à chaud
                 info!("📊 Reporting hash rate désactivé");
-                return;
-            } 
-            let body = match serde_json::to_vec(&payload) {
-                Ok(b) => b,
-                Err(e) => {
-                    warn!("Failed to serialize stats payload: {}", e);
-                    continue;
+                if final_flush {
+                    break;
                 }
-            };
+                continue;
+            }
+
+            if final_flush {
+                // Flush final : on laisse finir un éventuel flush en cours, puis on attend l'envoi
+                let deadline = Instant::now() + Duration::from_secs(5);
+                while buffer.flushing.load(Ordering::Acquire) && Instant::now() < deadline {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+                buffer.flush(&client, &server_url, &bearer_token).await;
+                break;
+            }
 
             let url = server_url.clone();
             let client = client.clone();
             let bearer_token = bearer_token.clone();
+            let buffer = Arc::clone(&buffer);
+            let send = async move {
+                buffer.flush(&client, &url, &bearer_token).await;
+            };
 
-            // Fire-and-forget, timeout très court
-            tokio::spawn(async move {
-                let req = client.post(&url)
-                    .header("content-type", "application/json")
-                    .header("Authorization", format!("Bearer {}", bearer_token))
-                    .body(body);
-
-                match tokio::time::timeout(Duration::from_secs(1), req.send()).await {
-                    Ok(Ok(resp)) => {
-                        if !resp.status().is_success() {
-                            warn!("Stats sent but server returned status={}", resp.status());
-                        } else {
-                            info!("Stats sent successfully ({} H/s)", hashrate);
-                        }
-                    }
-                    Ok(Err(e)) => warn!("HTTP error sending stats: {}", e),
-                    Err(_) => warn!("Stats send timed out"),
-                }
-            });
+            // Fire-and-forget
+            tokio::spawn(send);
         }
-    });
+
+        // Ce qui n'a pas pu partir reste sur disque (si STATS_BUFFER_FILE est défini)
+        buffer.persist();
+        info!("📊 Reporter de stats arrêté");
+    })
 }
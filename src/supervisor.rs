@@ -0,0 +1,182 @@
+// src/supervisor.rs
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::FutureExt;
+use log::{error, info, warn};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// Délai maximal entre deux relances
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(300);
+
+/// Lance la tâche produite par `make_future` et la relance si elle panique.
+///
+/// Après la panique n° `attempt` (à partir de 0), la relance attend `base_delay * 2^attempt`
+/// (plafonné à 300 s). Une exécution qui a duré plus de 300 s remet le compteur à zéro.
+/// Au-delà de `max_retries` paniques consécutives, la tâche est abandonnée.
+/// Une fin normale de la tâche termine la supervision.
+pub fn spawn_supervised<F, Fut>(
+    make_future: F,
+    instance_id: String,
+    wallet_prefix: String,
+    max_retries: u32,
+    base_delay: Duration,
+) -> JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut failures: u32 = 0;
+        loop {
+            let started = Instant::now();
+            let panic = match AssertUnwindSafe(make_future()).catch_unwind().await {
+                Ok(()) => return,
+                Err(panic) => panic,
+            };
+
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "panique sans message".to_string());
+            if started.elapsed() > MAX_RESTART_DELAY {
+                failures = 0;
+            }
+            if failures >= max_retries {
+                error!(
+                    "💀 [{}] {} Tâche abandonnée après {} paniques consécutives (dernière : {})",
+                    instance_id, wallet_prefix, failures + 1, message
+                );
+                return;
+            }
+
+            let delay = base_delay
+                .checked_mul(2u32.saturating_pow(failures))
+                .unwrap_or(MAX_RESTART_DELAY)
+                .min(MAX_RESTART_DELAY);
+            failures += 1;
+            warn!(
+                "💥 [{}] {} Tâche paniquée ({}), relance {}/{} dans {:?}",
+                instance_id, wallet_prefix, message, failures, max_retries, delay
+            );
+            sleep(delay).await;
+            info!("🔁 [{}] {} Relance de la tâche", instance_id, wallet_prefix);
+        }
+    })
+}
+
+/// Ajuste périodiquement `target`, le nombre de threads de minage actifs par wallet,
+/// selon la charge de la machine.
+///
+/// Toutes les `interval`, la charge moyenne sur 1 minute est lue via `sysinfo` ; la part due
+/// aux autres process (charge moins nos propres threads actifs) est retranchée du nombre de CPU,
+/// et le reste est réparti entre les `wallet_count()` wallets, borné à `[min, max]`.
+pub fn spawn_thread_scaler<W>(
+    target: Arc<AtomicUsize>,
+    wallet_count: W,
+    min: usize,
+    max: usize,
+    interval: Duration,
+    shutdown: CancellationToken,
+) -> JoinHandle<()>
+where
+    W: Fn() -> usize + Send + 'static,
+{
+    tokio::spawn(async move {
+        let cpus = num_cpus::get() as f64;
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => return,
+                _ = sleep(interval) => {}
+            }
+
+            let wallets = wallet_count().max(1);
+            let current = target.load(Ordering::Relaxed);
+            let load = sysinfo::System::load_average().one;
+            let other_load = (load - (current * wallets) as f64).max(0.0);
+            let available = (cpus - other_load).max(0.0);
+            let wanted = ((available / wallets as f64).floor() as usize).clamp(min, max);
+
+            if wanted != current {
+                target.store(wanted, Ordering::Relaxed);
+                info!(
+                    "⚖️ Charge {:.2} sur {} CPU : {} → {} threads actifs par wallet",
+                    load, cpus, current, wanted
+                );
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn panicking_task_is_relaunched_after_base_delay() {
+        let base_delay = Duration::from_millis(200);
+        let runs = Arc::new(parking_lot::Mutex::new(Vec::<Instant>::new()));
+
+        let handle = {
+            let runs = Arc::clone(&runs);
+            spawn_supervised(
+                move || {
+                    let runs = Arc::clone(&runs);
+                    async move {
+                        let first = {
+                            let mut runs = runs.lock();
+                            runs.push(Instant::now());
+                            runs.len() == 1
+                        };
+                        if first {
+                            panic!("panique de test");
+                        }
+                    }
+                },
+                "test".to_string(),
+                "addr_test1".to_string(),
+                3,
+                base_delay,
+            )
+        };
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .expect("supervision terminée après la relance")
+            .expect("tâche de supervision sans panique");
+
+        // Une panique puis une relance qui se termine normalement
+        let runs = runs.lock();
+        assert_eq!(runs.len(), 2);
+        assert!(runs[1] - runs[0] >= base_delay, "relance après {:?}", runs[1] - runs[0]);
+    }
+
+    #[tokio::test]
+    async fn task_is_abandoned_after_max_retries() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let handle = {
+            let runs = Arc::clone(&runs);
+            spawn_supervised(
+                move || {
+                    let runs = Arc::clone(&runs);
+                    async move {
+                        runs.fetch_add(1, Ordering::Relaxed);
+                        panic!("panique de test");
+                    }
+                },
+                "test".to_string(),
+                "addr_test1".to_string(),
+                2,
+                Duration::from_millis(10),
+            )
+        };
+        tokio::time::timeout(Duration::from_secs(5), handle).await.unwrap().unwrap();
+        // Premier lancement puis `max_retries` relances
+        assert_eq!(runs.load(Ordering::Relaxed), 3);
+    }
+}
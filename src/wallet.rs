@@ -1,8 +1,10 @@
+use std::fmt;
 use std::fs;
 use std::path::Path;
-use rand::{RngCore, SeedableRng};
+use rand::{rngs::OsRng, RngCore, SeedableRng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use rand_chacha::ChaCha20Rng;
-use ed25519_dalek::{Signer, SigningKey};
+use ed25519_dalek::{Signer, SigningKey, Verifier, VerifyingKey};
 use bip39::{Mnemonic, Language};
 use hex;
 use zeroize::Zeroize;
@@ -11,14 +13,203 @@ use blake2::digest::{Update, VariableOutput};
 use blake2::Blake2bVar;
 use ciborium::value::{Value, Integer};
 use serde_cbor::to_vec;
-use log::info;
+use log::{info, warn};
 use ed25519_dalek::Signature;
 use serde_cbor::de::from_slice;
+use ed25519_bip32::{DerivationScheme, XPrv, XPRV_SIZE};
+use sha2::Sha512;
+use argon2::Argon2;
+use chacha20poly1305::{aead::{Aead, KeyInit}, Key, XChaCha20Poly1305, XNonce};
+
+/// Index durci (hardened) BIP32
+const HARDENED: u32 = 0x8000_0000;
+
+/// Format des fichiers chiffrés : `MSENC1` || sel (16 o) || nonce (24 o) || ciphertext
+const ENCRYPTED_MAGIC: &[u8] = b"MSENC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Erreurs de chargement des wallets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalletError {
+    /// La seed et la clé d'une même ligne (numérotée à partir de 1) ne donnent pas la même adresse
+    KeySeedMismatch { line: usize, seed_addr: String, key_addr: String },
+    /// Clé privée Bech32 (`ed25519_sk1...`) illisible ou de mauvaise taille
+    InvalidPrivateKey(String),
+    /// Archive de sauvegarde chiffrée illisible, incomplète ou mot de passe incorrect
+    Backup(String),
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalletError::KeySeedMismatch { line, seed_addr, key_addr } => write!(
+                f,
+                "ligne {} : la seed donne {} mais la clé donne {}",
+                line, seed_addr, key_addr
+            ),
+            WalletError::InvalidPrivateKey(reason) => write!(f, "clé privée Bech32 invalide : {}", reason),
+            WalletError::Backup(reason) => write!(f, "sauvegarde chiffrée : {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for WalletError {}
+
+/// Adresse du wallet non décodable (fichier de seeds/clés modifié à la main, par exemple)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddressError {
+    /// Chaîne Bech32 invalide (caractères, checksum...)
+    Bech32 { address: String, reason: String },
+    /// Données Bech32 non convertibles en octets
+    Payload { address: String, reason: String },
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressError::Bech32 { address, reason } => {
+                write!(f, "adresse {:?} : décodage Bech32 impossible ({})", address, reason)
+            }
+            AddressError::Payload { address, reason } => {
+                write!(f, "adresse {:?} : conversion base32 impossible ({})", address, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AddressError {}
+
+/// Clé privée d'un wallet : ed25519 « classique » (32 octets) ou clé étendue BIP32-Ed25519 (CIP-1852)
+#[derive(Clone)]
+enum WalletKey {
+    Ed25519(SigningKey),
+    Extended(XPrv),
+}
+
+impl WalletKey {
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            WalletKey::Ed25519(sk) => sk.sign(message).to_bytes().to_vec(),
+            WalletKey::Extended(xprv) => {
+                let sig: ed25519_bip32::Signature<Vec<u8>> = xprv.sign(message);
+                sig.as_ref().to_vec()
+            }
+        }
+    }
+
+    fn public_key_bytes(&self) -> [u8; 32] {
+        match self {
+            WalletKey::Ed25519(sk) => sk.verifying_key().to_bytes(),
+            WalletKey::Extended(xprv) => xprv.public().public_key(),
+        }
+    }
+
+    /// 32 octets (ed25519) ou 64 octets (clé étendue kL || kR)
+    fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            WalletKey::Ed25519(sk) => sk.to_bytes().to_vec(),
+            WalletKey::Extended(xprv) => xprv.as_ref()[..64].to_vec(),
+        }
+    }
+}
+
+impl From<SigningKey> for WalletKey {
+    fn from(sk: SigningKey) -> Self {
+        WalletKey::Ed25519(sk)
+    }
+}
+
+/// Longueurs de phrase mnémonique BIP-39 supportées
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MnemonicLength {
+    Words12,
+    Words15,
+    Words18,
+    Words21,
+    #[default]
+    Words24,
+}
+
+impl MnemonicLength {
+    /// Valide un nombre de mots (12/15/18/21/24)
+    pub fn from_word_count(word_count: usize) -> Result<Self, String> {
+        match word_count {
+            12 => Ok(MnemonicLength::Words12),
+            15 => Ok(MnemonicLength::Words15),
+            18 => Ok(MnemonicLength::Words18),
+            21 => Ok(MnemonicLength::Words21),
+            24 => Ok(MnemonicLength::Words24),
+            n => Err(format!(
+                "Longueur de phrase mnémonique non supportée : {} mots (attendu 12, 15, 18, 21 ou 24)",
+                n
+            )),
+        }
+    }
+
+    /// Taille d'entropie correspondante en octets
+    pub fn entropy_len(self) -> usize {
+        match self {
+            MnemonicLength::Words12 => 16,
+            MnemonicLength::Words15 => 20,
+            MnemonicLength::Words18 => 24,
+            MnemonicLength::Words21 => 28,
+            MnemonicLength::Words24 => 32,
+        }
+    }
+}
+
+/// Type d'adresse des wallets dérivés d'une phrase mnémonique (`WALLET_ADDRESS_TYPE`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AddressType {
+    /// Schéma historique : clé = 32 premiers octets de la seed BIP-39, adresse de paiement
+    /// par hash de clé sans partie staking (header `0b0110`)
+    #[default]
+    KeyHash,
+    /// Adresse entreprise (header `0b0110`, sans partie staking) de la clé de paiement Shelley,
+    /// soit la partie paiement de l'adresse `Base` de la même phrase
+    Enterprise,
+    /// Adresse base Shelley (header `0b0000`) : clé de paiement + clé de staking
+    Base,
+    /// Adresse base de la dérivation standard CIP-1852 (`generate_cip1852`), compatible
+    /// Eternl / Lace ; `keys.hex` contient la clé étendue de paiement (64 octets)
+    Cip1852 { account: u32, index: u32 },
+}
+
+impl std::str::FromStr for AddressType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "key_hash" | "keyhash" => Ok(AddressType::KeyHash),
+            "enterprise" => Ok(AddressType::Enterprise),
+            "base" => Ok(AddressType::Base),
+            "cip1852" => Ok(AddressType::Cip1852 { account: 0, index: 0 }),
+            other => {
+                // `cip1852:<account>:<index>`
+                let path: Option<Vec<u32>> = other
+                    .strip_prefix("cip1852:")
+                    .and_then(|rest| rest.split(':').map(|n| n.parse().ok()).collect());
+                match path.as_deref() {
+                    Some(&[account, index]) => Ok(AddressType::Cip1852 { account, index }),
+                    _ => Err(format!(
+                        "Type d'adresse inconnu : {} (attendu key_hash, enterprise, base ou cip1852[:<account>:<index>])",
+                        other
+                    )),
+                }
+            }
+        }
+    }
+}
+
+/// Ligne de `seeds.txt` d'un wallet importé depuis sa seule clé privée (pas de phrase mnémonique) :
+/// le wallet est alors reconstruit depuis la ligne correspondante de `keys.hex`
+pub const NO_MNEMONIC_MARKER: &str = "-";
 
 /// Représente un wallet Ed25519 avec adresse Shelley Bech32
 #[derive(Clone)]
 pub struct Wallet {
-    signing_key: SigningKey,
+    signing_key: WalletKey,
     pub address: String,           // adresse Bech32 (mainnet ou testnet)
     pub mnemonic: Option<String>,  // seed phrase optionnelle pour régénération
     pub shelley_addr: String,      // adresse Shelley explicite (vide par défaut pour compatibilité)
@@ -29,45 +220,96 @@ impl Wallet {
         hex::encode(self.signing_key.to_bytes())
     }    
 
-    /// Génère un nouveau wallet Ed25519 aléatoire sans seed BIP39
-    pub fn generate(use_mainnet: bool) -> Self {
-        let mut rng = ChaCha20Rng::from_entropy();
-        let mut entropy = [0u8; 32];
+    /// Génère un nouveau wallet Ed25519 aléatoire (seed BIP-39 de `word_count` mots)
+    pub fn generate(use_mainnet: bool, word_count: MnemonicLength, address_type: AddressType) -> Self {
+        Wallet::generate_with_rng(&mut ChaCha20Rng::from_entropy(), use_mainnet, word_count, address_type)
+    }
+
+    /// Génère `count` wallets en parallèle sur les cœurs disponibles (Rayon).
+    /// Chaque tâche Rayon tire ses entropies d'un `ChaCha20Rng` propre, seedé par `OsRng`.
+    pub fn generate_many(
+        count: usize,
+        use_mainnet: bool,
+        word_count: MnemonicLength,
+        address_type: AddressType,
+    ) -> Vec<Wallet> {
+        (0..count)
+            .into_par_iter()
+            .map_init(
+                || ChaCha20Rng::from_rng(OsRng).expect("Erreur initialisation ChaCha20 depuis OsRng"),
+                |rng, _| Wallet::generate_with_rng(rng, use_mainnet, word_count, address_type),
+            )
+            .collect()
+    }
+
+    fn generate_with_rng(
+        rng: &mut ChaCha20Rng,
+        use_mainnet: bool,
+        word_count: MnemonicLength,
+        address_type: AddressType,
+    ) -> Self {
+        let mut entropy = vec![0u8; word_count.entropy_len()];
         rng.fill_bytes(&mut entropy);
 
         let mnemonic = Mnemonic::from_entropy_in(Language::English, &entropy)
             .expect("Erreur génération BIP-39");
+        entropy.zeroize();
 
-        let phrase = mnemonic.to_string();
-        let seed_full = mnemonic.to_seed("");
-        let seed_bytes = &seed_full[0..32];
-
-        let mut sk_bytes = [0u8; 32];
-        sk_bytes.copy_from_slice(seed_bytes);
-        let signing_key = SigningKey::from_bytes(&sk_bytes);
-        let pubkey_bytes = signing_key.verifying_key().to_bytes();
-        let addr = Wallet::derive_bech32_address(&pubkey_bytes, use_mainnet);
+        Wallet::from_phrase(&mnemonic.to_string(), use_mainnet, address_type)
+            .expect("Erreur dérivation du wallet")
+    }
 
-        sk_bytes.zeroize();
-        entropy.zeroize();
+    /// Reconstruit le wallet d'une phrase mnémonique avec le type d'adresse demandé
+    pub fn from_phrase(
+        phrase: &str,
+        use_mainnet: bool,
+        address_type: AddressType,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let signing_key = match address_type {
+            AddressType::KeyHash => {
+                let mnemonic = Mnemonic::parse_in_normalized(Language::English, phrase)?;
+                let seed_full = mnemonic.to_seed("");
+                let mut sk_bytes = [0u8; 32];
+                sk_bytes.copy_from_slice(&seed_full[..32]);
+                let signing_key = SigningKey::from_bytes(&sk_bytes);
+                sk_bytes.zeroize();
+                signing_key
+            }
+            AddressType::Enterprise => Self::legacy_keys_from_phrase(phrase)?.0,
+            AddressType::Base => return Self::generate_shelley_base_from_mnemonic_phrase(phrase, use_mainnet),
+            AddressType::Cip1852 { account, index } => {
+                return Self::generate_cip1852(phrase, account, index, use_mainnet)
+            }
+        };
 
-        Self {
-            signing_key,
-            address: addr,
-            mnemonic: Some(phrase),
+        let pubkey_bytes = signing_key.verifying_key().to_bytes();
+        Ok(Self {
+            signing_key: signing_key.into(),
+            address: Wallet::derive_bech32_address(&pubkey_bytes, use_mainnet),
+            mnemonic: Some(phrase.to_string()),
             shelley_addr: String::new(),
-        }
+        })
     }
 
-    /// Génère un wallet depuis une seed BIP-39 (24 mots)
+    /// Recalcule l'adresse (schéma de `generate` pour `address_type`) à partir d'une phrase mnémonique
+    pub fn address_from_phrase(
+        phrase: &str,
+        use_mainnet: bool,
+        address_type: AddressType,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Wallet::from_phrase(phrase, use_mainnet, address_type)?.address)
+    }
+
+    /// Génère un wallet depuis une seed BIP-39 (12 à 24 mots)
     pub fn generate_from_bip39(
         seed_path: impl AsRef<Path>,
         key_path: impl AsRef<Path>,
         use_mainnet: bool,
+        word_count: MnemonicLength,
     ) -> Self {
-        // Génération d'entropie sécurisée (32 octets pour 24 mots)
+        // Génération d'entropie sécurisée (16 octets pour 12 mots ... 32 octets pour 24 mots)
         let mut rng = ChaCha20Rng::from_entropy();
-        let mut entropy = [0u8; 32];
+        let mut entropy = vec![0u8; word_count.entropy_len()];
         rng.fill_bytes(&mut entropy);
 
         let mnemonic = Mnemonic::from_entropy_in(Language::English, &entropy)
@@ -93,7 +335,7 @@ impl Wallet {
 
         info!("🔐 Wallet généré depuis BIP-39 : {}", addr);
         Self {
-            signing_key,
+            signing_key: signing_key.into(),
             address: addr,
             mnemonic: Some(phrase),
             shelley_addr: String::new(),
@@ -101,35 +343,125 @@ impl Wallet {
     }
 
     /// Génère un wallet depuis une phrase mnémonique donnée (méthode Shelley explicite de type base)
+    ///
+    /// ⚠️ Dérivation historique propre à ce miner (clés tirées d'un ChaCha20 seedé par la seed BIP-39) :
+    /// l'adresse obtenue ne correspond PAS à celle d'un wallet Cardano standard (Eternl, Lace...).
+    /// Conservée pour les wallets déjà générés ; voir `generate_cip1852` pour la dérivation standard.
     pub fn generate_shelley_base_from_mnemonic_phrase(
         phrase: &str,
         use_mainnet: bool,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let (signing_key_pay, signing_key_stake) = Self::legacy_keys_from_phrase(phrase)?;
+        let pubkey_pay = signing_key_pay.verifying_key().to_bytes();
+        let pubkey_stake = signing_key_stake.verifying_key().to_bytes();
+
+        // Calcule les hash (blake2b‑224) des deux clés publiques
+        let mut hasher_pay = Blake2bVar::new(28)?;
+        hasher_pay.update(&pubkey_pay);
+        let mut payment_hash = vec![0u8; 28];
+        hasher_pay.finalize_variable(&mut payment_hash)?;
+
+        let mut hasher_stake = Blake2bVar::new(28)?;
+        hasher_stake.update(&pubkey_stake);
+        let mut stake_hash = vec![0u8; 28];
+        hasher_stake.finalize_variable(&mut stake_hash)?;
+
+        // Construis l’adresse base : header + payment_hash + stake_hash
+        let header: u8 = if use_mainnet {
+            // type = 0000 (base) + networkid = 1 → bits = 0b0000_0001 = 0x01
+            0b0000_0001
+        } else {
+            // networkid = 0
+            0b0000_0000
+        };
+        let mut addr_bytes = Vec::with_capacity(1 + payment_hash.len() + stake_hash.len());
+        addr_bytes.push(header);
+        addr_bytes.extend_from_slice(&payment_hash);
+        addr_bytes.extend_from_slice(&stake_hash);
+
+        let prefix = if use_mainnet { "addr" } else { "addr_test" };
+        let shelley_addr = bech32::encode(prefix, addr_bytes.to_base32(), Variant::Bech32)?;
+
+        info!(
+            "🔐 Wallet généré (Shelley base) depuis phrase mnémonique : {} (staking : {})",
+            &shelley_addr,
+            Wallet::encode_stake_address(&stake_hash, use_mainnet)?
+        );
+
+        // Nous gardons la clé de paiement comme signing_key principal
+        Ok(Self {
+            signing_key: signing_key_pay.into(),
+            address: shelley_addr.clone(),
+            mnemonic: Some(phrase.to_string()),
+            shelley_addr,
+        })
+    }
+
+    /// Clés (paiement, staking) de la dérivation historique : deux tirages successifs
+    /// d'un ChaCha20 seedé par les 32 premiers octets de la seed BIP-39.
+    fn legacy_keys_from_phrase(
+        phrase: &str,
+    ) -> Result<(SigningKey, SigningKey), Box<dyn std::error::Error + Send + Sync>> {
         // Parse la phrase mnémonique
         let mnemonic = Mnemonic::parse_in_normalized(Language::English, phrase)?;
         let seed_full = mnemonic.to_seed("");
-        let seed_bytes = &seed_full[0..32];
 
         // Initialise RNG depuis seed
         let mut rng_seed = [0u8; 32];
-        rng_seed.copy_from_slice(seed_bytes);
+        rng_seed.copy_from_slice(&seed_full[0..32]);
         let mut rng = ChaCha20Rng::from_seed(rng_seed);
+        rng_seed.zeroize();
 
         // Génère la clé de paiement
         let mut sk_bytes_pay = [0u8; 32];
         rng.fill_bytes(&mut sk_bytes_pay);
         let signing_key_pay = SigningKey::from_bytes(&sk_bytes_pay);
-        let pubkey_pay = signing_key_pay.verifying_key().to_bytes();
         sk_bytes_pay.zeroize();
 
         // Génère la clé de staking
         let mut sk_bytes_stake = [0u8; 32];
         rng.fill_bytes(&mut sk_bytes_stake);
         let signing_key_stake = SigningKey::from_bytes(&sk_bytes_stake);
-        let pubkey_stake = signing_key_stake.verifying_key().to_bytes();
         sk_bytes_stake.zeroize();
 
-        // Calcule les hash (blake2b‑224) des deux clés publiques
+        Ok((signing_key_pay, signing_key_stake))
+    }
+
+    /// Génère un wallet selon la dérivation standard CIP-1852 (Ed25519-BIP32, schéma Icarus) :
+    /// paiement `m/1852'/1815'/account'/0/index`, staking `m/1852'/1815'/account'/2/0`.
+    ///
+    /// L'adresse base produite est identique à celle affichée par Eternl / Lace pour la même seed
+    /// (vecteurs de la CIP-19, vérifiés dans tests/wallet_addresses.rs).
+    pub fn generate_cip1852(
+        phrase: &str,
+        account: u32,
+        index: u32,
+        use_mainnet: bool,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mnemonic = Mnemonic::parse_in_normalized(Language::English, phrase)?;
+
+        // Clé maître Icarus : PBKDF2-HMAC-SHA512(passphrase vide, sel = entropie, 4096 itérations)
+        let mut entropy = mnemonic.to_entropy();
+        let mut master_bytes = [0u8; XPRV_SIZE];
+        pbkdf2::pbkdf2_hmac::<Sha512>(b"", &entropy, 4096, &mut master_bytes);
+        let master = XPrv::normalize_bytes_force3rd(master_bytes);
+        master_bytes.zeroize();
+        entropy.zeroize();
+
+        let account_key = master
+            .derive(DerivationScheme::V2, 1852 | HARDENED)
+            .derive(DerivationScheme::V2, 1815 | HARDENED)
+            .derive(DerivationScheme::V2, account | HARDENED);
+        let payment_key = account_key
+            .derive(DerivationScheme::V2, 0)
+            .derive(DerivationScheme::V2, index);
+        let stake_key = account_key
+            .derive(DerivationScheme::V2, 2)
+            .derive(DerivationScheme::V2, 0);
+
+        let pubkey_pay = payment_key.public().public_key();
+        let pubkey_stake = stake_key.public().public_key();
+
         let mut hasher_pay = Blake2bVar::new(28)?;
         hasher_pay.update(&pubkey_pay);
         let mut payment_hash = vec![0u8; 28];
@@ -140,14 +472,8 @@ impl Wallet {
         let mut stake_hash = vec![0u8; 28];
         hasher_stake.finalize_variable(&mut stake_hash)?;
 
-        // Construis l’adresse base : header + payment_hash + stake_hash
-        let header: u8 = if use_mainnet {
-            // type = 0000 (base) + networkid = 1 → bits = 0b0000_0001 = 0x01
-            0b0000_0001
-        } else {
-            // networkid = 0
-            0b0000_0000
-        };
+        // Adresse base : header (type 0000 + network id) + payment_hash + stake_hash
+        let header: u8 = if use_mainnet { 0b0000_0001 } else { 0b0000_0000 };
         let mut addr_bytes = Vec::with_capacity(1 + payment_hash.len() + stake_hash.len());
         addr_bytes.push(header);
         addr_bytes.extend_from_slice(&payment_hash);
@@ -156,51 +482,198 @@ impl Wallet {
         let prefix = if use_mainnet { "addr" } else { "addr_test" };
         let shelley_addr = bech32::encode(prefix, addr_bytes.to_base32(), Variant::Bech32)?;
 
-        info!("🔐 Wallet généré (Shelley base) depuis phrase mnémonique : {}", &shelley_addr);
+        info!(
+            "🔐 Wallet généré (CIP-1852 {}/{}) depuis phrase mnémonique : {} (staking : {})",
+            account,
+            index,
+            &shelley_addr,
+            Wallet::encode_stake_address(&stake_hash, use_mainnet)?
+        );
 
-        // Nous gardons la clé de paiement comme signing_key principal
         Ok(Self {
-            signing_key: signing_key_pay,
+            signing_key: WalletKey::Extended(payment_key),
             address: shelley_addr.clone(),
             mnemonic: Some(phrase.to_string()),
             shelley_addr,
         })
     }
 
-    /// Charge un wallet depuis un fichier clé privée hex
+    /// Charge un wallet depuis un fichier clé privée hex ou Bech32 (`ed25519_sk1...`)
     pub fn load_from_file(
         key_path: impl AsRef<Path>,
         use_mainnet: bool,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let hex_str = fs::read_to_string(key_path)?;
-        let bytes = hex::decode(hex_str.trim())?;
+        Wallet::from_key_hex(&hex_str, use_mainnet)
+    }
+
+    /// Charge un wallet depuis un fichier clé privée chiffré par `save_encrypted`
+    pub fn load_encrypted(
+        key_path: impl AsRef<Path>,
+        passphrase: &str,
+        use_mainnet: bool,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let hex_str = read_secret_file(key_path, Some(passphrase))?;
+        Wallet::from_key_hex(&hex_str, use_mainnet)
+    }
+
+    /// Clé privée au format Bech32 de cardano-addresses / cardano-cli :
+    /// `ed25519_sk1...` (32 octets), ou `ed25519e_sk1...` (64 octets) pour une clé étendue CIP-1852
+    pub fn to_bech32_private_key(&self) -> String {
+        let hrp = match self.signing_key {
+            WalletKey::Ed25519(_) => "ed25519_sk",
+            WalletKey::Extended(_) => "ed25519e_sk",
+        };
+        let mut bytes = self.signing_key.to_bytes();
+        let encoded = bech32::encode(hrp, bytes.to_base32(), Variant::Bech32).expect("Erreur encodage Bech32");
+        bytes.zeroize();
+        encoded
+    }
+
+    /// Construit un wallet depuis une clé privée `ed25519_sk1...` (32 octets).
+    /// Les clés étendues `ed25519e_sk1...` ne sont pas importables (chain code absent).
+    pub fn from_bech32_private_key(bech: &str, use_mainnet: bool) -> Result<Wallet, WalletError> {
+        let (hrp, data, variant) =
+            bech32::decode(bech.trim()).map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))?;
+        if hrp != "ed25519_sk" {
+            return Err(WalletError::InvalidPrivateKey(format!("préfixe {:?}, ed25519_sk attendu", hrp)));
+        }
+        if variant != Variant::Bech32 {
+            return Err(WalletError::InvalidPrivateKey("variante Bech32m inattendue".to_string()));
+        }
+        let mut bytes: Vec<u8> = bech32::FromBase32::from_base32(&data)
+            .map_err(|e: bech32::Error| WalletError::InvalidPrivateKey(e.to_string()))?;
         if bytes.len() != 32 {
-            return Err("La clé privée doit faire 32 octets".into());
+            let len = bytes.len();
+            bytes.zeroize();
+            return Err(WalletError::InvalidPrivateKey(format!("{} octets, 32 attendus", len)));
         }
         let mut key_bytes = [0u8; 32];
         key_bytes.copy_from_slice(&bytes);
-        let signing_key = SigningKey::from_bytes(&key_bytes);
-        let pubkey_bytes = signing_key.verifying_key().to_bytes();
-        let addr = Wallet::derive_bech32_address(&pubkey_bytes, use_mainnet);
+        bytes.zeroize();
+        let wallet = Wallet::from_signing_key_bytes(&key_bytes, use_mainnet);
         key_bytes.zeroize();
+        Ok(wallet)
+    }
 
-        Ok(Self {
-            signing_key,
-            address: addr,
+    /// Importe une clé secrète ed25519 brute en hex (32 octets, préfixe `0x` toléré)
+    pub fn from_secret_hex(hex_str: &str, use_mainnet: bool) -> Result<Wallet, WalletError> {
+        let hex_str = hex_str.trim();
+        let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
+        let mut bytes = hex::decode(hex_str).map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))?;
+        Wallet::from_secret_bytes(&mut bytes, use_mainnet)
+    }
+
+    /// Importe un fichier `payment.skey` de `cardano-cli` (enveloppe JSON avec `cborHex`).
+    /// Seules les clés ed25519 simples (`PaymentSigningKeyShelley_ed25519`) sont acceptées ;
+    /// les clés étendues BIP32 sont refusées.
+    pub fn from_cardano_skey(json: &str, use_mainnet: bool) -> Result<Wallet, WalletError> {
+        #[derive(serde::Deserialize)]
+        struct TextEnvelope {
+            #[serde(rename = "type")]
+            key_type: String,
+            #[serde(rename = "cborHex")]
+            cbor_hex: String,
+        }
+
+        let envelope: TextEnvelope =
+            serde_json::from_str(json).map_err(|e| WalletError::InvalidPrivateKey(format!("skey illisible : {}", e)))?;
+        if envelope.key_type.contains("Extended") {
+            return Err(WalletError::InvalidPrivateKey(format!(
+                "clé étendue ({}) non supportée, clé ed25519 simple attendue",
+                envelope.key_type
+            )));
+        }
+        if !envelope.key_type.ends_with("SigningKeyShelley_ed25519") {
+            return Err(WalletError::InvalidPrivateKey(format!("type de clé {:?} inattendu", envelope.key_type)));
+        }
+
+        let cbor = hex::decode(envelope.cbor_hex.trim()).map_err(|e| WalletError::InvalidPrivateKey(e.to_string()))?;
+        // `cborHex` encode une chaîne d'octets CBOR (`5820` + 32 octets)
+        let mut bytes = match ciborium::de::from_reader::<Value, _>(cbor.as_slice()) {
+            Ok(Value::Bytes(bytes)) => bytes,
+            Ok(_) => return Err(WalletError::InvalidPrivateKey("cborHex n'est pas une chaîne d'octets".to_string())),
+            Err(e) => return Err(WalletError::InvalidPrivateKey(format!("cborHex illisible : {}", e))),
+        };
+        Wallet::from_secret_bytes(&mut bytes, use_mainnet)
+    }
+
+    /// Valide la longueur (32 octets) puis efface `bytes`
+    fn from_secret_bytes(bytes: &mut Vec<u8>, use_mainnet: bool) -> Result<Wallet, WalletError> {
+        if bytes.len() != 32 {
+            let len = bytes.len();
+            bytes.zeroize();
+            return Err(WalletError::InvalidPrivateKey(format!("{} octets, 32 attendus", len)));
+        }
+        let mut key_bytes = [0u8; 32];
+        key_bytes.copy_from_slice(bytes);
+        bytes.zeroize();
+        let wallet = Wallet::from_signing_key_bytes(&key_bytes, use_mainnet);
+        key_bytes.zeroize();
+        Ok(wallet)
+    }
+
+    /// Wallet (adresse entreprise) d'une clé privée ed25519 brute
+    fn from_signing_key_bytes(key_bytes: &[u8; 32], use_mainnet: bool) -> Self {
+        let signing_key = SigningKey::from_bytes(key_bytes);
+        let pubkey_bytes = signing_key.verifying_key().to_bytes();
+        Self {
+            signing_key: signing_key.into(),
+            address: Wallet::derive_bech32_address(&pubkey_bytes, use_mainnet),
             mnemonic: None,
             shelley_addr: String::new(),
-        })
+        }
+    }
+
+    /// Construit un wallet depuis une clé privée ed25519 hex (32 octets) ou Bech32 (`ed25519_sk1...`)
+    fn from_key_hex(
+        hex_str: &str,
+        use_mainnet: bool,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if hex_str.trim().starts_with("ed25519_sk1") {
+            return Ok(Wallet::from_bech32_private_key(hex_str, use_mainnet)?);
+        }
+        let bytes = hex::decode(hex_str.trim())?;
+        if bytes.len() != 32 {
+            return Err("La clé privée doit faire 32 octets".into());
+        }
+        let mut key_bytes = [0u8; 32];
+        key_bytes.copy_from_slice(&bytes);
+        let wallet = Wallet::from_signing_key_bytes(&key_bytes, use_mainnet);
+        key_bytes.zeroize();
+
+        Ok(wallet)
+    }
+
+    /// Adresse entreprise de la clé d'une ligne de `keys.hex` : clé ed25519 (32 octets, hex ou Bech32)
+    /// ou clé étendue CIP-1852 (64 octets kL || kR, sans chain code : seule la clé publique en est tirée)
+    fn stored_key_address(
+        key_hex: &str,
+        use_mainnet: bool,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let trimmed = key_hex.trim();
+        if trimmed.len() != 128 {
+            return Ok(Wallet::from_key_hex(trimmed, use_mainnet)?.address);
+        }
+        let mut bytes = hex::decode(trimmed)?;
+        let mut extended = [0u8; 64];
+        extended.copy_from_slice(&bytes);
+        bytes.zeroize();
+        // La clé publique ne dépend pas du chain code
+        let xprv = XPrv::from_extended_and_chaincode(&extended, &[0u8; 32]);
+        extended.zeroize();
+        Ok(Wallet::derive_bech32_address(&xprv.public().public_key(), use_mainnet))
     }
 
     /// Retourne la clé publique au format hex
     pub fn public_key_hex(&self) -> String {
-        hex::encode(self.signing_key.verifying_key().to_bytes())
+        hex::encode(self.signing_key.public_key_bytes())
     }
 
     /// Signe un message arbitraire
     pub fn sign(&self, message: &str) -> String {
         let sig = self.signing_key.sign(message.as_bytes());
-        hex::encode(sig.to_bytes())
+        hex::encode(sig)
     }
 
 
@@ -213,7 +686,7 @@ impl Wallet {
             ( Value::Integer(Integer::from(1i64)),     // header key “alg”
               Value::Integer(Integer::from(-8i64)) ),  // EdDSA
             ( Value::Text("address".into()),
-              Value::Bytes(self.address_bytes()) ),     // adresse en bytes
+              Value::Bytes(self.address_bytes()?) ),    // adresse en bytes
         ]);
         let protected_bytes = to_vec(&protected_map)?;                // CBOR serialize
         let protected_bstr = Value::Bytes(protected_bytes.clone());
@@ -234,8 +707,7 @@ impl Wallet {
         let sig_structure_bytes = to_vec(&sig_structure)?;
 
         // 5. Sign the sig_structure_bytes
-        let sig = self.signing_key.sign(&sig_structure_bytes);
-        let sig_bytes = sig.to_bytes().to_vec();
+        let sig_bytes = self.signing_key.sign(&sig_structure_bytes);
 
         // 6. Build COSE_Sign1 = [ protected_bstr, unprotected_map, payload (bstr), signature (bstr) ]
         let cose_sign1 = Value::Array(vec![
@@ -272,17 +744,161 @@ impl Wallet {
             Value::Bytes(protected),
             Value::Map(vec![]),
             Value::Bytes(message.as_bytes().to_vec()),
-            Value::Bytes(sig.to_bytes().to_vec()),
+            Value::Bytes(sig),
         ]))
         .unwrap();
 
         hex::encode(cose)
     }
 
+    /// Vérifie une signature COSE_Sign1 produite par `sign_cip30` / `sign_cip8`.
+    ///
+    /// Une adresse ne contient que le hash de la clé publique : `pubkey_hex` est donc requis,
+    /// et doit correspondre au credential de paiement de `address`. Retourne `Ok(false)` si la
+    /// clé, l'adresse du header protégé ou la signature ne correspondent pas.
+    pub fn verify_cip30(
+        address: &str,
+        pubkey_hex: &str,
+        cose_hex: &str,
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let cose_bytes = hex::decode(cose_hex)?;
+        let cose: Value = from_slice(&cose_bytes)?;
+        let items = match cose {
+            Value::Array(items) if items.len() == 4 => items,
+            _ => return Err("COSE_Sign1 invalide : tableau de 4 éléments attendu".into()),
+        };
+        let (protected, payload, sig_bytes) = match (&items[0], &items[2], &items[3]) {
+            (Value::Bytes(p), Value::Bytes(m), Value::Bytes(s)) => (p.clone(), m.clone(), s.clone()),
+            _ => return Err("COSE_Sign1 invalide : protected, payload et signature doivent être des bstr".into()),
+        };
+
+        let (_hrp, data, _variant) = bech32::decode(address)?;
+        let addr_bytes: Vec<u8> = bech32::FromBase32::from_base32(&data)?;
+        if addr_bytes.len() < 29 {
+            return Err("Adresse trop courte pour contenir un credential de paiement".into());
+        }
+
+        // Si le header protégé porte une adresse (CIP-8), elle doit être celle attendue
+        let protected_map: Value = from_slice(&protected)?;
+        if let Value::Map(entries) = &protected_map {
+            for (k, v) in entries {
+                if let (Value::Text(key), Value::Bytes(header_addr)) = (k, v) {
+                    if key == "address" && header_addr != &addr_bytes {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+
+        // La clé publique doit correspondre au credential de paiement de l'adresse
+        let pubkey_bytes: [u8; 32] = hex::decode(pubkey_hex)?
+            .try_into()
+            .map_err(|_| "La clé publique doit faire 32 octets")?;
+        let mut hasher = Blake2bVar::new(28)?;
+        hasher.update(&pubkey_bytes);
+        let mut key_hash = vec![0u8; 28];
+        hasher.finalize_variable(&mut key_hash)?;
+        if key_hash[..] != addr_bytes[1..29] {
+            return Ok(false);
+        }
+
+        // Reconstruit le Sig_structure et vérifie la signature
+        let sig_structure = to_vec(&Value::Array(vec![
+            Value::Text("Signature1".into()),
+            Value::Bytes(protected),
+            Value::Bytes(Vec::new()), // external_aad
+            Value::Bytes(payload),
+        ]))?;
+        let verifying_key = VerifyingKey::from_bytes(&pubkey_bytes)?;
+        let signature = Signature::from_slice(&sig_bytes)?;
+
+        Ok(verifying_key.verify(&sig_structure, &signature).is_ok())
+    }
+
+    /// Adresse de récompense (`stake1...` / `stake_test1...`) de la partie staking de `shelley_addr`,
+    /// quelle que soit la dérivation (historique ou CIP-1852), à enregistrer dans les outils Cardano
+    /// pour déléguer ou consulter les récompenses.
+    /// `None` pour un wallet sans adresse base (importé depuis sa seule clé privée).
+    pub fn stake_address(&self) -> Option<String> {
+        let (stake_hash, mainnet) = self.stake_key_hash()?;
+        Wallet::encode_stake_address(&stake_hash, mainnet).ok()
+    }
+
+    /// `stake_address` encodée pour le réseau demandé (`stake1...` ou `stake_test1...`), quel que soit
+    /// celui de `shelley_addr`. Chaîne vide pour un wallet sans adresse base.
+    pub fn derive_stake_address(&self, use_mainnet: bool) -> String {
+        self.stake_key_hash()
+            .and_then(|(stake_hash, _)| Wallet::encode_stake_address(&stake_hash, use_mainnet).ok())
+            .unwrap_or_default()
+    }
+
+    /// Hash Blake2b-224 de la clé de staking de `shelley_addr` et réseau de l'adresse (`true` = mainnet)
+    fn stake_key_hash(&self) -> Option<(Vec<u8>, bool)> {
+        let (_hrp, data, _variant) = bech32::decode(&self.shelley_addr).ok()?;
+        let bytes: Vec<u8> = bech32::FromBase32::from_base32(&data).ok()?;
+        // Adresse base (types 0 à 3, credential de staking = hash de clé pour les types 0 et 2) :
+        // header + payment (28 octets) + stake (28 octets)
+        let header = *bytes.first()?;
+        if bytes.len() != 57 || !matches!(header >> 4, 0 | 2) {
+            return None;
+        }
+        Some((bytes[29..].to_vec(), header & 0x0f == 1))
+    }
+
+    /// Encode une adresse de récompense : header `0b1110` (reward, hash de clé) + network id, puis le hash
+    fn encode_stake_address(stake_hash: &[u8], use_mainnet: bool) -> Result<String, bech32::Error> {
+        let header: u8 = if use_mainnet { 0xe1 } else { 0xe0 };
+        let mut addr_bytes = Vec::with_capacity(1 + stake_hash.len());
+        addr_bytes.push(header);
+        addr_bytes.extend_from_slice(stake_hash);
+
+        let prefix = if use_mainnet { "stake" } else { "stake_test" };
+        bech32::encode(prefix, addr_bytes.to_base32(), Variant::Bech32)
+    }
+
+    /// Vérifie qu'une adresse de paiement Shelley est bien formée pour le réseau attendu :
+    /// Bech32 valide, HRP `addr` (mainnet) ou `addr_test` (testnet), type d'adresse de paiement
+    /// (0 à 7) avec la longueur correspondante, et network id du header (1 mainnet, 0 testnet).
+    pub fn is_valid_bech32_address(addr: &str, expect_mainnet: bool) -> bool {
+        let (hrp, data, variant) = match bech32::decode(addr.trim()) {
+            Ok(decoded) => decoded,
+            Err(_) => return false,
+        };
+        let expected_hrp = if expect_mainnet { "addr" } else { "addr_test" };
+        if variant != Variant::Bech32 || hrp != expected_hrp {
+            return false;
+        }
+        let bytes: Vec<u8> = match bech32::FromBase32::from_base32(&data) {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+        let Some(&header) = bytes.first() else {
+            return false;
+        };
+
+        let network_id = header & 0x0f;
+        let length_ok = match header >> 4 {
+            // base : payment + stake (28 + 28 octets)
+            0..=3 => bytes.len() == 57,
+            // pointeur : payment + pointeur à longueur variable
+            4 | 5 => bytes.len() > 29,
+            // enterprise : payment seul
+            6 | 7 => bytes.len() == 29,
+            _ => false,
+        };
+        length_ok && network_id == u8::from(expect_mainnet)
+    }
+
     /// Décode l’adresse Bech32 en bytes
-    pub fn address_bytes(&self) -> Vec<u8> {
-        let (_hrp, data, _variant) = bech32::decode(&self.address).expect("Erreur décodage Bech32");
-        bech32::FromBase32::from_base32(&data).expect("Erreur conversion from base32")
+    pub fn address_bytes(&self) -> Result<Vec<u8>, AddressError> {
+        let (_hrp, data, _variant) = bech32::decode(&self.address).map_err(|e| AddressError::Bech32 {
+            address: self.address.clone(),
+            reason: e.to_string(),
+        })?;
+        bech32::FromBase32::from_base32(&data).map_err(|e: bech32::Error| AddressError::Payload {
+            address: self.address.clone(),
+            reason: e.to_string(),
+        })
     }
 
     /// Sauvegarde une seule clé privée dans un fichier (hex)
@@ -292,6 +908,15 @@ impl Wallet {
         Ok(())
     }
 
+    /// Sauvegarde la clé privée chiffrée par passphrase (Argon2id + XChaCha20-Poly1305)
+    pub fn save_encrypted(
+        &self,
+        path: impl AsRef<Path>,
+        passphrase: &str,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        write_secret_file(path, &self.signing_key_hex(), Some(passphrase))
+    }
+
     /// Dérive une adresse Shelley Bech32 à partir de pubkey
     fn derive_bech32_address(pubkey: &[u8], use_mainnet: bool) -> String {
         let mut hasher = Blake2bVar::new(28).expect("Erreur Blake2bVar");
@@ -340,34 +965,183 @@ impl Wallet {
         Ok(())
     }
 
+    /// Charge les wallets depuis seeds/keys ; les fichiers chiffrés exigent `passphrase`
     pub fn load_many_from_files(
         seed_path: &Path,
         key_path: &Path,
+        passphrase: Option<&str>,
         use_mainnet: bool,
+        address_type: AddressType,
     ) -> Result<Vec<Wallet>, Box<dyn std::error::Error + Send + Sync>> {
-        let seeds_str = fs::read_to_string(seed_path)?;
-        let keys_str = fs::read_to_string(key_path)?;
-        let seed_lines: Vec<_> = seeds_str.lines().collect();
-        let key_lines: Vec<_> = keys_str.lines().collect();
+        let seeds_str = read_secret_file(seed_path, passphrase)?;
+        let keys_str = read_secret_file(key_path, passphrase)?;
+        Self::parse_many(&seeds_str, &keys_str, seed_path, key_path, use_mainnet, address_type)
+    }
+
+    /// Reconstruit les wallets depuis le contenu de `seeds.txt` / `keys.hex` (une entrée par ligne).
+    /// `seed_path` et `key_path` ne servent qu'aux messages d'erreur.
+    ///
+    /// `address_type` doit être celui de la génération : `keys.hex` contient la clé de ce type,
+    /// une autre dérivation échoue au contrôle clé/seed (sauf `Enterprise` ↔ `Base`, même clé).
+    /// Une clé étendue CIP-1852 est re-dérivée depuis la phrase ; la clé stockée sert au contrôle.
+    /// Un wallet sans phrase mnémonique n'a pas de clé de staking et reste en adresse entreprise.
+    pub fn parse_many(
+        seeds_str: &str,
+        keys_str: &str,
+        seed_path: &Path,
+        key_path: &Path,
+        use_mainnet: bool,
+        address_type: AddressType,
+    ) -> Result<Vec<Wallet>, Box<dyn std::error::Error + Send + Sync>> {
+        let seed_lines: Vec<_> = seeds_str.lines().filter(|l| !l.trim().is_empty()).collect();
+        let key_lines: Vec<_> = keys_str.lines().filter(|l| !l.trim().is_empty()).collect();
+
+        // Un écart (écriture interrompue, fichier édité à la main...) ferait perdre des wallets au zip
+        if seed_lines.len() != key_lines.len() {
+            return Err(format!(
+                "{:?} contient {} seeds mais {:?} contient {} clés : fichiers désynchronisés",
+                seed_path,
+                seed_lines.len(),
+                key_path,
+                key_lines.len()
+            )
+            .into());
+        }
+
+        // WALLET_SKIP_KEY_VERIFY=true : une clé incohérente est seulement signalée (migration)
+        let skip_key_verify = std::env::var("WALLET_SKIP_KEY_VERIFY")
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
 
         let mut wallets = Vec::new();
-        for (seed_phrase, _key_hex) in seed_lines.iter().zip(key_lines.iter()) {
-            let wallet = Wallet::generate_shelley_base_from_mnemonic_phrase(seed_phrase, use_mainnet)?;
-            let mut sk_bytes = [0u8; 32];
-            let mnemonic = Mnemonic::parse_in_normalized(Language::English, seed_phrase)?;            
-            let seed_full = mnemonic.to_seed("");
-            sk_bytes.copy_from_slice(&seed_full[..32]);            
-            let signing_key = SigningKey::from_bytes(&sk_bytes);
-            let pubkey_bytes = signing_key.verifying_key().to_bytes();
-            let addr = Wallet::derive_bech32_address(&pubkey_bytes, use_mainnet);
-            wallets.push(Wallet {
-                signing_key: signing_key,
-                address: addr,
-                mnemonic: Some(seed_phrase.to_string()),
-                shelley_addr: wallet.shelley_addr.clone(),
-            });
+        for (line, (seed_phrase, key_hex)) in seed_lines.iter().zip(key_lines.iter()).enumerate() {
+            // Wallet importé sans phrase mnémonique : la clé fait foi
+            if seed_phrase.trim() == NO_MNEMONIC_MARKER {
+                let wallet = Wallet::from_key_hex(key_hex, use_mainnet)
+                    .map_err(|e| format!("{:?} ligne {} : {}", key_path, line + 1, e))?;
+                wallets.push(wallet);
+                continue;
+            }
+            let word_count = seed_phrase.split_whitespace().count();
+            MnemonicLength::from_word_count(word_count)
+                .map_err(|e| format!("{:?} ligne {} : {}", seed_path, line + 1, e))?;
+            let mut wallet = Wallet::from_phrase(seed_phrase, use_mainnet, address_type)?;
+            if address_type == AddressType::KeyHash {
+                // L'adresse base de la phrase reste exposée pour sa partie staking
+                wallet.shelley_addr =
+                    Wallet::generate_shelley_base_from_mnemonic_phrase(seed_phrase, use_mainnet)?.shelley_addr;
+            }
+            // Adresse entreprise de la clé du wallet, comparable à celle de la clé stockée quel que soit le type
+            let addr = Wallet::derive_bech32_address(&wallet.signing_key.public_key_bytes(), use_mainnet);
+
+            // La clé stockée doit désigner le même wallet que la seed
+            let key_addr = Wallet::stored_key_address(key_hex, use_mainnet)
+                .unwrap_or_else(|e| format!("<clé invalide: {}>", e));
+            if key_addr != addr {
+                let mismatch = WalletError::KeySeedMismatch { line: line + 1, seed_addr: addr.clone(), key_addr };
+                if !skip_key_verify {
+                    return Err(Box::new(mismatch));
+                }
+                warn!("⚠️ {:?} {} (ignoré : WALLET_SKIP_KEY_VERIFY), clé re-dérivée utilisée", key_path, mismatch);
+            }
+            wallets.push(wallet);
         }
 
         Ok(wallets)
     }
 }
+
+// === Chiffrement des fichiers de secrets ===
+
+fn derive_file_key(
+    passphrase: &str,
+    salt: &[u8],
+) -> Result<[u8; 32], Box<dyn std::error::Error + Send + Sync>> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Erreur dérivation Argon2 : {}", e))?;
+    Ok(key)
+}
+
+/// Indique si des données ont été produites par `encrypt_with_passphrase`
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(ENCRYPTED_MAGIC)
+}
+
+/// Chiffre `plaintext` avec une clé dérivée de `passphrase` (Argon2id + XChaCha20-Poly1305)
+pub fn encrypt_with_passphrase(
+    plaintext: &[u8],
+    passphrase: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut rng = ChaCha20Rng::from_entropy();
+    let mut salt = [0u8; SALT_LEN];
+    rng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill_bytes(&mut nonce);
+
+    let mut key = derive_file_key(passphrase, &salt)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    key.zeroize();
+
+    let ciphertext = cipher
+        .encrypt(XNonce::from_slice(&nonce), plaintext)
+        .map_err(|_| "Erreur chiffrement XChaCha20-Poly1305")?;
+
+    let mut out = Vec::with_capacity(ENCRYPTED_MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(ENCRYPTED_MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Déchiffre des données produites par `encrypt_with_passphrase`
+pub fn decrypt_with_passphrase(
+    data: &[u8],
+    passphrase: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    let header_len = ENCRYPTED_MAGIC.len() + SALT_LEN + NONCE_LEN;
+    if !is_encrypted(data) || data.len() < header_len {
+        return Err("Fichier chiffré invalide".into());
+    }
+    let salt = &data[ENCRYPTED_MAGIC.len()..ENCRYPTED_MAGIC.len() + SALT_LEN];
+    let nonce = &data[ENCRYPTED_MAGIC.len() + SALT_LEN..header_len];
+
+    let mut key = derive_file_key(passphrase, salt)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    key.zeroize();
+
+    let plaintext = cipher
+        .decrypt(XNonce::from_slice(nonce), &data[header_len..])
+        .map_err(|_| "Déchiffrement impossible (passphrase incorrecte ou fichier corrompu)")?;
+    Ok(plaintext)
+}
+
+/// Lit un fichier de secrets, chiffré ou en clair (rétrocompatibilité)
+pub fn read_secret_file(
+    path: impl AsRef<Path>,
+    passphrase: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let data = fs::read(path)?;
+    let plaintext = if is_encrypted(&data) {
+        let passphrase = passphrase.ok_or("Fichier chiffré mais aucune passphrase fournie (WALLET_PASSPHRASE)")?;
+        decrypt_with_passphrase(&data, passphrase)?
+    } else {
+        data
+    };
+    Ok(String::from_utf8(plaintext)?)
+}
+
+/// Écrit un fichier de secrets, chiffré si une passphrase est fournie
+pub fn write_secret_file(
+    path: impl AsRef<Path>,
+    contents: &str,
+    passphrase: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    match passphrase {
+        Some(passphrase) => fs::write(path, encrypt_with_passphrase(contents.as_bytes(), passphrase)?)?,
+        None => fs::write(path, contents)?,
+    }
+    Ok(())
+}
@@ -1,43 +1,271 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{Read, Write};
+use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
 use std::fs::{self, OpenOptions};
 use std::time::{Duration, Instant};
 use std::thread::sleep;
 
-use parking_lot::RwLock;
+use fs2::FileExt;
+use parking_lot::{RwLock, RwLockWriteGuard};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use std::sync::Arc;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use zip::{write::SimpleFileOptions, AesMode, CompressionMethod, ZipArchive, ZipWriter};
 
-use crate::wallet::Wallet;
+use crate::wallet::{write_secret_file, AddressType, MnemonicLength, Wallet, WalletError, NO_MNEMONIC_MARKER};
+
+/// Passphrase de chiffrement des fichiers seeds/keys (`WALLET_PASSPHRASE`), si définie
+pub fn wallet_passphrase() -> Option<String> {
+    std::env::var("WALLET_PASSPHRASE").ok().filter(|p| !p.is_empty())
+}
+
+/// Type d'adresse des wallets (`WALLET_ADDRESS_TYPE` : `key_hash`, `enterprise`, `base` ou `cip1852`), `key_hash` par défaut
+pub fn wallet_address_type() -> AddressType {
+    match std::env::var("WALLET_ADDRESS_TYPE") {
+        Ok(v) if !v.trim().is_empty() => v.parse().unwrap_or_else(|e| {
+            log::warn!("⚠️ WALLET_ADDRESS_TYPE ignoré : {}", e);
+            AddressType::default()
+        }),
+        _ => AddressType::default(),
+    }
+}
+
+/// Incohérence détectée entre les seeds stockées et les wallets chargés
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// L'adresse re-dérivée depuis la seed diffère de l'adresse stockée
+    AddressMismatch { index: usize, stored: String, derived: String },
+}
+
+impl fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityError::AddressMismatch { index, stored, derived } => write!(
+                f,
+                "wallet #{}: adresse stockée {} != adresse dérivée {}",
+                index, stored, derived
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {}
+
+/// Verrou exclusif OS sur un fichier `.lock`, relâché automatiquement au `Drop`
+/// (retour anticipé sur erreur compris).
+///
+/// Le fichier n'est pas supprimé : un autre process peut déjà attendre le verrou sur ce
+/// même inode, et le recréer ouvrirait une fenêtre où deux process se croient seuls.
+/// Un fichier `.lock` restant sur disque ne bloque rien, seul le verrou OS compte.
+pub struct FileLock {
+    file: fs::File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// Tente d'obtenir le verrou pendant `timeout` (nouvel essai toutes les 100 ms)
+    pub fn acquire(path: &Path, timeout: Duration) -> std::io::Result<Self> {
+        let file = OpenOptions::new().write(true).create(true).open(path)?;
+        let start = Instant::now();
+        while let Err(e) = file.try_lock_exclusive() {
+            if start.elapsed() >= timeout {
+                return Err(std::io::Error::new(
+                    e.kind(),
+                    format!("impossible d'obtenir le lock pour {:?}: {}", path, e),
+                ));
+            }
+            sleep(Duration::from_millis(100));
+        }
+        Ok(Self { file, path: path.to_path_buf() })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if let Err(e) = self.file.unlock() {
+            log::warn!("⚠️ Impossible de relâcher le lock {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// Registre global des adresses déjà générées, partagé par toutes les instances d'un même
+/// `config_root` : une ligne `<adresse> <fichier de seeds propriétaire>` par wallet.
+///
+/// Lu et réécrit (tmp + rename) sous `FileLock`, pour que deux instances qui démarrent
+/// en même temps ne réservent pas la même adresse.
+pub struct AddressRegistry {
+    path: PathBuf,
+}
+
+impl AddressRegistry {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self { path: path.as_ref().to_path_buf() }
+    }
+
+    /// `adresse -> propriétaire` ; un registre absent est vide
+    fn read(&self) -> std::io::Result<HashMap<String, String>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(contents
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(2, ' ');
+                let address = parts.next().filter(|a| !a.is_empty())?;
+                Some((address.to_string(), parts.next().unwrap_or("").to_string()))
+            })
+            .collect())
+    }
+
+    /// Enregistre les wallets de `owner`. Ceux d'indice `>= fresh_from` (tout juste générés)
+    /// dont l'adresse est déjà connue sont remplacés par `regenerate()` ; une adresse chargée
+    /// déjà attribuée à un autre propriétaire (dossier copié...) est seulement signalée.
+    pub fn claim(
+        &self,
+        owner: &Path,
+        wallets: &mut [Wallet],
+        fresh_from: usize,
+        mut regenerate: impl FnMut() -> Wallet,
+    ) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let _lock = FileLock::acquire(&self.path.with_extension("lock"), Duration::from_secs(5))?;
+        let mut known = self.read()?;
+        let owner = owner.display().to_string();
+
+        let mut added = false;
+        for (i, wallet) in wallets.iter_mut().enumerate() {
+            if i >= fresh_from {
+                while known.contains_key(&wallet.address) {
+                    log::warn!("⚠️ AddressRegistry: adresse {} déjà générée, régénération", wallet.address);
+                    *wallet = regenerate();
+                }
+            } else if let Some(other) = known.get(&wallet.address).filter(|o| **o != owner) {
+                log::warn!(
+                    "⚠️ AddressRegistry: wallet {} déjà utilisé par {} (dossier copié ?)",
+                    wallet.address,
+                    other
+                );
+                continue;
+            }
+            if !known.contains_key(&wallet.address) {
+                known.insert(wallet.address.clone(), owner.clone());
+                added = true;
+            }
+        }
+        if !added {
+            return Ok(());
+        }
+
+        let mut lines: Vec<String> = known.iter().map(|(a, o)| format!("{} {}", a, o)).collect();
+        lines.sort();
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, lines.join("\n") + "\n")?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+/// Accès en écriture à la liste des wallets ; l'index par adresse du container
+/// est reconstruit au `Drop`, avant la libération du verrou.
+pub struct WalletsWriteGuard<'a> {
+    wallets: RwLockWriteGuard<'a, Vec<Wallet>>,
+    index: &'a RwLock<HashMap<String, usize>>,
+}
+
+impl Deref for WalletsWriteGuard<'_> {
+    type Target = Vec<Wallet>;
+
+    fn deref(&self) -> &Vec<Wallet> {
+        &self.wallets
+    }
+}
+
+impl DerefMut for WalletsWriteGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<Wallet> {
+        &mut self.wallets
+    }
+}
+
+impl Drop for WalletsWriteGuard<'_> {
+    fn drop(&mut self) {
+        *self.index.write() = build_address_index(&self.wallets);
+    }
+}
+
+/// Index `adresse -> position` ; en cas de doublon, la première occurrence l'emporte
+fn build_address_index(wallets: &[Wallet]) -> HashMap<String, usize> {
+    let mut index = HashMap::with_capacity(wallets.len());
+    for (idx, wallet) in wallets.iter().enumerate() {
+        index.entry(wallet.address.clone()).or_insert(idx);
+    }
+    index
+}
+
+/// Notice jointe aux sauvegardes chiffrées (`WalletContainer::export_to_encrypted_zip`)
+const BACKUP_README: &str = "\
+Sauvegarde de wallets Scavenger Miner
+=====================================
+
+Archive zip chiffrée en AES-256 (mot de passe choisi à l'export).
+
+- seeds.txt : une phrase mnémonique BIP39 (anglais) par ligne, ou \"-\" pour un wallet
+              importé depuis sa seule clé privée
+- keys.hex  : la clé privée ed25519 (hex) correspondante, même ligne que sa seed
+
+Restauration : WalletContainer::import_from_encrypted_zip, ou copier les deux fichiers
+dans le dossier wallets/ de l'instance (ils sont alors en clair : à chiffrer avec
+WALLET_PASSPHRASE).
+";
+
+/// Nom des dossiers de snapshot : horodatage ISO 8601 (format basique, sans `:`) en UTC
+const SNAPSHOT_DIR_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
 
 /// Container thread-safe pour gérer plusieurs wallets par instance.
 pub struct WalletContainer {
     wallets: Arc<RwLock<Vec<Wallet>>>,
+    /// Position de chaque wallet par adresse, toujours modifiée sous le write lock de `wallets`
+    address_index: RwLock<HashMap<String, usize>>,
     seeds_path: PathBuf,
     keys_path: PathBuf,
     use_mainnet: bool,
+    address_type: AddressType,
 }
 
 impl WalletContainer {
+    /// Encapsule des wallets déjà chargés, sans rien écrire sur disque.
+    /// `save` / `push_and_save` persisteront ensuite vers `seeds_path` et `keys_path`.
     pub fn new(
         wallets: Vec<Wallet>,
         seeds_path: PathBuf,
         keys_path: PathBuf,
         use_mainnet: bool,
+        address_type: AddressType,
     ) -> Self {
         WalletContainer {
+            address_index: RwLock::new(build_address_index(&wallets)),
             wallets: Arc::new(RwLock::new(wallets)),
             seeds_path,
             keys_path,
             use_mainnet,
+            address_type,
         }
-    }    
+    }
+
     /// Charge si possible depuis les fichiers ; sinon génère uniquement les manquants.
+    /// Le type d'adresse (`WALLET_ADDRESS_TYPE`) vaut pour le chargement comme pour la génération.
+    /// Avec un `registry`, les nouveaux wallets en collision sont régénérés puis enregistrés.
     pub fn load_or_create<P: AsRef<Path>>(
         seeds_path: P,
         keys_path: P,
         use_mainnet: bool,
         max_wallets: usize,
+        registry: Option<&AddressRegistry>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let seeds_path = seeds_path.as_ref().to_path_buf();
         let keys_path = keys_path.as_ref().to_path_buf();
@@ -46,15 +274,20 @@ impl WalletContainer {
         if let Some(parent) = keys_path.parent() { fs::create_dir_all(parent)?; }
 
         let mut wallets: Vec<Wallet> = Vec::new();
+        let passphrase = wallet_passphrase();
+        let address_type = wallet_address_type();
 
         // 🔹 Étape 1 : Charger les seeds existantes si elles existent
         if seeds_path.exists() && keys_path.exists() {
-            match Wallet::load_many_from_files(&seeds_path, &keys_path, use_mainnet) {
+            match Wallet::load_many_from_files(&seeds_path, &keys_path, passphrase.as_deref(), use_mainnet, address_type) {
                 Ok(list) => {
-                    log::info!("♻️  WalletContainer: {} wallets existants chargés", list.len());
+                    log::info!("♻️  WalletContainer: {} wallets existants chargés ({:?})", list.len(), address_type);
                     wallets = list;
                 }
-                Err(e) => log::warn!("⚠️ WalletContainer: impossible de charger les fichiers existants: {}", e),
+                // Ne pas continuer : compléter puis sauvegarder écraserait les wallets existants
+                Err(e) => {
+                    return Err(format!("WalletContainer: impossible de charger les fichiers existants: {}", e).into())
+                }
             }
         }
 
@@ -64,9 +297,7 @@ impl WalletContainer {
         if existing < max_wallets {
             let to_generate = max_wallets - existing;
             log::info!("🪙 Génération de {} nouveaux wallets (déjà {} existants)", to_generate, existing);
-            for _ in 0..to_generate {
-                wallets.push(Wallet::generate(use_mainnet));
-            }
+            wallets.extend(Wallet::generate_many(to_generate, use_mainnet, MnemonicLength::default(), address_type));
         } else if existing > max_wallets {
             log::warn!(
                 "⚠️ {} wallets existants mais max_wallets={} — aucun n’est supprimé (préservation)",
@@ -75,12 +306,34 @@ impl WalletContainer {
             );
         }
 
-        let container = Self {
-            wallets: Arc::new(RwLock::new(wallets)),
-            seeds_path,
-            keys_path,
-            use_mainnet,
-        };
+        // 🔹 Registre global : pas deux fois la même adresse entre instances
+        if let Some(registry) = registry {
+            let regenerate = || Wallet::generate(use_mainnet, MnemonicLength::default(), address_type);
+            registry
+                .claim(&seeds_path, &mut wallets, existing, regenerate)
+                .map_err(|e| format!("WalletContainer: registre {:?}: {}", registry.path, e))?;
+        }
+
+        let container = Self::new(wallets, seeds_path, keys_path, use_mainnet, address_type);
+
+        // 🔹 Vérification seeds ↔ adresses (bloquante seulement si WALLET_INTEGRITY_STRICT=true)
+        let integrity_errors = container.verify_integrity();
+        if !integrity_errors.is_empty() {
+            for e in &integrity_errors {
+                log::warn!("⚠️ WalletContainer: intégrité — {}", e);
+            }
+            let strict = std::env::var("WALLET_INTEGRITY_STRICT")
+                .map(|v| v.to_lowercase() == "true")
+                .unwrap_or(false);
+            if strict {
+                return Err(format!(
+                    "WalletContainer: {} incohérence(s) dans {:?}",
+                    integrity_errors.len(),
+                    container.seeds_path
+                )
+                .into());
+            }
+        }
 
         // 🔹 Étape 3 : Sauvegarder seulement si ajout de nouveaux wallets
         if existing < max_wallets {
@@ -92,48 +345,131 @@ impl WalletContainer {
     }
 
     /// Sauvegarde atomique et protégée par lock
+    ///
+    /// Le lock (`FileLock` sur `<seeds>.lock`) est relâché quelle que soit l'issue,
+    /// et par l'OS si le process meurt pendant la sauvegarde.
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let lock_path = self.seeds_path.with_extension("lock");
-
-        // Essayer d'obtenir le lock avec retry
-        let start = Instant::now();
-        let mut got_lock = OpenOptions::new().write(true).create_new(true).open(&lock_path).is_ok();
-        while !got_lock && start.elapsed() < Duration::from_secs(5) {
-            got_lock = OpenOptions::new().write(true).create_new(true).open(&lock_path).is_ok();
-            if !got_lock {
-                sleep(Duration::from_millis(100));
-            }
-        }
-
-        if !got_lock {
-            return Err(format!(
-                "WalletContainer: impossible d'obtenir le lock pour {:?}",
-                lock_path
-            )
-            .into());
-        }
+        let _lock = FileLock::acquire(&lock_path, Duration::from_secs(5))
+            .map_err(|e| format!("WalletContainer: {}", e))?;
 
         let wallets = self.wallets.read();
         let seeds: Vec<String> = wallets
             .iter()
-            .map(|w| w.mnemonic.clone().unwrap_or_default())
+            .map(|w| w.mnemonic.clone().unwrap_or_else(|| NO_MNEMONIC_MARKER.to_string()))
             .collect();
         let keys: Vec<String> = wallets.iter().map(|w| w.signing_key_hex()).collect();
 
         let seeds_tmp = self.seeds_path.with_extension("tmp");
         let keys_tmp = self.keys_path.with_extension("tmp");
 
-        fs::write(&seeds_tmp, seeds.join("\n"))?;
-        fs::write(&keys_tmp, keys.join("\n"))?;
+        // Chiffrés si WALLET_PASSPHRASE est défini, sinon en clair (comportement historique)
+        let passphrase = wallet_passphrase();
+        write_secret_file(&seeds_tmp, &seeds.join("\n"), passphrase.as_deref())
+            .map_err(|e| e.to_string())?;
+        write_secret_file(&keys_tmp, &keys.join("\n"), passphrase.as_deref())
+            .map_err(|e| e.to_string())?;
 
         fs::rename(&seeds_tmp, &self.seeds_path)?;
         fs::rename(&keys_tmp, &self.keys_path)?;
 
-        let _ = fs::remove_file(&lock_path);
+        Ok(())
+    }
+
+    /// Exporte seeds et clés dans une archive zip chiffrée (AES-256) protégée par `password`,
+    /// avec un `README.txt` décrivant le format, pour migrer les wallets sans transférer de fichiers en clair.
+    pub fn export_to_encrypted_zip(&self, dest_path: impl AsRef<Path>, password: &str) -> Result<(), WalletError> {
+        let backup_err = |e: &dyn fmt::Display| WalletError::Backup(e.to_string());
+        if password.is_empty() {
+            return Err(WalletError::Backup("mot de passe vide".to_string()));
+        }
+
+        let (seeds, keys) = {
+            let wallets = self.wallets.read();
+            let seeds: Vec<String> = wallets
+                .iter()
+                .map(|w| w.mnemonic.clone().unwrap_or_else(|| NO_MNEMONIC_MARKER.to_string()))
+                .collect();
+            let keys: Vec<String> = wallets.iter().map(|w| w.signing_key_hex()).collect();
+            (seeds.join("\n"), keys.join("\n"))
+        };
 
+        let file = fs::File::create(dest_path.as_ref()).map_err(|e| backup_err(&e))?;
+        let mut zip = ZipWriter::new(file);
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .with_aes_encryption(AesMode::Aes256, password);
+        for (name, contents) in [("seeds.txt", seeds.as_str()), ("keys.hex", keys.as_str()), ("README.txt", BACKUP_README)] {
+            zip.start_file(name, options).map_err(|e| backup_err(&e))?;
+            zip.write_all(contents.as_bytes()).map_err(|e| backup_err(&e))?;
+        }
+        zip.finish().map_err(|e| backup_err(&e))?;
+
+        log::info!("📦 WalletContainer: {} wallets exportés vers {:?}", self.len(), dest_path.as_ref());
         Ok(())
     }
 
+    /// Reconstruit un container depuis une archive de `export_to_encrypted_zip`.
+    /// Rien n'est écrit : `save` persistera vers `seeds.txt` / `keys.hex` du dossier de l'archive.
+    pub fn import_from_encrypted_zip(
+        src_path: impl AsRef<Path>,
+        password: &str,
+        use_mainnet: bool,
+    ) -> Result<Self, WalletError> {
+        let src_path = src_path.as_ref();
+        let backup_err = |e: &dyn fmt::Display| WalletError::Backup(e.to_string());
+
+        let file = fs::File::open(src_path).map_err(|e| backup_err(&e))?;
+        let mut archive = ZipArchive::new(file).map_err(|e| backup_err(&e))?;
+        let mut read_entry = |name: &str| -> Result<String, WalletError> {
+            let mut entry = archive
+                .by_name_decrypt(name, password.as_bytes())
+                .map_err(|e| WalletError::Backup(format!("{} : {}", name, e)))?;
+            let mut contents = String::new();
+            entry
+                .read_to_string(&mut contents)
+                .map_err(|e| WalletError::Backup(format!("{} : {}", name, e)))?;
+            Ok(contents)
+        };
+        let seeds = read_entry("seeds.txt")?;
+        let keys = read_entry("keys.hex")?;
+
+        let address_type = wallet_address_type();
+        let wallets = Wallet::parse_many(
+            &seeds,
+            &keys,
+            Path::new("seeds.txt"),
+            Path::new("keys.hex"),
+            use_mainnet,
+            address_type,
+        )
+        .map_err(|e| backup_err(&e))?;
+        log::info!("📦 WalletContainer: {} wallets importés depuis {:?}", wallets.len(), src_path);
+
+        let dir = src_path.parent().unwrap_or_else(|| Path::new("."));
+        Ok(Self::new(wallets, dir.join("seeds.txt"), dir.join("keys.hex"), use_mainnet, address_type))
+    }
+
+    /// Re-dérive l'adresse de chaque wallet depuis sa phrase mnémonique et la compare à l'adresse stockée.
+    /// Une phrase illisible est reportée comme `AddressMismatch` avec le message d'erreur en guise d'adresse dérivée.
+    /// Les wallets importés sans phrase (`-`) sont ignorés : leur clé est déjà contrôlée par `Wallet::parse_many`.
+    pub fn verify_integrity(&self) -> Vec<IntegrityError> {
+        self.read_all()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, wallet)| {
+                let phrase = wallet.mnemonic.as_deref()?;
+                let derived = Wallet::address_from_phrase(phrase, self.use_mainnet, self.address_type)
+                    .unwrap_or_else(|e| format!("<seed invalide: {}>", e));
+                (derived != wallet.address).then(|| IntegrityError::AddressMismatch {
+                    index,
+                    stored: wallet.address.clone(),
+                    derived,
+                })
+            })
+            .collect()
+    }
+
     pub fn get_random(&self) -> Option<Wallet> {
         let wallets = self.wallets.read();
         wallets.choose(&mut thread_rng()).cloned()
@@ -144,6 +480,101 @@ impl WalletContainer {
         wallets.get(idx).cloned()
     }
 
+    /// Surveille le dossier de `seeds_path` et recharge les wallets dès que le fichier des seeds
+    /// ou des clés est modifié (voir `reload_from_disk`).
+    ///
+    /// Le watcher vit dans un thread dédié pendant toute la durée du process. Les mineurs
+    /// en cours ne sont pas interrompus : ils relisent le container au cycle suivant.
+    pub fn watch_directory(self: &Arc<Self>) -> Result<(), std::io::Error> {
+        use notify::{EventKind, RecursiveMode, Watcher};
+
+        let dir = self
+            .seeds_path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).map_err(std::io::Error::other)?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(std::io::Error::other)?;
+        log::info!("👀 WalletContainer: surveillance de {:?}", dir);
+
+        let container = Arc::clone(self);
+        std::thread::Builder::new()
+            .name("wallet-watcher".to_string())
+            .spawn(move || {
+                // Le watcher doit vivre aussi longtemps que la boucle
+                let _watcher = watcher;
+                let watched = [container.seeds_path.clone(), container.keys_path.clone()];
+                let relevant = |res: &notify::Result<notify::Event>| {
+                    res.as_ref().is_ok_and(|event| {
+                        matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_))
+                            && event.paths.iter().any(|p| watched.iter().any(|w| p.ends_with(w.file_name().unwrap_or_default())))
+                    })
+                };
+                while let Ok(event) = rx.recv() {
+                    if !relevant(&event) {
+                        continue;
+                    }
+                    // Regroupe les évènements d'une même écriture (seeds puis clés, fichiers .tmp renommés)
+                    sleep(Duration::from_millis(500));
+                    while rx.try_recv().is_ok() {}
+                    if let Err(e) = container.reload_from_disk() {
+                        log::warn!("⚠️ WalletContainer: rechargement ignoré, wallets actuels conservés: {}", e);
+                    }
+                }
+            })?;
+        Ok(())
+    }
+
+    /// Relit les fichiers seeds/keys et remplace la liste des wallets si elle a changé,
+    /// en journalisant chaque wallet ajouté ou retiré.
+    pub fn reload_from_disk(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let loaded = {
+            // Pas de lecture pendant une sauvegarde de ce process ou d'un autre
+            let _lock = FileLock::acquire(&self.seeds_path.with_extension("lock"), Duration::from_secs(5))?;
+            Wallet::load_many_from_files(
+                &self.seeds_path,
+                &self.keys_path,
+                wallet_passphrase().as_deref(),
+                self.use_mainnet,
+                self.address_type,
+            )?
+        };
+
+        let mut wallets = self.write();
+        let before: HashSet<String> = wallets.iter().map(|w| w.address.clone()).collect();
+        let after: HashSet<String> = loaded.iter().map(|w| w.address.clone()).collect();
+        if before == after {
+            return Ok(());
+        }
+        for addr in after.difference(&before) {
+            log::info!("➕ WalletContainer: wallet ajouté {}", addr);
+        }
+        for addr in before.difference(&after) {
+            log::info!("➖ WalletContainer: wallet retiré {}", addr);
+        }
+        *wallets = loaded;
+        Ok(())
+    }
+
+    /// Recherche O(1) d'un wallet par adresse
+    pub fn get_by_address(&self, addr: &str) -> Option<Wallet> {
+        // `wallets` d'abord : l'index n'est modifié que sous son write lock
+        let wallets = self.wallets.read();
+        let idx = *self.address_index.read().get(addr)?;
+        wallets.get(idx).cloned()
+    }
+
+    /// Verrou en écriture sur les wallets ; l'index par adresse est reconstruit à sa libération
+    pub fn write(&self) -> WalletsWriteGuard<'_> {
+        WalletsWriteGuard {
+            wallets: self.wallets.write(),
+            index: &self.address_index,
+        }
+    }
+
     pub fn len(&self) -> usize {
         self.wallets.read().len()
     }
@@ -154,8 +585,141 @@ impl WalletContainer {
 
     pub fn push_and_save(&self, w: Wallet) -> Result<(), Box<dyn std::error::Error>> {
         {
-            self.wallets.write().push(w);
+            self.write().push(w);
         }
+        self.auto_snapshot();
         self.save()
     }
+
+    /// Copie `seeds.txt` et `keys.hex` (tels quels, chiffrés ou non) vers
+    /// `backup_dir/<horodatage ISO 8601>/seeds.txt.bak` et `keys.hex.bak`.
+    ///
+    /// Les copies sont écrites dans un dossier temporaire renommé une fois complet :
+    /// un snapshot listé est toujours entier. Retourne le dossier du snapshot.
+    pub fn snapshot(&self, backup_dir: impl AsRef<Path>) -> Result<PathBuf, std::io::Error> {
+        let backup_dir = backup_dir.as_ref();
+        let name = Utc::now().format(SNAPSHOT_DIR_FORMAT).to_string();
+        let snapshot_dir = backup_dir.join(&name);
+        let tmp_dir = backup_dir.join(format!(".{}.tmp", name));
+        fs::create_dir_all(&tmp_dir)?;
+
+        let copied = {
+            // Pas de copie pendant une sauvegarde de ce process ou d'un autre
+            let _lock = FileLock::acquire(&self.seeds_path.with_extension("lock"), Duration::from_secs(5))?;
+            fs::copy(&self.seeds_path, tmp_dir.join("seeds.txt.bak"))
+                .and_then(|_| fs::copy(&self.keys_path, tmp_dir.join("keys.hex.bak")))
+        };
+        if let Err(e) = copied.and_then(|_| fs::rename(&tmp_dir, &snapshot_dir)) {
+            let _ = fs::remove_dir_all(&tmp_dir);
+            return Err(e);
+        }
+        log::info!("🗂️ WalletContainer: snapshot des wallets dans {:?}", snapshot_dir);
+        Ok(snapshot_dir)
+    }
+
+    /// Snapshots complets de `backup_dir`, du plus ancien au plus récent
+    pub fn list_snapshots(backup_dir: impl AsRef<Path>) -> Vec<(DateTime<Utc>, PathBuf)> {
+        let mut snapshots: Vec<(DateTime<Utc>, PathBuf)> = fs::read_dir(backup_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                let taken_at = NaiveDateTime::parse_from_str(name.to_str()?, SNAPSHOT_DIR_FORMAT).ok()?;
+                let path = entry.path();
+                (path.join("seeds.txt.bak").is_file() && path.join("keys.hex.bak").is_file())
+                    .then(|| (taken_at.and_utc(), path))
+            })
+            .collect();
+        snapshots.sort();
+        snapshots
+    }
+
+    /// Recharge les wallets d'un snapshot (`WALLET_PASSPHRASE` si les fichiers sont chiffrés).
+    /// Rien n'est écrit : `save` persistera vers `seeds.txt` / `keys.hex` du dossier du snapshot.
+    pub fn restore_snapshot(snapshot_path: impl AsRef<Path>, use_mainnet: bool) -> Result<Self, WalletError> {
+        let snapshot_path = snapshot_path.as_ref();
+        let address_type = wallet_address_type();
+        let wallets = Wallet::load_many_from_files(
+            &snapshot_path.join("seeds.txt.bak"),
+            &snapshot_path.join("keys.hex.bak"),
+            wallet_passphrase().as_deref(),
+            use_mainnet,
+            address_type,
+        )
+        .map_err(|e| WalletError::Backup(format!("snapshot {:?} : {}", snapshot_path, e)))?;
+        log::info!("🗂️ WalletContainer: {} wallets restaurés depuis {:?}", wallets.len(), snapshot_path);
+
+        Ok(Self::new(
+            wallets,
+            snapshot_path.join("seeds.txt"),
+            snapshot_path.join("keys.hex"),
+            use_mainnet,
+            address_type,
+        ))
+    }
+
+    /// Snapshot avant réécriture des fichiers, dans `WALLET_BACKUP_DIR` (`<dossier des seeds>/backups`
+    /// par défaut), en ne gardant que les `WALLET_SNAPSHOT_KEEP` (10) plus récents ; 0 désactive.
+    /// Un échec est journalisé sans bloquer la sauvegarde.
+    fn auto_snapshot(&self) {
+        let keep = std::env::var("WALLET_SNAPSHOT_KEEP")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(10);
+        if keep == 0 || !(self.seeds_path.exists() && self.keys_path.exists()) {
+            return;
+        }
+        let backup_dir = std::env::var("WALLET_BACKUP_DIR")
+            .ok()
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| self.seeds_path.parent().unwrap_or_else(|| Path::new(".")).join("backups"));
+
+        if let Err(e) = self.snapshot(&backup_dir) {
+            log::error!("❌ WalletContainer: snapshot impossible dans {:?}: {}", backup_dir, e);
+            return;
+        }
+        let snapshots = Self::list_snapshots(&backup_dir);
+        for (_, old) in snapshots.iter().take(snapshots.len().saturating_sub(keep)) {
+            if let Err(e) = fs::remove_dir_all(old) {
+                log::warn!("⚠️ WalletContainer: ancien snapshot {:?} non supprimé: {}", old, e);
+            }
+        }
+    }
+
+    /// Ajoute un wallet importé (clé secrète, `payment.skey`...) et sauvegarde.
+    /// Retourne `false` sans rien écrire si son adresse est déjà dans le container.
+    pub fn import_wallet(&self, wallet: Wallet) -> Result<bool, Box<dyn std::error::Error>> {
+        if self.get_by_address(&wallet.address).is_some() {
+            return Ok(false);
+        }
+        self.push_and_save(wallet)?;
+        Ok(true)
+    }
+
+    /// Retire le wallet d'indice `idx` et réécrit les fichiers seeds/keys.
+    pub fn remove_by_index(&self, idx: usize) -> Option<Wallet> {
+        self.remove_and_save(|wallets| (idx < wallets.len()).then_some(idx))
+    }
+
+    /// Retire le wallet d'adresse `addr` et réécrit les fichiers seeds/keys.
+    pub fn remove_by_address(&self, addr: &str) -> Option<Wallet> {
+        self.remove_and_save(|wallets| wallets.iter().position(|w| w.address == addr))
+    }
+
+    /// Retire (sous write lock) le wallet désigné par `find`, puis sauvegarde toujours.
+    /// Un échec de sauvegarde est journalisé ; le wallet reste retiré en mémoire.
+    fn remove_and_save(&self, find: impl FnOnce(&[Wallet]) -> Option<usize>) -> Option<Wallet> {
+        let removed = {
+            let mut wallets = self.write();
+            let idx = find(&wallets)?;
+            wallets.remove(idx)
+        };
+        self.auto_snapshot();
+        if let Err(e) = self.save() {
+            log::error!("❌ WalletContainer: retrait de {} non persisté: {}", removed.address, e);
+        }
+        Some(removed)
+    }
 }
@@ -0,0 +1,44 @@
+// tests/cip30_verify.rs
+//! `Wallet::verify_cip30` : aller-retour signature / vérification sur des wallets générés,
+//! puis rejet d'un payload modifié, d'une autre clé publique ou d'une autre adresse.
+
+use scavenger_miner::wallet::{AddressType, MnemonicLength, Wallet};
+
+const MESSAGE: &str = "Assign accumulated Scavenger rights to: addr_test1destination";
+
+#[test]
+fn sign_then_verify_round_trip() {
+    for address_type in [AddressType::KeyHash, AddressType::Enterprise, AddressType::Base] {
+        let wallet = Wallet::generate(false, MnemonicLength::Words12, address_type);
+        let pubkey = wallet.public_key_hex();
+
+        let cip30 = wallet.sign_cip30(MESSAGE);
+        assert!(Wallet::verify_cip30(&wallet.address, &pubkey, &cip30).unwrap(), "{:?}", address_type);
+        let cip8 = wallet.sign_cip8(MESSAGE, &[]).unwrap();
+        assert!(Wallet::verify_cip30(&wallet.address, &pubkey, &cip8).unwrap(), "{:?}", address_type);
+    }
+}
+
+#[test]
+fn tampered_payload_is_rejected() {
+    let wallet = Wallet::generate(false, MnemonicLength::Words12, AddressType::KeyHash);
+    let pubkey = wallet.public_key_hex();
+
+    // Dernier octet du message modifié dans le CBOR, signature inchangée
+    let cose = wallet.sign_cip30(MESSAGE);
+    let tampered = cose.replace(&hex::encode("destination"), &hex::encode("destinatioN"));
+    assert_ne!(tampered, cose);
+    assert!(!Wallet::verify_cip30(&wallet.address, &pubkey, &tampered).unwrap());
+}
+
+#[test]
+fn other_key_or_address_is_rejected() {
+    let wallet = Wallet::generate(false, MnemonicLength::Words12, AddressType::KeyHash);
+    let other = Wallet::generate(false, MnemonicLength::Words12, AddressType::KeyHash);
+    let cose = wallet.sign_cip8(MESSAGE, &[]).unwrap();
+
+    // Clé qui ne correspond pas au credential de l'adresse
+    assert!(!Wallet::verify_cip30(&wallet.address, &other.public_key_hex(), &cose).unwrap());
+    // Adresse du header protégé différente de l'adresse annoncée
+    assert!(!Wallet::verify_cip30(&other.address, &other.public_key_hex(), &cose).unwrap());
+}
@@ -0,0 +1,132 @@
+// tests/cip8_signing.rs
+//! Structure COSE_Sign1 produite par `sign_cip8` / `sign_cip30` (CBOR assemblé à la main dans wallet.rs) :
+//! forme du tableau, signature vérifiée sur le Sig_structure reconstruit et valeurs de référence.
+//!
+//! Clé fixe : vecteur de test 1 de la RFC 8032 (ed25519), adresse testnet. Les valeurs de référence
+//! ont été calculées indépendamment (ed25519 déterministe, CBOR canonique).
+
+use ciborium::value::{Integer, Value};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+use scavenger_miner::wallet::Wallet;
+
+const SECRET_KEY_HEX: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+const PUBLIC_KEY_HEX: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+/// Header entreprise testnet (`0x60`) + blake2b-224 de la clé publique
+const ADDRESS_BYTES_HEX: &str = "6035dedd2982a03cf39e7dce03c839994ffdec2ec6b04f1cf2d40e61a3";
+const MESSAGE: &str = "I agree to the terms";
+
+const GOLDEN_CIP8: &str = "84582aa201276761646472657373581d6035dedd2982a03cf39e7dce03c839994ffdec2ec6b04f1cf2d40e61a3a054\
+4920616772656520746f20746865207465726d735840f430f8e79029edb1db126357aa183c0b52b763e23b40886b1888ffe429ce7b415e8af24db7ede9\
+23482d41e530b71cff6b870f0dec66c52dc5bdee200ea9d103";
+const GOLDEN_CIP30: &str = "8443a10127a0544920616772656520746f20746865207465726d735840cdf10174f39561918d02883d71856de80c73\
+20573db450ed01a05c63debde20c9c41ca978443f5c3e27b4c9397a7f5cc5ddb4d21979091ac5b355c726de0990b";
+
+fn fixed_wallet() -> Wallet {
+    Wallet::from_secret_hex(SECRET_KEY_HEX, false).expect("clé de test valide")
+}
+
+/// Décode un COSE_Sign1 hex et vérifie sa forme `[protected (bstr), {} , payload (bstr), signature (bstr)]`
+fn decode_cose_sign1(cose_hex: &str) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let bytes = hex::decode(cose_hex).expect("hex valide");
+    let value: Value = ciborium::de::from_reader(bytes.as_slice()).expect("CBOR valide");
+    let items = match value {
+        Value::Array(items) => items,
+        other => panic!("COSE_Sign1 doit être un tableau, obtenu {:?}", other),
+    };
+    assert_eq!(items.len(), 4, "COSE_Sign1 : 4 éléments attendus");
+    match (&items[0], &items[1], &items[2], &items[3]) {
+        (Value::Bytes(protected), Value::Map(unprotected), Value::Bytes(payload), Value::Bytes(signature)) => {
+            assert!(unprotected.is_empty(), "header non protégé vide attendu");
+            assert_eq!(signature.len(), 64, "signature ed25519 de 64 octets attendue");
+            (protected.clone(), payload.clone(), signature.clone())
+        }
+        other => panic!("éléments du COSE_Sign1 inattendus : {:?}", other),
+    }
+}
+
+/// Décode le header protégé en paires (clé, valeur)
+fn protected_entries(protected: &[u8]) -> Vec<(Value, Value)> {
+    match ciborium::de::from_reader(protected).expect("header protégé CBOR valide") {
+        Value::Map(entries) => entries,
+        other => panic!("header protégé : map attendue, obtenu {:?}", other),
+    }
+}
+
+/// Vérifie la signature sur `Sig_structure = ["Signature1", protected, external_aad (vide), payload]`
+fn assert_signature_valid(protected: &[u8], payload: &[u8], signature: &[u8]) {
+    let sig_structure = Value::Array(vec![
+        Value::Text("Signature1".into()),
+        Value::Bytes(protected.to_vec()),
+        Value::Bytes(Vec::new()),
+        Value::Bytes(payload.to_vec()),
+    ]);
+    let mut to_verify = Vec::new();
+    ciborium::ser::into_writer(&sig_structure, &mut to_verify).expect("sérialisation du Sig_structure");
+
+    let pubkey: [u8; 32] = hex::decode(PUBLIC_KEY_HEX).unwrap().try_into().unwrap();
+    let verifying_key = VerifyingKey::from_bytes(&pubkey).expect("clé publique valide");
+    let signature = Signature::from_slice(signature).expect("signature de 64 octets");
+    verifying_key
+        .verify(&to_verify, &signature)
+        .expect("signature valide sur le Sig_structure reconstruit");
+}
+
+#[test]
+fn fixed_wallet_matches_rfc8032_key() {
+    let wallet = fixed_wallet();
+    assert_eq!(wallet.public_key_hex(), PUBLIC_KEY_HEX);
+    assert_eq!(hex::encode(wallet.address_bytes().unwrap()), ADDRESS_BYTES_HEX);
+}
+
+#[test]
+fn cip30_cose_sign1_structure_and_signature() {
+    let cose = fixed_wallet().sign_cip30(MESSAGE);
+    let (protected, payload, signature) = decode_cose_sign1(&cose);
+
+    assert_eq!(
+        protected_entries(&protected),
+        vec![(Value::Integer(Integer::from(1)), Value::Integer(Integer::from(-8)))],
+        "header protégé : alg = EdDSA (-8) seul"
+    );
+    assert_eq!(payload, MESSAGE.as_bytes());
+    assert_signature_valid(&protected, &payload, &signature);
+}
+
+#[test]
+fn cip8_cose_sign1_structure_and_signature() {
+    let wallet = fixed_wallet();
+    let cose = wallet.sign_cip8(MESSAGE, &[]).expect("signature CIP-8");
+    let (protected, payload, signature) = decode_cose_sign1(&cose);
+
+    assert_eq!(
+        protected_entries(&protected),
+        vec![
+            (Value::Integer(Integer::from(1)), Value::Integer(Integer::from(-8))),
+            (Value::Text("address".into()), Value::Bytes(hex::decode(ADDRESS_BYTES_HEX).unwrap())),
+        ],
+        "header protégé : alg = EdDSA (-8) puis adresse"
+    );
+    assert_eq!(payload, MESSAGE.as_bytes());
+    assert_signature_valid(&protected, &payload, &signature);
+}
+
+#[test]
+fn signatures_match_golden_values() {
+    let wallet = fixed_wallet();
+    assert_eq!(wallet.sign_cip8(MESSAGE, &[]).unwrap(), GOLDEN_CIP8);
+    assert_eq!(wallet.sign_cip30(MESSAGE), GOLDEN_CIP30);
+}
+
+#[test]
+fn verify_cip30_accepts_both_formats_and_rejects_other_messages() {
+    let wallet = fixed_wallet();
+    let cip8 = wallet.sign_cip8(MESSAGE, &[]).unwrap();
+    let cip30 = wallet.sign_cip30(MESSAGE);
+    assert!(Wallet::verify_cip30(&wallet.address, PUBLIC_KEY_HEX, &cip8).unwrap());
+    assert!(Wallet::verify_cip30(&wallet.address, PUBLIC_KEY_HEX, &cip30).unwrap());
+
+    let other = wallet.sign_cip30("Assign accumulated Scavenger rights to: addr_test1...");
+    let tampered = other.replace(&hex::encode("Assign"), &hex::encode("Assiga"));
+    assert!(!Wallet::verify_cip30(&wallet.address, PUBLIC_KEY_HEX, &tampered).unwrap());
+}
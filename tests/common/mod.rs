@@ -0,0 +1,95 @@
+// tests/common/mod.rs
+//! Serveur Scavenger simulé (httpmock), partagé par les tests d'intégration : chaque méthode
+//! monte un endpoint de l'API (`/TandC`, `/register/...`, `/challenge`, `/solution/...`) et
+//! retourne le `Mock` pour vérifier qu'il a bien été appelé.
+
+#![allow(dead_code)] // chaque test n'utilise qu'une partie des endpoints
+
+use httpmock::prelude::*;
+use httpmock::Mock;
+use serde_json::json;
+
+use scavenger_miner::api_client::ApiClient;
+
+/// Message des conditions d'utilisation renvoyé par `GET /TandC`
+pub const TERMS_MESSAGE: &str = "I agree to the terms";
+
+pub struct ScavengerServer {
+    pub server: MockServer,
+}
+
+impl ScavengerServer {
+    pub async fn start() -> Self {
+        Self { server: MockServer::start_async().await }
+    }
+
+    /// Client API pointé sur le serveur simulé
+    pub fn client(&self) -> ApiClient {
+        ApiClient::builder().base_url(&self.server.base_url()).build().expect("client API")
+    }
+
+    /// `GET /TandC` : conditions d'utilisation, message `TERMS_MESSAGE`
+    pub async fn mock_terms(&self) -> Mock<'_> {
+        self.server
+            .mock_async(|when, then| {
+                when.method(GET).path("/TandC");
+                then.status(200).json_body(json!({
+                    "version": "1-0",
+                    "content": "Terms of use",
+                    "message": TERMS_MESSAGE,
+                }));
+            })
+            .await
+    }
+
+    /// `POST /register/{address}/{signature}/{pubkey}` : reçu d'enregistrement
+    pub async fn mock_register(&self, address: &str, signature: &str, pubkey: &str) -> Mock<'_> {
+        let path = format!("/register/{}/{}/{}", address, signature, pubkey);
+        self.server
+            .mock_async(|when, then| {
+                when.method(POST).path(path);
+                then.status(201).json_body(json!({
+                    "registrationReceipt": {
+                        "preimage": TERMS_MESSAGE,
+                        "signature": "receipt-signature",
+                        "timestamp": "2025-11-01T00:00:00Z",
+                    }
+                }));
+            })
+            .await
+    }
+
+    /// `GET /challenge` : challenge actif `challenge_id`, de difficulté `difficulty`
+    pub async fn mock_challenge(&self, challenge_id: &str, difficulty: &str) -> Mock<'_> {
+        let body = json!({
+            "code": "active",
+            "challenge": {
+                "challenge_id": challenge_id,
+                "day": 1,
+                "challenge_number": 1,
+                "issued_at": "2025-11-01T00:00:00Z",
+                "latest_submission": "2025-11-02T00:00:00Z",
+                "difficulty": difficulty,
+                "no_pre_mine": "e8a195800b",
+                "no_pre_mine_hour": "123456789",
+            }
+        });
+        self.server
+            .mock_async(|when, then| {
+                when.method(GET).path("/challenge");
+                then.status(200).json_body(body);
+            })
+            .await
+    }
+
+    /// `POST /solution/{address}/{challenge_id}/{nonce}` : solution acceptée
+    pub async fn mock_solution(&self, address: &str, challenge_id: &str, nonce: &str) -> Mock<'_> {
+        let path = format!("/solution/{}/{}/{}", address, challenge_id, nonce);
+        self.server
+            .mock_async(|when, then| {
+                when.method(POST).path(path);
+                then.status(200).json_body(json!({"statusCode": 200, "message": "Solution accepted"}));
+            })
+            .await
+    }
+}
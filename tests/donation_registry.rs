@@ -0,0 +1,88 @@
+// tests/donation_registry.rs
+//! Registre JSON des donations partagé entre instances : sauvegardes concurrentes fusionnées
+//! sous verrou, sans qu'une instance n'écrase les donations de l'autre ; export / import CSV.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use chrono::{TimeZone, Utc};
+
+use scavenger_miner::donations::{DonationError, DonationRegistry};
+
+/// Dossier propre au test, vidé au départ
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("scavenger-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn concurrent_saves_keep_both_entries() {
+    let dir = test_dir("registry-race");
+    let path = dir.join("donations_log.json");
+    let barrier = Arc::new(Barrier::new(2));
+
+    let handles: Vec<_> = ["addr_test1wallet_a", "addr_test1wallet_b"]
+        .into_iter()
+        .map(|orig| {
+            let (path, barrier) = (path.clone(), Arc::clone(&barrier));
+            thread::spawn(move || {
+                // Chaque « instance » part du même registre vide
+                let mut registry = DonationRegistry::load(&path);
+                registry.mark_done(orig, "addr_test1destination");
+                barrier.wait();
+                registry.save(&path);
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let saved = DonationRegistry::load(&path);
+    assert_eq!(saved.completed_count(), 2);
+    assert!(saved.already_done("addr_test1wallet_a", "addr_test1destination").unwrap());
+    assert!(saved.already_done("addr_test1wallet_b", "addr_test1destination").unwrap());
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn csv_export_import_round_trip() {
+    let mut registry = DonationRegistry::default();
+    let first = Utc.with_ymd_and_hms(2025, 11, 2, 8, 30, 0).unwrap();
+    let second = Utc.with_ymd_and_hms(2025, 11, 3, 12, 0, 0).unwrap();
+    registry.mark_done_at("addr_test1wallet_b", "addr_test1destination", second);
+    registry.mark_done_at("addr_test1wallet_a", "addr_test1destination", first);
+
+    let mut csv = Vec::new();
+    registry.export_csv(&mut csv).unwrap();
+    let text = String::from_utf8(csv.clone()).unwrap();
+    // En-tête puis lignes triées par date croissante
+    assert_eq!(
+        text.lines().collect::<Vec<_>>(),
+        [
+            "original_wallet,destination_address,timestamp",
+            "addr_test1wallet_a,addr_test1destination,2025-11-02T08:30:00+00:00",
+            "addr_test1wallet_b,addr_test1destination,2025-11-03T12:00:00+00:00",
+        ]
+    );
+
+    let imported = DonationRegistry::import_csv(csv.as_slice()).unwrap();
+    assert_eq!(imported.completed, registry.completed);
+}
+
+#[test]
+fn csv_import_reports_the_invalid_line() {
+    let csv = "original_wallet,destination_address,timestamp\n\
+               addr_test1wallet_a,addr_test1destination,2025-11-02T08:30:00+00:00\n\
+               addr_test1wallet_b,addr_test1destination,hier\n";
+    match DonationRegistry::import_csv(csv.as_bytes()) {
+        Err(DonationError::Parse { line, .. }) => assert_eq!(line, 3),
+        Err(e) => panic!("erreur inattendue : {}", e),
+        Ok(_) => panic!("date invalide acceptée"),
+    }
+}
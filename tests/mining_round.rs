@@ -0,0 +1,129 @@
+// tests/mining_round.rs
+//! Tour de minage complet contre un serveur Scavenger simulé (`common::ScavengerServer`) :
+//! conditions d'utilisation, enregistrement, challenge, minage puis soumission.
+//!
+//! La ROM est réduite (`MINE_ROM_SIZE_MB=1`) et le masque de difficulté laisse passer
+//! un hash sur 16 : le test tourne en quelques secondes, sans toucher au vrai serveur.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use scavenger_miner::miner::{
+    benchmark_challenge, get_or_create_rom, hash_params, mine, mine_with_deadline, rom_seed, BlockNonceGenerator,
+    MinerConfig, MinerError,
+};
+use scavenger_miner::wallet::{AddressType, MnemonicLength, Wallet};
+
+mod common;
+use common::{ScavengerServer, TERMS_MESSAGE};
+
+const CHALLENGE_ID: &str = "**D01C01";
+/// Un hash sur 16 passe : les 4 premiers bits doivent être nuls
+const DIFFICULTY: &str = "0fffffff";
+
+#[tokio::test]
+async fn full_mining_round_submits_a_valid_nonce() {
+    std::env::set_var("MINE_ROM_SIZE_MB", "1");
+
+    let server = ScavengerServer::start().await;
+    let client = server.client();
+    let wallet = Wallet::generate(false, MnemonicLength::Words12, AddressType::KeyHash);
+
+    // 1. Conditions d'utilisation
+    let terms_mock = server.mock_terms().await;
+    let terms = client.get_terms(None, None, None).await.expect("GET /TandC");
+    terms_mock.assert_async().await;
+    assert_eq!(terms.message, TERMS_MESSAGE);
+
+    // 2. Enregistrement de l'adresse avec la signature CIP-30 du message des conditions
+    let signature = wallet.sign_cip30(&terms.message);
+    let pubkey = wallet.public_key_hex();
+    let register_mock = server.mock_register(&wallet.address, &signature, &pubkey).await;
+    client
+        .register_address(&wallet.address, &signature, &pubkey, None, None)
+        .await
+        .expect("POST /register");
+    register_mock.assert_async().await;
+
+    // 3. Challenge de faible difficulté
+    let challenge_mock = server.mock_challenge(CHALLENGE_ID, DIFFICULTY).await;
+    let challenge = client
+        .get_challenge(None, None)
+        .await
+        .expect("GET /challenge")
+        .challenge
+        .expect("challenge actif");
+    challenge_mock.assert_async().await;
+    assert_eq!(challenge.challenge_id, CHALLENGE_ID);
+
+    // 4. Minage (threads bloquants, hors du runtime)
+    let config = MinerConfig::new(wallet.address.clone(), Arc::new(challenge.clone()));
+    let mining_config = config.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        mine(mining_config, 2, None, None, None, None, |_| Box::new(BlockNonceGenerator::new()))
+    })
+    .await
+    .expect("thread de minage")
+    .expect("nonce trouvé");
+
+    // Le nonce doit réellement respecter le masque de difficulté du challenge
+    let rom = get_or_create_rom(&rom_seed(&challenge).expect("seed de ROM"));
+    let (nb_loops, nb_instrs) = hash_params();
+    assert!(result.verify(&config, nb_loops, nb_instrs, &rom));
+    let digest = ashmaize::hash(result.preimage.as_bytes(), &rom, nb_loops, nb_instrs);
+    let prefix = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+    let mask = u32::from_str_radix(DIFFICULTY, 16).unwrap();
+    assert_eq!(prefix & !mask, 0, "hash {:08x} hors difficulté {}", prefix, DIFFICULTY);
+
+    // 5. Soumission : le serveur doit recevoir exactement ce nonce
+    let solution_mock = server.mock_solution(&wallet.address, CHALLENGE_ID, &result.nonce).await;
+    let submitted = client
+        .submit_solution(&wallet.address, CHALLENGE_ID, &result.nonce, None, None)
+        .await
+        .expect("POST /solution");
+    solution_mock.assert_async().await;
+    assert_eq!(submitted.message.as_deref(), Some("Solution accepted"));
+}
+
+/// Difficulté inatteignable : seul l'arrêt (échéance ou demande) termine le minage
+const IMPOSSIBLE_DIFFICULTY: &str = "00000000";
+
+#[test]
+fn deadline_stops_mining_with_deadline_exceeded() {
+    std::env::set_var("MINE_ROM_SIZE_MB", "1");
+    let challenge = benchmark_challenge(0, IMPOSSIBLE_DIFFICULTY);
+    let config = MinerConfig::new("addr_test1deadline".to_string(), Arc::new(challenge));
+
+    let result = mine_with_deadline(
+        config,
+        1,
+        None,
+        None,
+        None,
+        Arc::new(AtomicBool::new(false)),
+        Some(Duration::from_millis(200)),
+        |_| Box::new(BlockNonceGenerator::new()),
+    );
+    assert_eq!(result.err(), Some(MinerError::DeadlineExceeded));
+}
+
+#[test]
+fn shutdown_before_deadline_stays_cancelled() {
+    std::env::set_var("MINE_ROM_SIZE_MB", "1");
+    let challenge = benchmark_challenge(1, IMPOSSIBLE_DIFFICULTY);
+    let config = MinerConfig::new("addr_test1shutdown".to_string(), Arc::new(challenge));
+
+    // Arrêt demandé par l'appelant avant toute échéance
+    let result = mine_with_deadline(
+        config,
+        1,
+        None,
+        None,
+        None,
+        Arc::new(AtomicBool::new(true)),
+        Some(Duration::from_secs(3600)),
+        |_| Box::new(BlockNonceGenerator::new()),
+    );
+    assert_eq!(result.err(), Some(MinerError::Cancelled));
+}
@@ -0,0 +1,118 @@
+// tests/nonce_generators.rs
+//! Générateurs de nonces partagés entre threads : aucun nonce ne doit être visité deux fois,
+//! ni par deux threads d'un même curseur (`BlockNonceGenerator`), ni par deux partitions
+//! (`PartitionedNonceGenerator`, via `NonceStrategy::Partitioned`).
+
+use std::collections::HashSet;
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use std::thread;
+
+use scavenger_miner::miner::{BlockNonceGenerator, MinerError, NonceGenerator, NonceStrategy, PartitionedNonceGenerator};
+
+const THREADS: usize = 4;
+const NONCES_PER_THREAD: usize = 500;
+
+/// Tire `NONCES_PER_THREAD` nonces de chaque générateur, chacun dans son propre thread
+fn draw_in_threads(generators: Vec<Box<dyn NonceGenerator>>) -> Vec<Vec<u64>> {
+    let handles: Vec<_> = generators
+        .into_iter()
+        .map(|mut generator| thread::spawn(move || (0..NONCES_PER_THREAD).map(|_| generator.next()).collect()))
+        .collect();
+    handles.into_iter().map(|h| h.join().unwrap()).collect()
+}
+
+/// Vrai si aucun nonce n'apparaît deux fois, tous threads confondus
+fn all_distinct(drawn: &[Vec<u64>]) -> bool {
+    let mut seen = HashSet::new();
+    drawn.iter().flatten().all(|nonce| seen.insert(*nonce))
+}
+
+#[test]
+fn block_generators_on_a_shared_cursor_never_overlap() {
+    // Petits blocs : les threads se disputent le curseur des centaines de fois
+    let cursor = Arc::new(AtomicU64::new(0));
+    let generators = (0..THREADS)
+        .map(|_| Box::new(BlockNonceGenerator::with_cursor(Arc::clone(&cursor), 3)) as Box<dyn NonceGenerator>)
+        .collect();
+    let drawn = draw_in_threads(generators);
+
+    assert!(all_distinct(&drawn));
+    // 167 blocs de 3 par thread, réservés à la suite depuis 0
+    let reserved = (THREADS * NONCES_PER_THREAD.div_ceil(3) * 3) as u64;
+    let max = drawn.iter().flatten().copied().max().unwrap();
+    assert!(max < reserved, "nonce {} hors des blocs réservés", max);
+}
+
+#[test]
+fn block_generator_wraps_around_the_cursor() {
+    let cursor = Arc::new(AtomicU64::new(u64::MAX - 1));
+    let mut generator = BlockNonceGenerator::with_cursor(cursor, 4);
+    let drawn: Vec<u64> = (0..4).map(|_| generator.next()).collect();
+    assert_eq!(drawn, [u64::MAX - 1, u64::MAX, 0, 1]);
+}
+
+#[test]
+fn partitioned_generators_never_overlap() {
+    // 3 process de 2 threads chacun, soit 6 plages
+    let (processes, threads_per_process) = (3u64, 2usize);
+    let generators: Vec<Box<dyn NonceGenerator>> = (0..processes)
+        .flat_map(|partition_id| {
+            let strategy = NonceStrategy::Partitioned { partition_id, total_partitions: processes };
+            (0..threads_per_process).map(move |index| strategy.generator(index, threads_per_process))
+        })
+        .collect();
+    let drawn = draw_in_threads(generators);
+
+    assert!(all_distinct(&drawn));
+    // Chaque thread reste dans sa plage, dans l'ordre process puis thread
+    for (range, nonces) in drawn.iter().enumerate() {
+        let mut reference = PartitionedNonceGenerator::new(range as u64, processes * threads_per_process as u64);
+        let expected: Vec<u64> = (0..NONCES_PER_THREAD).map(|_| reference.next()).collect();
+        assert_eq!(nonces, &expected, "plage {}", range);
+    }
+}
+
+#[test]
+fn last_partition_wraps_to_its_own_start() {
+    // Plages de 1 ou 2 nonces : le retour au début de plage est observable
+    let mut last = PartitionedNonceGenerator::new(u64::MAX - 1, u64::MAX);
+    let drawn: Vec<u64> = (0..5).map(|_| last.next()).collect();
+    assert_eq!(drawn, [u64::MAX - 1, u64::MAX, u64::MAX - 1, u64::MAX, u64::MAX - 1]);
+}
+
+/// `NonceStrategy::from_vars` sur une liste de variables fixe, sans toucher à l'environnement
+fn strategy_from(vars: &[(&str, &str)]) -> Result<NonceStrategy, MinerError> {
+    NonceStrategy::from_vars(|name| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string()))
+}
+
+#[test]
+fn nonce_strategy_reads_partition_settings() {
+    let strategy = strategy_from(&[
+        ("NONCE_STRATEGY", "partitioned"),
+        ("NONCE_PARTITION_ID", "2"),
+        ("NONCE_PARTITIONS", "3"),
+    ]);
+    assert!(matches!(strategy, Ok(NonceStrategy::Partitioned { partition_id: 2, total_partitions: 3 })));
+    assert!(matches!(strategy_from(&[]), Ok(NonceStrategy::Random)));
+}
+
+#[test]
+fn invalid_nonce_strategy_settings_are_rejected() {
+    let rejected: [&[(&str, &str)]; 5] = [
+        // Partition hors plage : deux process mineraient la même plage
+        &[("NONCE_STRATEGY", "partitioned"), ("NONCE_PARTITION_ID", "3"), ("NONCE_PARTITIONS", "3")],
+        &[("NONCE_STRATEGY", "partitioned"), ("NONCE_PARTITIONS", "0")],
+        &[("NONCE_STRATEGY", "partitioned"), ("NONCE_PARTITION_ID", "un")],
+        &[("NONCE_STRATEGY", "sequential"), ("NONCE_START", "-1")],
+        // Faute de frappe : pas de repli silencieux sur `random`
+        &[("NONCE_STRATEGY", "partitionned")],
+    ];
+    for vars in rejected {
+        assert!(
+            matches!(strategy_from(vars), Err(MinerError::InvalidConfig(_))),
+            "configuration acceptée : {:?}",
+            vars
+        );
+    }
+}
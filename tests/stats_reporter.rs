@@ -0,0 +1,69 @@
+// tests/stats_reporter.rs
+//! `StatsReporter` réactivé à chaud : tant que `ENABLE_STATS_BACKEND` vaut `false` les ticks
+//! passent sans rien envoyer, puis la mesure suivante part dès que le backend est activé.
+//! Clés par wallet de `wallet_rates` (`wallet_prefix`).
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use httpmock::prelude::*;
+use parking_lot::RwLock;
+use tokio_util::sync::CancellationToken;
+
+use scavenger_miner::api_client::ApiCallCounters;
+use scavenger_miner::stats_client::{wallet_prefix, StatsConfig, StatsReporter};
+
+const WALLET: &str = "addr_test1vqtestwallet";
+
+#[tokio::test]
+async fn reporter_sends_once_the_backend_is_enabled() {
+    std::env::set_var("ENABLE_STATS_BACKEND", "false");
+    std::env::set_var("STATS_PER_WALLET", "false");
+
+    let server = MockServer::start_async().await;
+    let stats_mock = server
+        .mock_async(|when, then| {
+            when.method(POST).path("/stats");
+            then.status(200);
+        })
+        .await;
+
+    let counter = Arc::new(AtomicU64::new(0));
+    let wallet_counters = Arc::new(RwLock::new(HashMap::from([(WALLET.to_string(), Arc::clone(&counter))])));
+    let reporter = StatsReporter::start(StatsConfig {
+        container_id: "container".to_string(),
+        miner_id: "miner-1".to_string(),
+        wallet_counters,
+        thread_counters: Arc::new(RwLock::new(Vec::new())),
+        api_counters: Arc::new(ApiCallCounters::default()),
+        server_url: format!("{}/stats", server.base_url()),
+        version: "test".to_string(),
+        report_interval_secs: 1,
+        shutdown: CancellationToken::new(),
+    });
+
+    // Deux ticks avec des hashs, backend désactivé : aucune mesure ne doit partir
+    for _ in 0..2 {
+        counter.fetch_add(1_000, Ordering::Release);
+        tokio::time::sleep(Duration::from_millis(1_100)).await;
+    }
+
+    // Réactivation à chaud : seule la mesure du tick suivant est envoyée
+    std::env::set_var("ENABLE_STATS_BACKEND", "true");
+    counter.fetch_add(1_000, Ordering::Release);
+    tokio::time::sleep(Duration::from_millis(2_500)).await;
+
+    reporter.shutdown().await;
+    stats_mock.assert_async().await;
+}
+
+#[test]
+fn wallet_prefix_keeps_wallets_of_one_network_apart() {
+    let first = "addr_test1qz2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3n0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgs68faae";
+    let second = "addr_test1qpu5vlrf4xkxv2qpwngf6cjhtw542ayty80v8dyr49rf5ewvxwdrt70qlcpeeagscasafhffqsxy36t90ldv06wqrk2qum8x5w";
+    assert_eq!(wallet_prefix(first), "addr_test1qz2fxv2umyht");
+    assert_ne!(wallet_prefix(first), wallet_prefix(second));
+    assert_eq!(wallet_prefix("addr1vx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzers66hrl8"), "addr1vx2fxv2umyht");
+}
@@ -0,0 +1,147 @@
+// tests/wallet_addresses.rs
+//! Adresses Shelley des wallets : adresse de récompense tirée de l'adresse base et
+//! validation des adresses de paiement par réseau, décodage d'une adresse corrompue.
+//!
+//! Vecteurs de référence : CIP-19 (adresse base type 0, clé de paiement et clé de staking),
+//! en mainnet et en testnet, dérivés de `CIP19_MNEMONIC` (compte 0, index 0).
+
+use std::path::Path;
+
+use scavenger_miner::wallet::{AddressError, AddressType, Wallet};
+
+/// Vecteur de test 1 de la RFC 8032 (ed25519)
+const SECRET_KEY_HEX: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+
+const CIP19_MNEMONIC: &str = "test walk nut penalty hip pave soap entry language right filter choice";
+const CIP19_BASE_MAINNET: &str =
+    "addr1qx2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3n0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgse35a3x";
+const CIP19_BASE_TESTNET: &str =
+    "addr_test1qz2fxv2umyhttkxyxp8x0dlpdt3k6cwng5pxj3jhsydzer3n0d3vllmyqwsx5wktcd8cc3sq835lu7drv2xwl2wywfgs68faae";
+const CIP19_STAKE_MAINNET: &str = "stake1uyehkck0lajq8gr28t9uxnuvgcqrc6070x3k9r8048z8y5gh6ffgw";
+const CIP19_STAKE_TESTNET: &str = "stake_test1uqehkck0lajq8gr28t9uxnuvgcqrc6070x3k9r8048z8y5gssrtvn";
+
+/// Wallet à clé fixe dont l'adresse base est remplacée par `shelley_addr`
+fn wallet_with_base(shelley_addr: &str) -> Wallet {
+    let mut wallet = Wallet::from_secret_hex(SECRET_KEY_HEX, false).expect("clé de test valide");
+    wallet.shelley_addr = shelley_addr.to_string();
+    wallet
+}
+
+#[test]
+fn stake_address_matches_cip19_vectors() {
+    assert_eq!(wallet_with_base(CIP19_BASE_MAINNET).stake_address().as_deref(), Some(CIP19_STAKE_MAINNET));
+    assert_eq!(wallet_with_base(CIP19_BASE_TESTNET).stake_address().as_deref(), Some(CIP19_STAKE_TESTNET));
+}
+
+#[test]
+fn stake_address_is_none_without_base_address() {
+    let wallet = Wallet::from_secret_hex(SECRET_KEY_HEX, false).expect("clé de test valide");
+    assert_eq!(wallet.stake_address(), None);
+}
+
+#[test]
+fn derive_stake_address_encodes_for_the_requested_network() {
+    let wallet = wallet_with_base(CIP19_BASE_MAINNET);
+    assert_eq!(wallet.derive_stake_address(true), CIP19_STAKE_MAINNET);
+    assert_eq!(wallet.derive_stake_address(false), CIP19_STAKE_TESTNET);
+
+    let without_base = Wallet::from_secret_hex(SECRET_KEY_HEX, false).expect("clé de test valide");
+    assert_eq!(without_base.derive_stake_address(true), "");
+}
+
+#[test]
+fn cip1852_matches_cip19_vectors() {
+    let mainnet = Wallet::generate_cip1852(CIP19_MNEMONIC, 0, 0, true).expect("dérivation CIP-1852");
+    assert_eq!(mainnet.address, CIP19_BASE_MAINNET);
+    assert_eq!(mainnet.stake_address().as_deref(), Some(CIP19_STAKE_MAINNET));
+
+    let testnet = Wallet::generate_cip1852(CIP19_MNEMONIC, 0, 0, false).expect("dérivation CIP-1852");
+    assert_eq!(testnet.address, CIP19_BASE_TESTNET);
+    assert_eq!(testnet.stake_address().as_deref(), Some(CIP19_STAKE_TESTNET));
+
+    // Même credential de staking que l'adresse base, quel que soit l'index de paiement
+    let other_index = Wallet::generate_cip1852(CIP19_MNEMONIC, 0, 1, true).expect("dérivation CIP-1852");
+    assert_ne!(other_index.address, mainnet.address);
+    assert_eq!(other_index.stake_address().as_deref(), Some(CIP19_STAKE_MAINNET));
+}
+
+#[test]
+fn cip1852_wallet_reloads_from_seeds_and_keys() {
+    let address_type: AddressType = "cip1852".parse().unwrap();
+    let wallet = Wallet::from_phrase(CIP19_MNEMONIC, true, address_type).expect("dérivation CIP-1852");
+    assert_eq!(wallet.address, CIP19_BASE_MAINNET);
+
+    // keys.hex contient la clé étendue (64 octets), re-dérivée depuis la phrase au rechargement
+    let key_hex = wallet.signing_key_hex();
+    assert_eq!(key_hex.len(), 128);
+    let reloaded =
+        Wallet::parse_many(CIP19_MNEMONIC, &key_hex, Path::new("seeds.txt"), Path::new("keys.hex"), true, address_type)
+            .expect("rechargement CIP-1852");
+    assert_eq!(reloaded.len(), 1);
+    assert_eq!(reloaded[0].address, CIP19_BASE_MAINNET);
+    assert_eq!(reloaded[0].public_key_hex(), wallet.public_key_hex());
+
+    // Une autre clé étendue sur la même ligne reste refusée
+    let other = Wallet::generate_cip1852(CIP19_MNEMONIC, 0, 1, true).expect("dérivation CIP-1852");
+    assert!(Wallet::parse_many(
+        CIP19_MNEMONIC,
+        &other.signing_key_hex(),
+        Path::new("seeds.txt"),
+        Path::new("keys.hex"),
+        true,
+        address_type
+    )
+    .is_err());
+}
+
+#[test]
+fn cip1852_address_type_parses_account_and_index() {
+    assert_eq!("cip1852:1:5".parse::<AddressType>(), Ok(AddressType::Cip1852 { account: 1, index: 5 }));
+    assert!("cip1852:1".parse::<AddressType>().is_err());
+}
+
+#[test]
+fn valid_addresses_for_their_network() {
+    assert!(Wallet::is_valid_bech32_address(CIP19_BASE_MAINNET, true));
+    assert!(Wallet::is_valid_bech32_address(CIP19_BASE_TESTNET, false));
+    // Adresse entreprise générée par le mineur, espaces autour tolérés
+    let wallet = Wallet::from_secret_hex(SECRET_KEY_HEX, false).expect("clé de test valide");
+    assert!(Wallet::is_valid_bech32_address(&format!(" {} ", wallet.address), false));
+}
+
+#[test]
+fn addresses_of_the_other_network_are_rejected() {
+    assert!(!Wallet::is_valid_bech32_address(CIP19_BASE_MAINNET, false));
+    assert!(!Wallet::is_valid_bech32_address(CIP19_BASE_TESTNET, true));
+}
+
+#[test]
+fn malformed_addresses_are_rejected() {
+    // Checksum altéré (dernier caractère)
+    let bad_checksum = format!("{}q", &CIP19_BASE_MAINNET[..CIP19_BASE_MAINNET.len() - 1]);
+    for addr in [
+        "",
+        "not an address",
+        bad_checksum.as_str(),
+        // Adresse de récompense : pas une adresse de paiement
+        CIP19_STAKE_MAINNET,
+        // Adresse base tronquée : checksum faux et longueur incohérente
+        &CIP19_BASE_MAINNET[..60],
+    ] {
+        assert!(!Wallet::is_valid_bech32_address(addr, true), "{:?}", addr);
+    }
+}
+
+#[test]
+fn garbage_address_bytes_is_an_error() {
+    let mut wallet = Wallet::from_secret_hex(SECRET_KEY_HEX, false).expect("clé de test valide");
+    wallet.address = "garbage!".to_string();
+    match wallet.address_bytes() {
+        Err(AddressError::Bech32 { address, .. }) => assert_eq!(address, "garbage!"),
+        other => panic!("erreur Bech32 attendue, obtenu {:?}", other),
+    }
+
+    // La signature CIP-8 remonte la même erreur, identifiable par les donations
+    let err = wallet.sign_cip8("message", &[]).unwrap_err();
+    assert!(err.is::<AddressError>(), "{}", err);
+}
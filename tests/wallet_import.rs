@@ -0,0 +1,40 @@
+// tests/wallet_import.rs
+//! Wallet importé depuis sa seule clé privée (`from_secret_hex`) : sauvegarde avec le marqueur `-`,
+//! rechargement puis vérification d'intégrité stricte (`WALLET_INTEGRITY_STRICT=true`).
+
+use std::fs;
+
+use scavenger_miner::wallet::{Wallet, NO_MNEMONIC_MARKER};
+use scavenger_miner::wallet_container::WalletContainer;
+
+/// Vecteur de test 1 de la RFC 8032 (ed25519)
+const SECRET_KEY_HEX: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+
+#[test]
+fn imported_wallet_passes_strict_integrity_after_reload() {
+    std::env::set_var("WALLET_INTEGRITY_STRICT", "true");
+    std::env::set_var("WALLET_SNAPSHOT_KEEP", "0");
+
+    let dir = std::env::temp_dir().join(format!("scavenger-wallet-import-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let seeds = dir.join("seeds.txt");
+    let keys = dir.join("keys.hex");
+
+    let container = WalletContainer::load_or_create(&seeds, &keys, false, 1, None).expect("container initial");
+    let imported = Wallet::from_secret_hex(SECRET_KEY_HEX, false).expect("clé de test valide");
+    let address = imported.address.clone();
+    assert!(container.import_wallet(imported).expect("import sauvegardé"));
+    assert!(container.verify_integrity().is_empty());
+
+    let saved_seeds = fs::read_to_string(&seeds).expect("seeds.txt");
+    assert_eq!(saved_seeds.lines().last(), Some(NO_MNEMONIC_MARKER));
+
+    // Rechargement : le wallet sans phrase ne doit pas bloquer le démarrage strict
+    let reloaded = WalletContainer::load_or_create(&seeds, &keys, false, 2, None).expect("rechargement strict");
+    assert_eq!(reloaded.len(), 2);
+    assert!(reloaded.verify_integrity().is_empty());
+    let wallet = reloaded.get_by_address(&address).expect("wallet importé rechargé");
+    assert!(wallet.mnemonic.is_none());
+
+    let _ = fs::remove_dir_all(&dir);
+}